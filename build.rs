@@ -0,0 +1,42 @@
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Capture build metadata for the /api/v0/version endpoint
+fn main() {
+    // Docker builds don't copy .git, so allow the SHA to be passed in
+    let git_sha = std::env::var("RAWORC_GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(|| command_output("git", &["rev-parse", "--short=12", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    let build_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    println!("cargo:rustc-env=RAWORC_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=RAWORC_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=RAWORC_BUILD_TIMESTAMP={}", build_timestamp);
+
+    println!("cargo:rerun-if-env-changed=RAWORC_GIT_SHA");
+    // Only watch git state when it exists; a missing path would force a rebuild every time
+    for path in [".git/HEAD", ".git/index"] {
+        if std::path::Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
@@ -0,0 +1,8 @@
+-- Client-supplied idempotency keys for session messages
+-- A retried POST carrying the same key returns the original message instead of inserting a duplicate
+
+ALTER TABLE session_messages ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_session_messages_idempotency_key
+    ON session_messages(session_id, idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
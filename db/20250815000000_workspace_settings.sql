@@ -0,0 +1,17 @@
+-- Per-workspace settings
+-- Workspaces are otherwise just names on other resources; a row here is optional and
+-- every column falls back to the global default when NULL
+
+CREATE TABLE IF NOT EXISTS workspace_settings (
+    workspace VARCHAR(255) PRIMARY KEY,
+    default_waiting_timeout_seconds INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT workspace_settings_workspace_check CHECK (workspace ~ '^[a-zA-Z0-9_.-]+$'),
+    CONSTRAINT workspace_settings_timeout_check CHECK (
+        default_waiting_timeout_seconds IS NULL OR default_waiting_timeout_seconds > 0
+    )
+);
+
+CREATE TRIGGER update_workspace_settings_updated_at BEFORE UPDATE ON workspace_settings
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
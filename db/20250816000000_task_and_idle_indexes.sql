@@ -0,0 +1,14 @@
+-- Indexes for the operator's task poll and the idle-timeout scan
+
+-- fetch_pending_tasks: WHERE status = 'pending' ORDER BY created_at
+CREATE INDEX IF NOT EXISTS idx_session_tasks_pending_created_at
+    ON session_tasks(created_at) WHERE status = 'pending';
+
+-- The original status index only covered pending rows; index every status for
+-- the processing/failed lookups as well
+DROP INDEX IF EXISTS idx_session_tasks_status;
+CREATE INDEX idx_session_tasks_status ON session_tasks(status);
+
+-- find_waiting_sessions_to_timeout: WHERE state = ... AND last_activity_at ... AND deleted_at IS NULL
+CREATE INDEX IF NOT EXISTS idx_sessions_state_last_activity
+    ON sessions(state, last_activity_at) WHERE deleted_at IS NULL;
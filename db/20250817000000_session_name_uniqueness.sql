@@ -0,0 +1,19 @@
+-- Session names are unique per owner within a workspace, ignoring soft-deleted sessions,
+-- so a name can be reused once the session holding it is deleted
+
+-- Disambiguate existing live duplicates, keeping the newest session's name unchanged
+UPDATE sessions s
+SET name = s.name || '-' || LEFT(s.id::text, 8)
+FROM (
+    SELECT id, ROW_NUMBER() OVER (
+        PARTITION BY workspace, created_by, name
+        ORDER BY created_at DESC
+    ) AS rn
+    FROM sessions
+    WHERE deleted_at IS NULL
+) ranked
+WHERE s.id = ranked.id AND ranked.rn > 1;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sessions_unique_active_name
+    ON sessions(workspace, created_by, name)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,23 @@
+-- Workspace-scoped secrets, encrypted at rest with RAWORC_SECRETS_KEY (AES-256-GCM)
+-- Plaintext is never stored and never returned by the API; it's only decrypted by the
+-- operator when injecting the secret into a session container's environment
+
+CREATE TABLE IF NOT EXISTS secrets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace VARCHAR(255) NOT NULL DEFAULT 'default',
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    nonce BYTEA NOT NULL,
+    ciphertext BYTEA NOT NULL,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT secrets_workspace_check CHECK (workspace ~ '^[a-zA-Z0-9_.-]+$'),
+    CONSTRAINT secrets_name_check CHECK (name ~ '^[A-Z_][A-Z0-9_]*$'),
+    CONSTRAINT secrets_unique_name_workspace UNIQUE(workspace, name)
+);
+
+CREATE INDEX idx_secrets_workspace ON secrets(workspace);
+
+CREATE TRIGGER update_secrets_updated_at BEFORE UPDATE ON secrets
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
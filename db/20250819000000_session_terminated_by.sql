@@ -0,0 +1,3 @@
+-- Who ended a session: the principal that deleted it or moved it to ERROR
+
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS terminated_by VARCHAR(255);
@@ -0,0 +1,17 @@
+-- Token usage reported by the host agent, one row per LLM turn
+-- Totals are aggregated on read; cost is the host's estimate and may be absent
+
+CREATE TABLE IF NOT EXISTS session_usage (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    agent_id UUID REFERENCES agents(id) ON DELETE SET NULL,
+    model VARCHAR(100),
+    prompt_tokens BIGINT NOT NULL,
+    completion_tokens BIGINT NOT NULL,
+    cost_usd DOUBLE PRECISION,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT session_usage_tokens_check CHECK (prompt_tokens >= 0 AND completion_tokens >= 0),
+    CONSTRAINT session_usage_cost_check CHECK (cost_usd IS NULL OR cost_usd >= 0)
+);
+
+CREATE INDEX idx_session_usage_session_id ON session_usage(session_id);
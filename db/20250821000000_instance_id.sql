@@ -0,0 +1,14 @@
+-- Identity of this raworc deployment, stamped on the containers it creates so that several
+-- deployments can share a Docker daemon without touching each other's containers.
+-- Generated once here; RAWORC_INSTANCE_ID overrides it.
+
+CREATE TABLE IF NOT EXISTS raworc_instance (
+    singleton BOOLEAN PRIMARY KEY DEFAULT TRUE,
+    instance_id VARCHAR(63) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT raworc_instance_singleton_check CHECK (singleton)
+);
+
+INSERT INTO raworc_instance (instance_id)
+VALUES (substr(md5(random()::text || clock_timestamp()::text), 1, 12))
+ON CONFLICT (singleton) DO NOTHING;
@@ -0,0 +1,26 @@
+-- session_tasks and command_results are created by the complete schema; this
+-- tightens their timestamps and indexes the queries added since
+
+-- Every row gets a timestamp on insert; make the columns say so
+UPDATE session_tasks SET created_at = NOW() WHERE created_at IS NULL;
+UPDATE session_tasks SET updated_at = created_at WHERE updated_at IS NULL;
+ALTER TABLE session_tasks
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN updated_at SET NOT NULL;
+
+UPDATE command_results SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE command_results ALTER COLUMN created_at SET NOT NULL;
+
+-- fetch_pending_tasks and create_queue_position: pending create tasks in queue order,
+-- and the count of create tasks in flight against the container cap
+CREATE INDEX IF NOT EXISTS idx_session_tasks_type_status_created_at
+    ON session_tasks(task_type, status, created_at);
+
+-- purge_finished_tasks: finished tasks by completion time
+CREATE INDEX IF NOT EXISTS idx_session_tasks_finished_completed_at
+    ON session_tasks(completed_at) WHERE status IN ('completed', 'failed');
+
+-- Results are read per session, newest first
+DROP INDEX IF EXISTS idx_command_results_session_id;
+CREATE INDEX IF NOT EXISTS idx_command_results_session_created_at
+    ON command_results(session_id, created_at DESC);
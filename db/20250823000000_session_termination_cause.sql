@@ -0,0 +1,20 @@
+-- Why a session ended, as a category dashboards can group by;
+-- termination_reason keeps the human-readable detail
+
+DO $$ BEGIN
+    CREATE TYPE termination_cause AS ENUM (
+        'manual', 'deleted', 'container_lost', 'health_check_failed', 'idle_timeout', 'ttl_expired'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS termination_cause termination_cause;
+
+-- Best-effort backfill from what earlier versions recorded; other sessions keep no cause,
+-- since earlier versions didn't record why they ended
+UPDATE sessions SET termination_cause = 'container_lost'
+WHERE termination_cause IS NULL AND termination_reason = 'Container is no longer running';
+
+UPDATE sessions SET termination_cause = 'deleted'
+WHERE termination_cause IS NULL AND deleted_at IS NOT NULL;
@@ -0,0 +1,45 @@
+-- Every session state transition, for the per-session timeline.
+-- Written by a trigger so each code path that changes state is covered;
+-- sessions only have history from the transitions after this migration
+
+CREATE TABLE IF NOT EXISTS session_state_history (
+    id BIGSERIAL PRIMARY KEY,
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    from_state session_state,
+    to_state session_state NOT NULL,
+    reason TEXT,
+    changed_by VARCHAR(255),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_state_history_session_id
+    ON session_state_history(session_id, id);
+
+-- The acting principal comes from the transaction-local `raworc.actor` setting when
+-- the writer sets it, otherwise from the creator (insert) or terminator (ERROR)
+CREATE OR REPLACE FUNCTION record_session_state_change()
+RETURNS TRIGGER AS $$
+DECLARE
+    actor VARCHAR(255) := NULLIF(current_setting('raworc.actor', true), '');
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO session_state_history (session_id, from_state, to_state, changed_by)
+        VALUES (NEW.id, NULL, NEW.state, COALESCE(actor, NEW.created_by));
+    ELSIF NEW.state IS DISTINCT FROM OLD.state THEN
+        INSERT INTO session_state_history (session_id, from_state, to_state, reason, changed_by)
+        VALUES (
+            NEW.id,
+            OLD.state,
+            NEW.state,
+            CASE WHEN NEW.state = 'ERROR' THEN NEW.termination_reason END,
+            COALESCE(actor, CASE WHEN NEW.state = 'ERROR' THEN NEW.terminated_by END)
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS record_session_state_change ON sessions;
+CREATE TRIGGER record_session_state_change
+AFTER INSERT OR UPDATE OF state ON sessions
+FOR EACH ROW EXECUTE FUNCTION record_session_state_change();
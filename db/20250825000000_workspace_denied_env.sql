@@ -0,0 +1,4 @@
+-- Environment variables session containers in a workspace may not receive,
+-- on top of the built-in and globally configured deny lists
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS denied_env_vars TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,37 @@
+-- AGENT messages must name an agent, and that agent must be assigned to the session.
+-- The complete schema declares the agent_id check; add it where an older database lacks it.
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'check_agent_id_for_agent_role'
+          AND conrelid = 'session_messages'::regclass
+    ) THEN
+        ALTER TABLE session_messages
+            ADD CONSTRAINT check_agent_id_for_agent_role CHECK (
+                (role != 'AGENT') OR (agent_id IS NOT NULL)
+            );
+    END IF;
+END
+$$;
+
+-- Only checked on insert: detaching an agent later keeps the messages it wrote.
+-- A missing agent_id is left to check_agent_id_for_agent_role.
+CREATE OR REPLACE FUNCTION check_agent_message_assignment()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.role = 'AGENT' AND NEW.agent_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM session_agents
+        WHERE session_id = NEW.session_id AND agent_id = NEW.agent_id
+    ) THEN
+        RAISE EXCEPTION 'Agent % is not assigned to session %', NEW.agent_id, NEW.session_id
+            USING ERRCODE = 'check_violation',
+                  CONSTRAINT = 'session_messages_agent_assigned';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS check_agent_message_assignment ON session_messages;
+CREATE TRIGGER check_agent_message_assignment BEFORE INSERT ON session_messages
+    FOR EACH ROW EXECUTE FUNCTION check_agent_message_assignment();
@@ -0,0 +1,7 @@
+-- Pin a session to the operator running on a given node; NULL runs anywhere
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS node_selector VARCHAR(63);
+
+-- Pending tasks are claimed by node, so look sessions up by it cheaply
+CREATE INDEX IF NOT EXISTS idx_sessions_node_selector
+    ON sessions(node_selector)
+    WHERE node_selector IS NOT NULL;
@@ -0,0 +1,25 @@
+-- Let writers explain non-ERROR transitions in the timeline: a transaction-local
+-- `raworc.reason` setting is recorded as the reason, e.g. for a cancelled BUSY session
+
+CREATE OR REPLACE FUNCTION record_session_state_change()
+RETURNS TRIGGER AS $$
+DECLARE
+    actor VARCHAR(255) := NULLIF(current_setting('raworc.actor', true), '');
+    reason TEXT := NULLIF(current_setting('raworc.reason', true), '');
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO session_state_history (session_id, from_state, to_state, changed_by)
+        VALUES (NEW.id, NULL, NEW.state, COALESCE(actor, NEW.created_by));
+    ELSIF NEW.state IS DISTINCT FROM OLD.state THEN
+        INSERT INTO session_state_history (session_id, from_state, to_state, reason, changed_by)
+        VALUES (
+            NEW.id,
+            OLD.state,
+            NEW.state,
+            COALESCE(reason, CASE WHEN NEW.state = 'ERROR' THEN NEW.termination_reason END),
+            COALESCE(actor, CASE WHEN NEW.state = 'ERROR' THEN NEW.terminated_by END)
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
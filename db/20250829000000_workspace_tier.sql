@@ -0,0 +1,9 @@
+-- Customer tier of a workspace; picks the default resources of its session containers
+DO $$ BEGIN
+    CREATE TYPE workspace_tier AS ENUM ('free', 'pro', 'enterprise');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS tier workspace_tier;
@@ -0,0 +1,19 @@
+-- Prior definitions of agents, appended by the server before each update
+-- when RAWORC_AGENT_REVISIONS is enabled, so a change can be reviewed or rolled back
+
+CREATE TABLE IF NOT EXISTS agent_revisions (
+    agent_id UUID NOT NULL REFERENCES agents(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    instructions TEXT NOT NULL,
+    model VARCHAR(100) NOT NULL,
+    tools JSONB NOT NULL DEFAULT '[]',
+    routes JSONB NOT NULL DEFAULT '[]',
+    guardrails JSONB NOT NULL DEFAULT '[]',
+    knowledge_bases JSONB NOT NULL DEFAULT '[]',
+    -- Principal whose update replaced this definition
+    replaced_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (agent_id, revision)
+);
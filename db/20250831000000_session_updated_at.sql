@@ -0,0 +1,11 @@
+-- When anything about a session last changed, for change tracking and ETags.
+-- Existing sessions start from their most recent known change.
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+UPDATE sessions
+SET updated_at = GREATEST(created_at, last_activity_at, terminated_at, deleted_at);
+
+DROP TRIGGER IF EXISTS update_sessions_updated_at ON sessions;
+CREATE TRIGGER update_sessions_updated_at BEFORE UPDATE ON sessions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,3 @@
+-- Hash of the token the host agent in a session's container authenticates with.
+-- The operator issues a new token for every container it creates; NULL until then.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS host_token_hash TEXT;
@@ -0,0 +1,15 @@
+-- Node whose operator created the session's current container; only that operator manages it.
+-- NULL alongside a container_id means an operator without RAWORC_NODE_NAME.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS node_name VARCHAR(63);
+
+-- Nodes whose operators have started with RAWORC_NODE_NAME; sessions may only be pinned to these
+CREATE TABLE IF NOT EXISTS operator_nodes (
+    name VARCHAR(63) PRIMARY KEY,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Containers of pinned sessions were created by their pinned node. Where unpinned sessions'
+-- containers run is unknown; they are left to operators without RAWORC_NODE_NAME.
+UPDATE sessions SET node_name = node_selector
+WHERE container_id IS NOT NULL AND node_name IS NULL AND node_selector IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Where the server reaches each named operator's node API for Docker access (containers,
+-- images, logs, shells); NULL until an operator with RAWORC_NODE_URL registers
+ALTER TABLE operator_nodes ADD COLUMN IF NOT EXISTS api_url TEXT;
@@ -0,0 +1,10 @@
+-- Workspace settings and secrets follow the API's workspace name rule, a lower-case DNS
+-- label, so rows can always be found under the normalized name requests are looked up by.
+-- Rows written under the previous, looser rule are left in place rather than rejected.
+ALTER TABLE workspace_settings DROP CONSTRAINT IF EXISTS workspace_settings_workspace_check;
+ALTER TABLE workspace_settings ADD CONSTRAINT workspace_settings_workspace_check
+    CHECK (workspace ~ '^[a-z0-9][a-z0-9-]{0,62}$') NOT VALID;
+
+ALTER TABLE secrets DROP CONSTRAINT IF EXISTS secrets_workspace_check;
+ALTER TABLE secrets ADD CONSTRAINT secrets_workspace_check
+    CHECK (workspace ~ '^[a-z0-9][a-z0-9-]{0,62}$') NOT VALID;
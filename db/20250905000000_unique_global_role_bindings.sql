@@ -0,0 +1,14 @@
+-- unique_role_binding doesn't cover global bindings, whose workspace is NULL, so identical
+-- global bindings could be created twice. Keep the oldest of any duplicates and index the rest.
+DELETE FROM role_bindings duplicate
+USING role_bindings original
+WHERE duplicate.workspace IS NULL
+  AND original.workspace IS NULL
+  AND duplicate.role_name = original.role_name
+  AND duplicate.principal_name = original.principal_name
+  AND duplicate.principal_type = original.principal_type
+  AND (duplicate.created_at, duplicate.id) > (original.created_at, original.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS unique_global_role_binding
+    ON role_bindings (role_name, principal_name, principal_type)
+    WHERE workspace IS NULL;
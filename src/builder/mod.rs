@@ -75,6 +75,11 @@ impl ImageBuilder {
             cmd.arg("--no-cache");
         }
 
+        // .git isn't copied into the build context, so hand the SHA to the build script
+        if let Some(git_sha) = current_git_sha() {
+            cmd.arg("--build-arg").arg(format!("RAWORC_GIT_SHA={}", git_sha));
+        }
+
         cmd.arg(".");
 
         let output = cmd
@@ -99,7 +104,7 @@ impl ImageBuilder {
         info!("Raworc Docker images:");
         
         let output = Command::new("docker")
-            .args(&["images", "--filter", "reference=raworc-*"])
+            .args(["images", "--filter", "reference=raworc-*"])
             .output()
             .context("Failed to list Docker images")?;
 
@@ -173,7 +178,7 @@ async fn push_images(
             // Tag image for registry
             info!("Tagging {} as {}", source_image, target);
             let tag_output = Command::new("docker")
-                .args(&["tag", &source_image, &target])
+                .args(["tag", &source_image, &target])
                 .output()
                 .context("Failed to tag image")?;
 
@@ -190,7 +195,7 @@ async fn push_images(
         // Push image
         info!("Pushing {}", target_image);
         let push_output = Command::new("docker")
-            .args(&["push", &target_image])
+            .args(["push", &target_image])
             .output()
             .context("Failed to push image")?;
 
@@ -203,4 +208,16 @@ async fn push_images(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn current_git_sha() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
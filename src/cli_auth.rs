@@ -2,7 +2,7 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AuthConfig {
@@ -22,15 +22,19 @@ fn get_config_file() -> Result<PathBuf> {
 }
 
 pub async fn store_auth_config(server_url: &str, token: &str) -> Result<()> {
-    let raworc_dir = get_raworc_dir()?;
-    fs::create_dir_all(&raworc_dir)?;
+    write_auth_config(&get_config_file()?, server_url, token)
+}
+
+fn write_auth_config(config_file: &Path, server_url: &str, token: &str) -> Result<()> {
+    if let Some(dir) = config_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
 
     let config = AuthConfig {
         server: server_url.to_string(),
         token: token.to_string(),
     };
 
-    let config_file = get_config_file()?;
     let yaml_content = serde_yaml::to_string(&config)?;
     fs::write(config_file, yaml_content)?;
 
@@ -69,6 +73,86 @@ pub async fn validate_token(server_url: &str, token: &str) -> Result<Option<Stri
     }
 }
 
+/// Credentials given on the command line for `raworc auth`
+#[derive(Debug, Default)]
+pub struct AuthArgs {
+    pub server: Option<String>,
+    pub user: Option<String>,
+    /// Read the password from stdin instead of prompting
+    pub password_stdin: bool,
+    pub password_file: Option<PathBuf>,
+    pub token: Option<String>,
+}
+
+/// `raworc auth`: log in from flags when `--server` is given, otherwise prompt.
+/// A token from the environment alone doesn't skip the prompts.
+pub async fn auth(args: AuthArgs) -> Result<()> {
+    match args.server.clone() {
+        Some(server_url) => auth_non_interactive(&server_url, args).await,
+        None if args.user.is_some() || args.password_stdin || args.password_file.is_some() => {
+            anyhow::bail!("--server is required with --user, --password-stdin or --password-file")
+        }
+        None => auth_interactive().await,
+    }
+}
+
+/// Log in or store a token from flags, for scripts and CI. The password is never a flag value,
+/// which would show up in `ps` and shell history: it comes from a file, stdin or a prompt.
+pub async fn auth_non_interactive(server_url: &str, args: AuthArgs) -> Result<()> {
+    auth_non_interactive_into(&get_config_file()?, server_url, args).await
+}
+
+async fn auth_non_interactive_into(config_file: &Path, server_url: &str, args: AuthArgs) -> Result<()> {
+    let server_url = server_url.trim_end_matches('/');
+
+    if let Some(token) = args.token {
+        if args.user.is_some() || args.password_stdin || args.password_file.is_some() {
+            anyhow::bail!("--token cannot be combined with --user, --password-stdin or --password-file");
+        }
+        return store_token(config_file, server_url, &token).await;
+    }
+
+    let username = args
+        .user
+        .ok_or_else(|| anyhow::anyhow!("--user is required unless --token is given"))?;
+    let password = match (args.password_stdin, args.password_file) {
+        (true, Some(_)) => anyhow::bail!("Give either --password-stdin or --password-file, not both"),
+        (true, None) => read_password_stdin()?,
+        (false, Some(path)) => read_password_file(&path)?,
+        (false, None) => rpassword::prompt_password("Password: ")?,
+    };
+
+    let token = login(server_url, &username, &password).await?;
+    store_token(config_file, server_url, &token).await
+}
+
+/// The password is the first line, so a trailing newline doesn't count
+fn first_line(content: &str) -> String {
+    content.lines().next().unwrap_or_default().to_string()
+}
+
+fn read_password_file(path: &PathBuf) -> Result<String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read password file {}: {}", path.display(), e))?;
+    Ok(first_line(&content))
+}
+
+/// Password piped to the command, e.g. `cat admin.pass | raworc ... --password-stdin`
+pub fn read_password_stdin() -> Result<String> {
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(first_line(&line))
+}
+
+/// Prompt for a new password twice without echoing it
+pub fn prompt_new_password() -> Result<String> {
+    let password = rpassword::prompt_password("New password: ")?;
+    if rpassword::prompt_password("Repeat password: ")? != password {
+        anyhow::bail!("Passwords don't match");
+    }
+    Ok(password)
+}
+
 pub async fn auth_interactive() -> Result<()> {
     println!("Raworc Authentication");
     println!();
@@ -115,6 +199,20 @@ pub async fn auth_login() -> Result<()> {
 
     println!("Authenticating...");
 
+    match login(server_url, username, &password).await {
+        Ok(token) => {
+            store_auth_config(server_url, &token).await?;
+            if let Some(user) = validate_token(server_url, &token).await? {
+                print_authenticated(&user, server_url);
+            }
+        }
+        Err(e) => println!("✗ {e}"),
+    }
+    Ok(())
+}
+
+/// Exchange service account credentials for a token
+async fn login(server_url: &str, username: &str, password: &str) -> Result<String> {
     let client = reqwest::Client::new();
     let login_request = serde_json::json!({
         "user": username,
@@ -128,29 +226,40 @@ pub async fn auth_login() -> Result<()> {
         .send()
         .await?;
 
-    if response.status().is_success() {
-        let result: serde_json::Value = response.json().await?;
-        if let Some(token) = result.get("token").and_then(|t| t.as_str()) {
-            store_auth_config(server_url, token).await?;
-            if let Some(user) = validate_token(server_url, token).await? {
-                println!();
-                println!("✓ Authentication successful!");
-                println!("   User: {user}");
-                println!("   Server: {server_url}");
-                println!();
-                println!("You can now use 'raworc' or 'raworc connect' to connect to this server.");
-            }
-            return Ok(());
-        } else {
-            println!("✗ Authentication failed: Invalid response");
-        }
-    } else {
-        println!(
-            "✗ Authentication failed: Server returned {status}",
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Authentication failed: Server returned {status}",
             status = response.status()
         );
     }
-    Ok(())
+
+    let result: serde_json::Value = response.json().await?;
+    result
+        .get("token")
+        .and_then(|t| t.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Authentication failed: Invalid response"))
+}
+
+/// Validate a token against the server and store it, failing if the server rejects it
+async fn store_token(config_file: &Path, server_url: &str, token: &str) -> Result<()> {
+    match validate_token(server_url, token).await? {
+        Some(user) => {
+            write_auth_config(config_file, server_url, token)?;
+            print_authenticated(&user, server_url);
+            Ok(())
+        }
+        None => anyhow::bail!("Invalid token or server unreachable"),
+    }
+}
+
+fn print_authenticated(user: &str, server_url: &str) {
+    println!();
+    println!("✓ Authentication successful!");
+    println!("   User: {user}");
+    println!("   Server: {server_url}");
+    println!();
+    println!("You can now use 'raworc' or 'raworc connect' to connect to this server.");
 }
 
 pub async fn auth_token_interactive() -> Result<()> {
@@ -165,16 +274,8 @@ pub async fn auth_token_interactive() -> Result<()> {
     let token = rpassword::read_password()?;
 
     println!("Validating token...");
-    if let Some(user) = validate_token(server_url, &token).await? {
-        store_auth_config(server_url, &token).await?;
-        println!();
-        println!("✓ Authentication successful!");
-        println!("   User: {user}");
-        println!("   Server: {server_url}");
-        println!();
-        println!("You can now use 'raworc' or 'raworc connect' to connect to this server.");
-    } else {
-        println!("✗ Invalid token or server unreachable");
+    if let Err(e) = store_token(&get_config_file()?, server_url, &token).await {
+        println!("✗ {e}");
     }
     Ok(())
 }
@@ -225,4 +326,77 @@ pub async fn show_auth_status() -> Result<()> {
     println!("{status}");
     println!();
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::server::rest::test_support::serve;
+
+    /// Stand-in server accepting `ci` / `s3cret` and the token it hands out for them
+    async fn auth_server() -> String {
+        let router = Router::new()
+            .route(
+                "/api/v0/auth/internal",
+                post(|Json(body): Json<Value>| async move {
+                    if body == json!({"user": "ci", "pass": "s3cret"}) {
+                        Ok(Json(json!({"token": "ci-token"})))
+                    } else {
+                        Err(StatusCode::UNAUTHORIZED)
+                    }
+                }),
+            )
+            .route(
+                "/api/v0/auth/me",
+                get(|headers: HeaderMap| async move {
+                    match headers.get("authorization").and_then(|value| value.to_str().ok()) {
+                        Some("Bearer ci-token") => Ok(Json(json!({"user": "ci"}))),
+                        _ => Err(StatusCode::UNAUTHORIZED),
+                    }
+                }),
+            );
+        serve(router).await
+    }
+
+    #[tokio::test]
+    async fn logging_in_from_flags_writes_the_token_file() {
+        let server_url = auth_server().await;
+        let dir = std::env::temp_dir().join(format!("raworc-auth-{}", uuid::Uuid::new_v4()));
+        let config_file = dir.join("auth.yaml");
+        let password_file = std::env::temp_dir().join(format!("raworc-pass-{}", uuid::Uuid::new_v4()));
+        let args = |password: &str| {
+            fs::write(&password_file, format!("{}\n", password)).unwrap();
+            AuthArgs {
+                user: Some("ci".to_string()),
+                password_file: Some(password_file.clone()),
+                ..AuthArgs::default()
+            }
+        };
+
+        let error = auth_non_interactive_into(&config_file, &server_url, args("wrong")).await.unwrap_err();
+        assert!(error.to_string().contains("401"), "{}", error);
+        assert!(!config_file.exists());
+
+        auth_non_interactive_into(&config_file, &format!("{}/", server_url), args("s3cret")).await.unwrap();
+        let stored: AuthConfig = serde_yaml::from_str(&fs::read_to_string(&config_file).unwrap()).unwrap();
+        assert_eq!(stored.server, server_url);
+        assert_eq!(stored.token, "ci-token");
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&password_file).unwrap();
+    }
+
+    #[test]
+    fn passwords_are_the_first_line_without_its_newline() {
+        assert_eq!(first_line("s3cret\n"), "s3cret");
+        assert_eq!(first_line("s3cret\r\nignored\n"), "s3cret");
+        assert_eq!(first_line(""), "");
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How long each probe may take before the server counts as unreachable
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The fields of `/api/v0/version` that identify a raworc server
+#[derive(Debug, Deserialize)]
+struct ServerVersion {
+    version: String,
+    api: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PingOutcome {
+    /// No HTTP response at all
+    Unreachable { error: String },
+    /// Something answered, but not with raworc's version document
+    NotRaworc { reason: String },
+    /// A raworc server whose health check failed
+    Unhealthy { version: String, status: StatusCode },
+    Healthy { version: String, api: String },
+}
+
+/// Decide what answered from the health status and the version endpoint's status and body
+pub fn classify(health: StatusCode, version_status: StatusCode, version_body: &str) -> PingOutcome {
+    if !version_status.is_success() {
+        return PingOutcome::NotRaworc {
+            reason: format!("/api/v0/version returned {}", version_status),
+        };
+    }
+
+    let server = match serde_json::from_str::<ServerVersion>(version_body) {
+        Ok(server) => server,
+        Err(_) => {
+            return PingOutcome::NotRaworc {
+                reason: "/api/v0/version did not return raworc version information".to_string(),
+            }
+        }
+    };
+
+    if health.is_success() {
+        PingOutcome::Healthy {
+            version: server.version,
+            api: server.api,
+        }
+    } else {
+        PingOutcome::Unhealthy {
+            version: server.version,
+            status: health,
+        }
+    }
+}
+
+/// Probe a server's health and version endpoints without credentials
+pub async fn ping(server_url: &str) -> Result<PingOutcome> {
+    let server_url = server_url.trim_end_matches('/');
+    let client = reqwest::Client::builder().timeout(PING_TIMEOUT).build()?;
+
+    let health = match client.get(format!("{server_url}/api/v0/health")).send().await {
+        Ok(response) => response.status(),
+        Err(e) => return Ok(PingOutcome::Unreachable { error: e.to_string() }),
+    };
+
+    let response = match client.get(format!("{server_url}/api/v0/version")).send().await {
+        Ok(response) => response,
+        Err(e) => return Ok(PingOutcome::Unreachable { error: e.to_string() }),
+    };
+    let version_status = response.status();
+    let version_body = response.text().await.unwrap_or_default();
+
+    Ok(classify(health, version_status, &version_body))
+}
+
+/// `raworc ping`: report on a server, failing so the CLI exits non-zero unless it is a healthy
+/// raworc server
+pub async fn ping_command(server_url: &str) -> Result<()> {
+    let failure = match ping(server_url).await? {
+        PingOutcome::Healthy { version, api } => {
+            println!("✓ Healthy raworc v{version} (API {api})");
+            println!("   Server: {server_url}");
+            return Ok(());
+        }
+        PingOutcome::Unhealthy { version, status } => {
+            format!("Raworc v{version} is reachable but its health check returned {status}")
+        }
+        PingOutcome::NotRaworc { reason } => format!("Server is reachable but is not raworc: {reason}"),
+        PingOutcome::Unreachable { error } => format!("Server is not reachable: {error}"),
+    };
+
+    println!("✗ {failure}");
+    println!("   Server: {server_url}");
+    anyhow::bail!("{server_url} is not a healthy raworc server")
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::{classify, PingOutcome};
+
+    const VERSION: &str = r#"{"version": "0.5.0", "api": "v0"}"#;
+
+    #[test]
+    fn a_raworc_server_is_healthy_or_unhealthy_by_its_health_check() {
+        assert_eq!(
+            classify(StatusCode::OK, StatusCode::OK, VERSION),
+            PingOutcome::Healthy { version: "0.5.0".to_string(), api: "v0".to_string() }
+        );
+        assert_eq!(
+            classify(StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK, VERSION),
+            PingOutcome::Unhealthy { version: "0.5.0".to_string(), status: StatusCode::SERVICE_UNAVAILABLE }
+        );
+    }
+
+    #[test]
+    fn other_servers_are_not_raworc() {
+        // A web server answering every path, or one without the version endpoint
+        assert!(matches!(
+            classify(StatusCode::OK, StatusCode::OK, "<html>Welcome</html>"),
+            PingOutcome::NotRaworc { .. }
+        ));
+        assert!(matches!(
+            classify(StatusCode::OK, StatusCode::OK, r#"{"version": "1.0"}"#),
+            PingOutcome::NotRaworc { .. }
+        ));
+        assert_eq!(
+            classify(StatusCode::NOT_FOUND, StatusCode::NOT_FOUND, ""),
+            PingOutcome::NotRaworc { reason: "/api/v0/version returned 404 Not Found".to_string() }
+        );
+    }
+}
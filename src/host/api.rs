@@ -1,9 +1,10 @@
 use super::config::Config;
 use super::error::{HostError, Result};
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{debug, info};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -26,6 +27,15 @@ pub struct Message {
     pub created_at: String,
 }
 
+/// An agent attached to the session, as listed by the server
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionAgent {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub configuration: serde_json::Value,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CreateMessageRequest {
     pub role: MessageRole,
@@ -34,11 +44,6 @@ pub struct CreateMessageRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ListMessagesResponse {
-    pub messages: Vec<Message>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SessionState {
@@ -69,15 +74,16 @@ impl RaworcClient {
         Self { client, config }
     }
     
-    /// Get messages for the current session
+    /// Get the session's most recent messages, oldest first
     pub async fn get_messages(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<Message>> {
         let mut url = format!(
-            "{}/api/v1/sessions/{}/messages",
+            "{}/api/v0/sessions/{}/messages",
             self.config.api_url,
             self.config.session_id
         );
         
-        let mut params = vec![];
+        // Newest first, so a long session's latest messages are always within the limit
+        let mut params = vec!["order=desc".to_string()];
         if let Some(limit) = limit {
             params.push(format!("limit={}", limit));
         }
@@ -85,22 +91,23 @@ impl RaworcClient {
             params.push(format!("offset={}", offset));
         }
         
-        if !params.is_empty() {
-            url.push_str("?");
-            url.push_str(&params.join("&"));
-        }
+        url.push('?');
+        url.push_str(&params.join("&"));
         
         debug!("Fetching messages from: {}", url);
         
-        let response = self.client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .send()
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+            })
             .await?;
         
         match response.status() {
             StatusCode::OK => {
-                let messages = response.json::<Vec<Message>>().await?;
+                let mut messages = response.json::<Vec<Message>>().await?;
+                messages.reverse();
                 debug!("Fetched {} messages", messages.len());
                 Ok(messages)
             }
@@ -117,33 +124,89 @@ impl RaworcClient {
         }
     }
     
+    /// Agents attached to the current session, in attachment order
+    pub async fn get_session_agents(&self) -> Result<Vec<SessionAgent>> {
+        let url = format!(
+            "{}/api/v0/sessions/{}/agents",
+            self.config.api_url,
+            self.config.session_id
+        );
+        
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+            })
+            .await?;
+        
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Vec<SessionAgent>>().await?),
+            StatusCode::UNAUTHORIZED => {
+                Err(HostError::Api("Unauthorized - check API token".to_string()))
+            }
+            StatusCode::NOT_FOUND => {
+                Err(HostError::Api(format!("Session {} not found", self.config.session_id)))
+            }
+            status => {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                Err(HostError::Api(format!("API error ({}): {}", status, error_text)))
+            }
+        }
+    }
+    
     /// Send a message as the agent
     pub async fn send_message(
         &self,
         content: String,
         agent_id: Option<Uuid>,
         metadata: Option<serde_json::Value>,
+    ) -> Result<Message> {
+        self.post_message(MessageRole::Agent, content, agent_id, metadata).await
+    }
+    
+    /// Send a SYSTEM message, used to surface host-side decisions in the session history
+    pub async fn send_system_message(
+        &self,
+        content: String,
+        metadata: Option<serde_json::Value>,
+    ) -> Result<Message> {
+        self.post_message(MessageRole::System, content, None, metadata).await
+    }
+    
+    async fn post_message(
+        &self,
+        role: MessageRole,
+        content: String,
+        agent_id: Option<Uuid>,
+        metadata: Option<serde_json::Value>,
     ) -> Result<Message> {
         let url = format!(
-            "{}/api/v1/sessions/{}/messages",
+            "{}/api/v0/sessions/{}/messages",
             self.config.api_url,
             self.config.session_id
         );
         
         let request = CreateMessageRequest {
-            role: MessageRole::Agent,
+            role,
             content,
             agent_id,
             metadata,
         };
         
+        // The same key is sent on every attempt so the server stores the message at most once
+        let idempotency_key = Uuid::new_v4().to_string();
+        
         debug!("Sending message to: {}", url);
         
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .json(&request)
-            .send()
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Idempotency-Key", &idempotency_key)
+                    .json(&request)
+            })
             .await?;
         
         match response.status() {
@@ -165,10 +228,52 @@ impl RaworcClient {
         }
     }
     
+    /// Send several agent messages in one request; the server stores all of them or none
+    pub async fn send_messages(&self, messages: Vec<CreateMessageRequest>) -> Result<Vec<Message>> {
+        let url = format!(
+            "{}/api/v0/sessions/{}/messages/batch",
+            self.config.api_url,
+            self.config.session_id
+        );
+        
+        // As with single messages, retries carry the same key so the batch is stored once
+        let idempotency_key = Uuid::new_v4().to_string();
+        
+        debug!("Sending {} messages to: {}", messages.len(), url);
+        
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Idempotency-Key", &idempotency_key)
+                    .json(&messages)
+            })
+            .await?;
+        
+        match response.status() {
+            StatusCode::OK | StatusCode::CREATED => {
+                let messages = response.json::<Vec<Message>>().await?;
+                info!("Sent {} messages", messages.len());
+                Ok(messages)
+            }
+            StatusCode::UNAUTHORIZED => {
+                Err(HostError::Api("Unauthorized - check API token".to_string()))
+            }
+            StatusCode::NOT_FOUND => {
+                Err(HostError::Api(format!("Session {} not found", self.config.session_id)))
+            }
+            status => {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                Err(HostError::Api(format!("Failed to send messages ({}): {}", status, error_text)))
+            }
+        }
+    }
+    
     /// Update session state
     pub async fn update_session_state(&self, state: SessionState) -> Result<()> {
         let url = format!(
-            "{}/api/v1/sessions/{}/state",
+            "{}/api/v0/sessions/{}/state",
             self.config.api_url,
             self.config.session_id
         );
@@ -177,11 +282,13 @@ impl RaworcClient {
         
         debug!("Updating session state to: {:?}", state);
         
-        let response = self.client
-            .put(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .json(&request)
-            .send()
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .put(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .json(&request)
+            })
             .await?;
         
         match response.status() {
@@ -201,4 +308,162 @@ impl RaworcClient {
             }
         }
     }
-}
\ No newline at end of file
+    
+    /// Record session activity without changing state, so long-running work doesn't hit the idle timeout
+    pub async fn heartbeat(&self) -> Result<()> {
+        let url = format!(
+            "{}/api/v0/sessions/{}/heartbeat",
+            self.config.api_url,
+            self.config.session_id
+        );
+        
+        let response = self
+            .send_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+            })
+            .await?;
+        
+        match response.status() {
+            StatusCode::OK | StatusCode::NO_CONTENT => {
+                debug!("Session heartbeat sent");
+                Ok(())
+            }
+            StatusCode::UNAUTHORIZED => {
+                Err(HostError::Api("Unauthorized - check API token".to_string()))
+            }
+            StatusCode::NOT_FOUND => {
+                Err(HostError::Api(format!("Session {} not found", self.config.session_id)))
+            }
+            status => {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                Err(HostError::Api(format!("Failed to send heartbeat ({}): {}", status, error_text)))
+            }
+        }
+    }
+    
+    /// Send a request, retrying transient failures with backoff according to the configured policy.
+    /// POSTs without an `Idempotency-Key` are only retried when the server can't have acted on them.
+    /// Responses that aren't retried are returned as-is for the caller to interpret.
+    async fn send_with_retry<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let policy = self.config.retry;
+        let mut attempt = 1;
+        
+        loop {
+            let request = build().build()?;
+            let mode = RetryMode::of(&request);
+            let result = self.client.execute(request).await;
+            
+            // Some(hint) means retry, optionally after a server-requested delay
+            let retry: Option<Option<Duration>> = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Some(retry_after(response))
+                }
+                Ok(response) if response.status().is_server_error() && mode == RetryMode::Idempotent => {
+                    Some(None)
+                }
+                Ok(_) => None,
+                Err(e) if e.is_connect() => Some(None),
+                Err(e) if e.is_timeout() && mode == RetryMode::Idempotent => Some(None),
+                Err(_) => None,
+            };
+            
+            match retry {
+                Some(hint) if attempt < policy.max_attempts => {
+                    let delay = hint.unwrap_or_else(|| policy.backoff(attempt));
+                    match &result {
+                        Ok(response) => warn!(
+                            "API request failed with {} (attempt {}/{}), retrying in {:?}",
+                            response.status(), attempt, policy.max_attempts, delay
+                        ),
+                        Err(e) => warn!(
+                            "API request failed: {} (attempt {}/{}), retrying in {:?}",
+                            e, attempt, policy.max_attempts, delay
+                        ),
+                    }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => return Ok(result?),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RetryMode {
+    /// Safe to repeat: retry on connection errors, timeouts, 429 and 5xx
+    Idempotent,
+    /// Repeating could duplicate work: only retry when the server can't have acted on
+    /// the request (connection refused, 429)
+    NotProcessed,
+}
+
+impl RetryMode {
+    /// Reads and updates are safe to repeat; a POST only when it carries an idempotency key
+    fn of(request: &reqwest::Request) -> Self {
+        if request.method() == reqwest::Method::POST && !request.headers().contains_key("Idempotency-Key") {
+            RetryMode::NotProcessed
+        } else {
+            RetryMode::Idempotent
+        }
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::test_support::{config, message, MockApi};
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_until_the_call_succeeds() {
+        let api = MockApi::start(vec![message("1", MessageRole::User, "hello")], 2).await;
+        let client = RaworcClient::new(config(&api.url));
+
+        let messages = client.get_messages(Some(5), None).await.unwrap();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(api.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn posts_are_only_retried_when_they_carry_an_idempotency_key() {
+        let api = MockApi::start(Vec::new(), 1).await;
+        let client = RaworcClient::new(config(&api.url));
+
+        // The heartbeat carries no key, so a 503 after it may have been processed ends the call
+        assert!(client.heartbeat().await.is_err());
+        assert_eq!(api.requests().len(), 1);
+
+        let api = MockApi::start(Vec::new(), 1).await;
+        let client = RaworcClient::new(config(&api.url));
+
+        client.send_system_message("note".to_string(), None).await.unwrap();
+        let requests = api.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].body, requests[1].body);
+    }
+
+    #[tokio::test]
+    async fn retries_give_up_after_the_configured_attempts() {
+        let api = MockApi::start(Vec::new(), 10).await;
+        let client = RaworcClient::new(config(&api.url));
+
+        assert!(client.update_session_state(SessionState::Busy).await.is_err());
+        assert_eq!(api.requests().len(), 3);
+    }
+}
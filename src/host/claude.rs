@@ -27,13 +27,16 @@ struct ClaudeContent {
     text: String,
 }
 
+/// Why the host cannot answer when it was started without an Anthropic API key
+pub const MISSING_API_KEY: &str = "ANTHROPIC_API_KEY is not set. Store it as a secret in the session's workspace and list it in the session's metadata.secrets, then restart the session.";
+
 pub struct ClaudeClient {
     client: Client,
-    api_key: String,
+    api_key: Option<String>,
 }
 
 impl ClaudeClient {
-    pub fn new(api_key: &str) -> Result<Self> {
+    pub fn new(api_key: Option<&str>) -> Result<Self> {
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(60))
             .build()
@@ -41,7 +44,7 @@ impl ClaudeClient {
         
         Ok(Self {
             client,
-            api_key: api_key.to_string(),
+            api_key: api_key.map(str::to_string),
         })
     }
     
@@ -50,6 +53,11 @@ impl ClaudeClient {
         messages: Vec<(String, String)>, // (role, content)
         system_prompt: Option<String>,
     ) -> Result<String> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or_else(|| HostError::Config(MISSING_API_KEY.to_string()))?;
+        
         let claude_messages: Vec<ClaudeMessage> = messages
             .into_iter()
             .map(|(role, content)| ClaudeMessage {
@@ -73,7 +81,7 @@ impl ClaudeClient {
         
         let response = self.client
             .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
+            .header("x-api-key", api_key)
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
             .json(&request)
@@ -1,10 +1,147 @@
 use std::time::Duration;
 
+use super::error::{HostError, Result};
+
+/// Messages fetched per poll unless configured otherwise
+pub const DEFAULT_POLL_LIMIT: u32 = 50;
+
+/// The server returns at most this many messages per request
+pub const MAX_POLL_LIMIT: u32 = 1000;
+
+/// Shortest and longest accepted polling intervals
+pub const MIN_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+pub const MAX_POLLING_INTERVAL: Duration = Duration::from_secs(300);
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub session_id: String,
     pub api_url: String,
     pub api_token: String,
-    pub claude_api_key: String,
+    /// Missing when the session was started without the `ANTHROPIC_API_KEY` secret
+    pub claude_api_key: Option<String>,
+    /// Pause between polls; longer for quiet sessions, shorter for busy ones
     pub polling_interval: Duration,
-}
\ No newline at end of file
+    /// Messages requested per poll
+    pub poll_limit: u32,
+    pub retry: RetryPolicy,
+}
+
+impl Config {
+    /// Reject polling settings outside the supported bounds
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_POLL_LIMIT).contains(&self.poll_limit) {
+            return Err(HostError::Config(format!(
+                "poll limit must be between 1 and {}, got {}",
+                MAX_POLL_LIMIT, self.poll_limit
+            )));
+        }
+        if !(MIN_POLLING_INTERVAL..=MAX_POLLING_INTERVAL).contains(&self.polling_interval) {
+            return Err(HostError::Config(format!(
+                "polling interval must be between {:?} and {:?}, got {:?}",
+                MIN_POLLING_INTERVAL, MAX_POLLING_INTERVAL, self.polling_interval
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Retry behaviour for calls to the Raworc API
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts per call, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the next attempt: exponential in the attempt number, capped at
+    /// `max_delay`, with the upper half jittered so concurrent hosts don't retry in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        let jitter = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() % 1000)
+            .unwrap_or(0);
+        exponential / 2 + (exponential / 2) * jitter / 1000
+    }
+}
+
+/// How much prior conversation is sent to Claude along with each message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWindow {
+    /// Keep the last N user/agent messages
+    Messages(usize),
+    /// Keep as many recent messages as fit in an approximate character budget
+    Characters(usize),
+}
+
+impl Default for ContextWindow {
+    fn default() -> Self {
+        ContextWindow::Messages(10)
+    }
+}
+
+impl ContextWindow {
+    /// Read `context_window_chars` or `context_window_messages` from an agent's configuration.
+    /// A character budget takes precedence when both are set.
+    pub fn from_agent_config(config: &serde_json::Value) -> Option<Self> {
+        if let Some(chars) = config.get("context_window_chars").and_then(|v| v.as_u64()) {
+            return Some(ContextWindow::Characters(chars as usize));
+        }
+        config
+            .get("context_window_messages")
+            .and_then(|v| v.as_u64())
+            .map(|messages| ContextWindow::Messages(messages as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+
+        // Each delay is jittered within the upper half of its exponential step
+        let first = policy.backoff(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let second = policy.backoff(2);
+        assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+        let capped = policy.backoff(10);
+        assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn validate_rejects_polling_settings_out_of_bounds() {
+        let valid = crate::host::test_support::config("http://127.0.0.1:1");
+        assert!(valid.validate().is_ok());
+
+        for poll_limit in [0, MAX_POLL_LIMIT + 1] {
+            let config = Config { poll_limit, ..(*valid).clone() };
+            assert!(config.validate().is_err(), "poll limit {}", poll_limit);
+        }
+        for polling_interval in [Duration::from_millis(10), Duration::from_secs(301)] {
+            let config = Config { polling_interval, ..(*valid).clone() };
+            assert!(config.validate().is_err(), "interval {:?}", polling_interval);
+        }
+    }
+}
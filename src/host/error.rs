@@ -11,8 +11,8 @@ pub enum HostError {
     #[error("Todo management error: {0}")]
     Todo(String),
     
-    #[error("Guardrail violation: {0}")]
-    Guardrail(String),
+    #[error("Guardrail violation ({rule}): {reason}")]
+    Guardrail { rule: &'static str, reason: String },
     
     #[error("Configuration error: {0}")]
     Config(String),
@@ -25,9 +25,6 @@ pub enum HostError {
     
     #[error("Request error: {0}")]
     Request(#[from] reqwest::Error),
-    
-    #[error("Unknown error: {0}")]
-    Unknown(String),
 }
 
 pub type Result<T> = std::result::Result<T, HostError>;
\ No newline at end of file
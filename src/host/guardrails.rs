@@ -1,16 +1,27 @@
 use super::error::{HostError, Result};
 use tracing::{debug, warn};
 
+// Rule identifiers reported alongside guardrail decisions
+pub const RULE_MESSAGE_SIZE: &str = "message_size";
+pub const RULE_HARMFUL_INTENT: &str = "harmful_intent";
+pub const RULE_SENSITIVE_CONTENT: &str = "sensitive_content";
+pub const RULE_REDACTION: &str = "redaction";
+
+/// Output that passed validation, along with any keywords that were redacted
+#[derive(Debug, Clone)]
+pub struct SanitizedOutput {
+    pub content: String,
+    pub redacted_keywords: Vec<String>,
+}
+
 pub struct Guardrails {
     max_message_length: usize,
-    max_messages_per_minute: u32,
 }
 
 impl Guardrails {
     pub fn new() -> Self {
         Self {
             max_message_length: 100_000,
-            max_messages_per_minute: 30,
         }
     }
     
@@ -38,9 +49,10 @@ impl Guardrails {
                     
                     if has_value {
                         warn!("Sensitive content detected in message");
-                        return Err(HostError::Guardrail(
-                            "Message contains potentially sensitive information".to_string()
-                        ));
+                        return Err(HostError::Guardrail {
+                            rule: RULE_SENSITIVE_CONTENT,
+                            reason: format!("Message contains potentially sensitive information ({})", keyword),
+                        });
                     }
                 }
             }
@@ -52,17 +64,21 @@ impl Guardrails {
     /// Check if content is within size limits
     pub fn check_message_size(&self, content: &str) -> Result<()> {
         if content.len() > self.max_message_length {
-            return Err(HostError::Guardrail(format!(
-                "Message exceeds maximum length of {} characters",
-                self.max_message_length
-            )));
+            return Err(HostError::Guardrail {
+                rule: RULE_MESSAGE_SIZE,
+                reason: format!(
+                    "Message exceeds maximum length of {} characters",
+                    self.max_message_length
+                ),
+            });
         }
         Ok(())
     }
     
     /// Sanitize content before sending
-    pub fn sanitize_output(&self, content: &str) -> String {
+    pub fn sanitize_output(&self, content: &str) -> SanitizedOutput {
         let mut sanitized = content.to_string();
+        let mut redacted_keywords = Vec::new();
         
         // Simple redaction without regex
         let sensitive_keywords = vec![
@@ -104,6 +120,7 @@ impl Guardrails {
                         let before = &sanitized[..value_start];
                         let after = &sanitized[value_end..];
                         sanitized = format!("{}[REDACTED]{}", before, after);
+                        redacted_keywords.push(keyword.to_string());
                     }
                 }
             }
@@ -122,7 +139,10 @@ impl Guardrails {
             sanitized.push_str("\n[Message truncated due to length]");
         }
         
-        sanitized
+        SanitizedOutput {
+            content: sanitized,
+            redacted_keywords,
+        }
     }
     
     /// Check if the input is asking for harmful actions
@@ -139,9 +159,10 @@ impl Guardrails {
         for pattern in harmful_patterns {
             if lower_content.contains(pattern) {
                 warn!("Potentially harmful command detected: {}", pattern);
-                return Err(HostError::Guardrail(
-                    "Request contains potentially harmful commands".to_string()
-                ));
+                return Err(HostError::Guardrail {
+                    rule: RULE_HARMFUL_INTENT,
+                    reason: format!("Request contains potentially harmful commands ({})", pattern),
+                });
             }
         }
         
@@ -159,7 +180,7 @@ impl Guardrails {
     }
     
     /// Validate all guardrails for output
-    pub fn validate_output(&self, content: &str) -> Result<String> {
+    pub fn validate_output(&self, content: &str) -> Result<SanitizedOutput> {
         debug!("Validating output with guardrails");
         
         self.check_message_size(content)?;
@@ -1,21 +1,128 @@
-use super::api::{RaworcClient, Message, MessageRole, SessionState};
+use super::api::{RaworcClient, CreateMessageRequest, Message, MessageRole, SessionState};
 use super::claude::ClaudeClient;
-use super::error::Result;
-use super::guardrails::Guardrails;
+use super::config::{ContextWindow, DEFAULT_POLL_LIMIT};
+use super::error::{HostError, Result};
+use super::guardrails::{Guardrails, RULE_REDACTION};
 use super::todo::TodoManager;
-use std::collections::HashSet;
+use crate::shared::models::message::{CANCEL_REQUEST_TYPE, IMPORTED_KEY};
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+// Must comfortably exceed the number of messages fetched per poll so the
+// active window is never evicted while it can still be returned by the API
+const PROCESSED_IDS_CAPACITY: usize = 1000;
+
+fn processed_ids_capacity(poll_limit: u32) -> usize {
+    PROCESSED_IDS_CAPACITY.max(poll_limit as usize * 2)
+}
+
+fn is_cancel_request(message: &Message) -> bool {
+    message.role == MessageRole::System
+        && message
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("type"))
+            .and_then(|t| t.as_str())
+            == Some(CANCEL_REQUEST_TYPE)
+}
+
+/// Messages recreated by a session import were answered in the original session
+fn is_imported(message: &Message) -> bool {
+    message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get(IMPORTED_KEY))
+        .and_then(|imported| imported.as_bool())
+        == Some(true)
+}
+
+/// Content and metadata of the SYSTEM message recording a guardrail decision
+fn guardrail_event(action: &str, rule: &str, detail: &str, message_id: &str) -> (String, serde_json::Value) {
+    let content = format!("Guardrail {}: {}", action.replace('_', " "), detail);
+    let metadata = serde_json::json!({
+        "type": "guardrail_event",
+        "action": action,
+        "rule": rule,
+        "message_id": message_id
+    });
+    (content, metadata)
+}
+
+// How often to report activity while messages are being processed
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+// How often to look for a cancel request while messages are being processed,
+// unless the host's polling interval is given
+const DEFAULT_CANCEL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Insertion-ordered set of message ids that evicts the oldest entry once full
+struct ProcessedIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl ProcessedIds {
+    fn new(capacity: usize) -> Self {
+        Self {
+            ids: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    
+    fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+    
+    fn insert(&mut self, id: String) {
+        if !self.ids.insert(id.clone()) {
+            return;
+        }
+        self.order.push_back(id);
+        
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Mark every message up to the latest unseen cancel request as processed, returning whether
+/// there was one. Messages after it are left for the next poll.
+fn take_cancel_request(processed_ids: &mut ProcessedIds, messages: &[Message]) -> bool {
+    let Some(cancel) = messages
+        .iter()
+        .rposition(|message| is_cancel_request(message) && !processed_ids.contains(&message.id))
+    else {
+        return false;
+    };
+    for message in &messages[..=cancel] {
+        processed_ids.insert(message.id.clone());
+    }
+    true
+}
+
 pub struct MessageHandler {
     api_client: Arc<RaworcClient>,
     claude_client: Arc<ClaudeClient>,
     todo_manager: Arc<Mutex<TodoManager>>,
     guardrails: Arc<Guardrails>,
-    processed_message_ids: Arc<Mutex<HashSet<String>>>,
+    processed_message_ids: Arc<Mutex<ProcessedIds>>,
     agent_id: Option<Uuid>,
+    context_window: ContextWindow,
+    poll_limit: u32,
+    // Messages currently being processed across overlapping polls; the session
+    // is only reported READY once this drops back to zero
+    in_flight: Arc<Mutex<usize>>,
+    // Bumped for every cancel request; work started before the bump is abandoned
+    cancel_generation: Arc<watch::Sender<u64>>,
+    cancel_check_interval: Duration,
 }
 
 impl MessageHandler {
@@ -30,63 +137,189 @@ impl MessageHandler {
             claude_client,
             todo_manager,
             guardrails,
-            processed_message_ids: Arc::new(Mutex::new(HashSet::new())),
-            agent_id: None, // Can be set from environment or config
+            processed_message_ids: Arc::new(Mutex::new(ProcessedIds::new(processed_ids_capacity(DEFAULT_POLL_LIMIT)))),
+            agent_id: None,
+            context_window: ContextWindow::default(),
+            poll_limit: DEFAULT_POLL_LIMIT,
+            in_flight: Arc::new(Mutex::new(0)),
+            cancel_generation: Arc::new(watch::Sender::new(0)),
+            cancel_check_interval: DEFAULT_CANCEL_CHECK_INTERVAL,
         }
     }
     
+    /// Agent that replies are attributed to
+    pub fn with_agent_id(mut self, agent_id: Uuid) -> Self {
+        self.agent_id = Some(agent_id);
+        self
+    }
+    
+    pub fn with_context_window(mut self, context_window: ContextWindow) -> Self {
+        self.context_window = context_window;
+        self
+    }
+    
+    /// How often to look for a cancel request while messages are being processed
+    pub fn with_cancel_check_interval(mut self, interval: Duration) -> Self {
+        self.cancel_check_interval = interval;
+        self
+    }
+    
+    /// Messages to request per poll; set before polling starts, as it resets the processed ids
+    pub fn with_poll_limit(mut self, poll_limit: u32) -> Self {
+        self.poll_limit = poll_limit;
+        self.processed_message_ids = Arc::new(Mutex::new(ProcessedIds::new(processed_ids_capacity(poll_limit))));
+        self
+    }
+    
     pub async fn poll_and_process(&self) -> Result<usize> {
         // Get recent messages
-        let messages = self.api_client.get_messages(Some(50), None).await?;
+        let messages = self.api_client.get_messages(Some(self.poll_limit), None).await?;
         
         if messages.is_empty() {
             return Ok(0);
         }
         
         // Find unprocessed user messages
-        let mut processed_ids = self.processed_message_ids.lock().await;
         let mut new_messages = Vec::new();
-        
-        for message in messages.iter() {
-            if !processed_ids.contains(&message.id) {
-                if message.role == MessageRole::User {
-                    new_messages.push(message.clone());
+        let mut cancel_requested = false;
+        {
+            let mut processed_ids = self.processed_message_ids.lock().await;
+            for message in messages.iter() {
+                if !processed_ids.contains(&message.id) {
+                    if message.role == MessageRole::User && !is_imported(message) {
+                        new_messages.push(message.clone());
+                    } else if is_cancel_request(message) {
+                        // Messages sent before the cancel are part of the cancelled work
+                        new_messages.clear();
+                        cancel_requested = true;
+                    }
+                    processed_ids.insert(message.id.clone());
                 }
-                processed_ids.insert(message.id.clone());
             }
         }
         
+        if cancel_requested {
+            info!("Cancel requested; abandoning the current operation");
+            self.cancel_generation.send_modify(|generation| *generation += 1);
+        }
+        
         if new_messages.is_empty() {
             return Ok(0);
         }
         
         info!("Found {} new user messages to process", new_messages.len());
         
-        // Update session state to BUSY
-        if let Err(e) = self.api_client.update_session_state(SessionState::Busy).await {
-            warn!("Failed to update session state to BUSY: {}", e);
-        }
+        self.begin_work(new_messages.len()).await;
+        let heartbeat = self.spawn_heartbeat();
+        let cancel_watcher = self.spawn_cancel_watcher();
+        let mut cancelled = self.cancel_generation.subscribe();
         
         // Process each new message
-        for message in new_messages.iter() {
-            if let Err(e) = self.process_message(message, &messages).await {
-                error!("Failed to process message {}: {}", message.id, e);
+        for (index, message) in new_messages.iter().enumerate() {
+            tokio::select! {
+                result = self.process_message(message, &messages) => {
+                    if let Err(e) = result {
+                        error!("Failed to process message {}: {}", message.id, e);
+                    }
+                }
+                _ = cancelled.changed() => {
+                    let abandoned = new_messages.len() - index;
+                    info!("Cancelled message {} and {} queued after it", message.id, abandoned - 1);
+                    self.abandon_work(abandoned).await;
+                    heartbeat.abort();
+                    cancel_watcher.abort();
+                    return Ok(index);
+                }
             }
+            self.finish_work(1).await;
         }
         
-        // Update session state back to READY
-        if let Err(e) = self.api_client.update_session_state(SessionState::Ready).await {
-            warn!("Failed to update session state to READY: {}", e);
-        }
+        heartbeat.abort();
+        cancel_watcher.abort();
         
         Ok(new_messages.len())
     }
     
+    /// Keep the session's activity fresh while long-running work is in progress
+    fn spawn_heartbeat(&self) -> tokio::task::JoinHandle<()> {
+        let api_client = self.api_client.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+            // The first tick completes immediately; processing just started so skip it
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = api_client.heartbeat().await {
+                    warn!("Failed to send session heartbeat: {}", e);
+                }
+            }
+        })
+    }
+    
+    /// Look for cancel requests while messages are processed, since the next poll only
+    /// comes once they are done
+    fn spawn_cancel_watcher(&self) -> tokio::task::JoinHandle<()> {
+        let api_client = self.api_client.clone();
+        let processed_message_ids = self.processed_message_ids.clone();
+        let cancel_generation = self.cancel_generation.clone();
+        let (poll_limit, interval) = (self.poll_limit, self.cancel_check_interval);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let messages = match api_client.get_messages(Some(poll_limit), None).await {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        warn!("Failed to check for a cancel request: {}", e);
+                        continue;
+                    }
+                };
+                if take_cancel_request(&mut *processed_message_ids.lock().await, &messages) {
+                    info!("Cancel requested; abandoning the current operation");
+                    cancel_generation.send_modify(|generation| *generation += 1);
+                }
+            }
+        })
+    }
+    
+    /// Mark messages as in flight, moving the session to BUSY if it was idle
+    async fn begin_work(&self, count: usize) {
+        // The lock is held across the state update so transitions from
+        // overlapping polls are applied in the same order as the counter changes
+        let mut in_flight = self.in_flight.lock().await;
+        if *in_flight == 0 {
+            if let Err(e) = self.api_client.update_session_state(SessionState::Busy).await {
+                warn!("Failed to update session state to BUSY: {}", e);
+            }
+        }
+        *in_flight += count;
+    }
+    
+    /// Mark messages as done, moving the session back to READY once nothing is in flight
+    async fn finish_work(&self, count: usize) {
+        let mut in_flight = self.in_flight.lock().await;
+        *in_flight = in_flight.saturating_sub(count);
+        if *in_flight == 0 {
+            if let Err(e) = self.api_client.update_session_state(SessionState::Ready).await {
+                warn!("Failed to update session state to READY: {}", e);
+            }
+        }
+    }
+    
+    /// Drop cancelled messages from the in-flight count. The server already moved the
+    /// session back to READY when it accepted the cancel, so no state update is sent.
+    async fn abandon_work(&self, count: usize) {
+        let mut in_flight = self.in_flight.lock().await;
+        *in_flight = in_flight.saturating_sub(count);
+    }
+    
     async fn process_message(&self, message: &Message, all_messages: &[Message]) -> Result<()> {
         info!("Processing message: {}", message.id);
         
         // Validate input with guardrails
-        self.guardrails.validate_input(&message.content)?;
+        if let Err(e) = self.guardrails.validate_input(&message.content) {
+            self.report_guardrail_violation("input_blocked", &e, &message.id).await;
+            return Err(e);
+        }
         
         // Check for todo commands first
         if let Some(response) = self.handle_todo_command(&message.content).await? {
@@ -111,21 +344,65 @@ impl MessageHandler {
             .await?;
         
         // Validate and sanitize output
-        let sanitized_response = self.guardrails.validate_output(&claude_response)?;
-        
-        // Send response back via API
-        self.api_client.send_message(
-            sanitized_response,
-            self.agent_id,
-            Some(serde_json::json!({
-                "type": "claude_response",
-                "model": "claude-3-5-sonnet-20241022"
-            })),
-        ).await?;
+        let sanitized = match self.guardrails.validate_output(&claude_response) {
+            Ok(sanitized) => sanitized,
+            Err(e) => {
+                self.report_guardrail_violation("output_blocked", &e, &message.id).await;
+                return Err(e);
+            }
+        };
+        
+        let reply_metadata = serde_json::json!({
+            "type": "claude_response",
+            "model": "claude-3-5-sonnet-20241022"
+        });
+        
+        if sanitized.redacted_keywords.is_empty() {
+            // Send response back via API
+            self.api_client.send_message(sanitized.content, self.agent_id, Some(reply_metadata)).await?;
+            return Ok(());
+        }
+        
+        // The reply and the record of its redaction are stored together or not at all
+        let (content, metadata) = guardrail_event(
+            "output_redacted",
+            RULE_REDACTION,
+            &format!("Redacted values for: {}", sanitized.redacted_keywords.join(", ")),
+            &message.id,
+        );
+        self.api_client.send_messages(vec![
+            CreateMessageRequest {
+                role: MessageRole::Agent,
+                content: sanitized.content,
+                agent_id: self.agent_id,
+                metadata: Some(reply_metadata),
+            },
+            CreateMessageRequest {
+                role: MessageRole::System,
+                content,
+                agent_id: None,
+                metadata: Some(metadata),
+            },
+        ]).await?;
         
         Ok(())
     }
     
+    async fn report_guardrail_violation(&self, action: &str, error: &HostError, message_id: &str) {
+        if let HostError::Guardrail { rule, reason } = error {
+            self.report_guardrail_event(action, rule, reason, message_id).await;
+        }
+    }
+    
+    /// Post a SYSTEM message so guardrail decisions are visible in the message history
+    async fn report_guardrail_event(&self, action: &str, rule: &str, detail: &str, message_id: &str) {
+        let (content, metadata) = guardrail_event(action, rule, detail, message_id);
+        
+        if let Err(e) = self.api_client.send_system_message(content, Some(metadata)).await {
+            warn!("Failed to report guardrail event for message {}: {}", message_id, e);
+        }
+    }
+    
     async fn handle_todo_command(&self, content: &str) -> Result<Option<String>> {
         let lower = content.to_lowercase();
         
@@ -167,7 +444,10 @@ impl MessageHandler {
                 if let Ok(id) = id_str.parse::<usize>() {
                     let mut manager = self.todo_manager.lock().await;
                     manager.complete(id).await?;
-                    return Ok(Some(format!("✓ Completed todo #{}", id)));
+                    return Ok(Some(match manager.get(id).await {
+                        Some(todo) => format!("✓ Completed todo #{}: {}", id, todo.description),
+                        None => format!("✓ Completed todo #{}", id),
+                    }));
                 }
             }
         }
@@ -190,7 +470,7 @@ impl MessageHandler {
     fn prepare_conversation_history(&self, messages: &[Message], current_id: &str) -> Vec<(String, String)> {
         let mut conversation = Vec::new();
         
-        // Add recent message history (last 10 messages before current)
+        // Add recent message history before the current message
         let mut history: Vec<_> = messages
             .iter()
             .filter(|m| m.id != current_id)
@@ -205,10 +485,22 @@ impl MessageHandler {
             })
             .collect();
         
-        // Keep only last 10 messages for context
-        if history.len() > 10 {
-            history = history.split_off(history.len() - 10);
-        }
+        // Trim to the configured context window, keeping the most recent messages
+        let keep = match self.context_window {
+            ContextWindow::Messages(max_messages) => history.len().min(max_messages),
+            ContextWindow::Characters(budget) => {
+                let mut used = 0;
+                history
+                    .iter()
+                    .rev()
+                    .take_while(|(_, content)| {
+                        used += content.len();
+                        used <= budget
+                    })
+                    .count()
+            }
+        };
+        history = history.split_off(history.len() - keep);
         
         conversation.extend(history);
         
@@ -221,8 +513,7 @@ impl MessageHandler {
     }
     
     fn build_system_prompt(&self) -> String {
-        format!(
-            r#"You are a helpful AI assistant operating within a Raworc session.
+        r#"You are a helpful AI assistant operating within a Raworc session.
 
 Key capabilities:
 - You can help users with various tasks and answer questions
@@ -245,6 +536,203 @@ Current session context:
 - This is an isolated session environment
 - Messages are persisted in the Raworc system
 - You're operating as an agent within this session"#
-        )
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::test_support::{handler, message, MockApi};
+
+    fn history() -> Vec<Message> {
+        vec![
+            message("1", MessageRole::User, "aaaa"),
+            message("2", MessageRole::Agent, "bbbb"),
+            message("3", MessageRole::System, "ignored"),
+            message("4", MessageRole::User, "cccc"),
+            message("5", MessageRole::Agent, "dddd"),
+            message("6", MessageRole::User, "current"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn message_window_keeps_the_most_recent_messages() {
+        let handler = handler("http://127.0.0.1:1").await.with_context_window(ContextWindow::Messages(2));
+
+        let conversation = handler.prepare_conversation_history(&history(), "6");
+
+        assert_eq!(
+            conversation,
+            vec![
+                ("user".to_string(), "cccc".to_string()),
+                ("assistant".to_string(), "dddd".to_string()),
+                ("user".to_string(), "current".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn character_window_keeps_what_fits_in_the_budget() {
+        // Three four-character messages fit in 13 characters, a fourth would not
+        let handler = handler("http://127.0.0.1:1").await.with_context_window(ContextWindow::Characters(13));
+
+        let conversation = handler.prepare_conversation_history(&history(), "6");
+
+        let contents: Vec<_> = conversation.iter().map(|(_, content)| content.as_str()).collect();
+        assert_eq!(contents, vec!["bbbb", "cccc", "dddd", "current"]);
+    }
+
+    #[test]
+    fn processed_ids_evict_the_oldest_once_full() {
+        let mut ids = ProcessedIds::new(3);
+        for id in ["a", "b", "c", "d", "e"] {
+            ids.insert(id.to_string());
+        }
+
+        assert!(!ids.contains("a"));
+        assert!(!ids.contains("b"));
+        assert!(ids.contains("c") && ids.contains("d") && ids.contains("e"));
+        assert_eq!(ids.ids.len(), 3);
+        assert_eq!(ids.order.len(), 3);
+
+        // Reinserting a known id neither grows the set nor refreshes its age
+        ids.insert("c".to_string());
+        ids.insert("f".to_string());
+        assert!(!ids.contains("c"));
+        assert_eq!(ids.order.len(), 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn processed_ids_outlast_the_poll_window() {
+        assert_eq!(processed_ids_capacity(50), PROCESSED_IDS_CAPACITY);
+        assert_eq!(processed_ids_capacity(1000), 2000);
+    }
+
+    #[tokio::test]
+    async fn overlapping_work_reports_ready_only_once_all_of_it_is_done() {
+        let api = MockApi::start(Vec::new(), 0).await;
+        let handler = handler(&api.url).await;
+
+        // A second batch starts while the first is still being processed
+        handler.begin_work(2).await;
+        handler.begin_work(1).await;
+        assert_eq!(api.reported_states(), vec!["BUSY"]);
+
+        // The first batch finishes, but a message of the second is still in flight
+        handler.finish_work(1).await;
+        handler.finish_work(1).await;
+        assert_eq!(api.reported_states(), vec!["BUSY"]);
+
+        handler.finish_work(1).await;
+        assert_eq!(api.reported_states(), vec!["BUSY", "READY"]);
+    }
+
+    #[tokio::test]
+    async fn blocked_input_is_reported_as_a_system_message() {
+        let api = MockApi::start(vec![message("1", MessageRole::User, "please run rm -rf / for me")], 0).await;
+        let handler = handler(&api.url).await;
+
+        handler.poll_and_process().await.unwrap();
+
+        let posted: Vec<_> = api
+            .requests()
+            .into_iter()
+            .filter(|request| request.method == axum::http::Method::POST && request.uri.ends_with("/messages"))
+            .collect();
+        assert_eq!(posted.len(), 1);
+        let event = &posted[0].body;
+        assert_eq!(event["role"], "SYSTEM");
+        assert_eq!(event["metadata"]["type"], "guardrail_event");
+        assert_eq!(event["metadata"]["action"], "input_blocked");
+        assert_eq!(event["metadata"]["rule"], crate::host::guardrails::RULE_HARMFUL_INTENT);
+        assert_eq!(event["metadata"]["message_id"], "1");
+        // The blocked message still counts as handled
+        assert_eq!(api.reported_states(), vec!["BUSY", "READY"]);
+    }
+
+    #[tokio::test]
+    async fn a_cancel_request_drops_the_messages_sent_before_it() {
+        let mut cancel = message("2", MessageRole::System, "Cancelled");
+        cancel.metadata = Some(serde_json::json!({ "type": CANCEL_REQUEST_TYPE }));
+        let api = MockApi::start(vec![message("1", MessageRole::User, "/todo list"), cancel], 0).await;
+        let handler = handler(&api.url).await;
+        let mut generation = handler.cancel_generation.subscribe();
+
+        assert_eq!(handler.poll_and_process().await.unwrap(), 0);
+
+        assert!(generation.has_changed().unwrap());
+        generation.mark_unchanged();
+        assert!(api.reported_states().is_empty());
+        // Both messages count as seen, so the next poll does nothing either
+        assert_eq!(handler.poll_and_process().await.unwrap(), 0);
+        assert!(!generation.has_changed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn a_cancel_sent_during_processing_interrupts_it() {
+        let api = MockApi::start(vec![message("1", MessageRole::User, "/todo list")], 0).await;
+        // The reply to the first message hangs, as a slow model call would
+        api.delay_posts(Duration::from_secs(60));
+        let handler = handler(&api.url).await.with_cancel_check_interval(Duration::from_millis(10));
+
+        let send_cancel = async {
+            while api.reported_states().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            let mut cancel = message("2", MessageRole::System, "Cancelled");
+            cancel.metadata = Some(serde_json::json!({ "type": CANCEL_REQUEST_TYPE }));
+            api.add_message(cancel);
+            api.add_message(message("3", MessageRole::User, "/todo list"));
+        };
+        let (processed, ()) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(handler.poll_and_process(), send_cancel)
+        })
+        .await
+        .expect("the cancel interrupts the pending reply");
+
+        assert_eq!(processed.unwrap(), 0);
+        assert_eq!(api.reported_states(), vec!["BUSY"]);
+        // Only the message sent after the cancel is left to answer
+        api.delay_posts(Duration::ZERO);
+        assert_eq!(handler.poll_and_process().await.unwrap(), 1);
+        assert_eq!(api.reported_states(), vec!["BUSY", "BUSY", "READY"]);
+    }
+
+    #[tokio::test]
+    async fn imported_messages_are_not_answered_again() {
+        let mut imported = message("1", MessageRole::User, "/todo list");
+        imported.metadata = Some(serde_json::json!({ "imported": true }));
+        let api = MockApi::start(vec![imported], 0).await;
+        let handler = handler(&api.url).await;
+
+        assert_eq!(handler.poll_and_process().await.unwrap(), 0);
+        assert!(api.reported_states().is_empty());
+    }
+
+    #[tokio::test]
+    async fn polls_request_the_configured_limit() {
+        let api = MockApi::start(Vec::new(), 0).await;
+        let handler = handler(&api.url).await.with_poll_limit(7);
+
+        assert_eq!(handler.poll_and_process().await.unwrap(), 0);
+
+        let requests = api.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.contains("limit=7"), "{}", requests[0].uri);
+    }
+
+    #[tokio::test]
+    async fn window_defaults_to_ten_messages_and_reads_agent_configuration() {
+        assert_eq!(ContextWindow::default(), ContextWindow::Messages(10));
+        assert_eq!(
+            ContextWindow::from_agent_config(&serde_json::json!({ "context_window_messages": 4 })),
+            Some(ContextWindow::Messages(4))
+        );
+        assert_eq!(
+            ContextWindow::from_agent_config(&serde_json::json!({ "context_window_messages": 4, "context_window_chars": 2000 })),
+            Some(ContextWindow::Characters(2000))
+        );
+        assert_eq!(ContextWindow::from_agent_config(&serde_json::json!({})), None);
+    }
+}
@@ -1,24 +1,133 @@
-// Host agent modules - placeholder for future implementation
-// mod api;
-// mod claude;
-// mod config;
-// mod error;
-// mod guardrails;
-// mod message_handler;
-// mod todo;
-
+// Host agent: answers a session's messages from inside its container
+mod api;
+mod claude;
+mod config;
+mod error;
+mod guardrails;
+mod message_handler;
+mod todo;
+#[cfg(test)]
+mod test_support;
 
 use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use self::api::RaworcClient;
+use self::claude::{ClaudeClient, MISSING_API_KEY};
+use self::config::{Config, ContextWindow, RetryPolicy};
+use self::guardrails::Guardrails;
+use self::message_handler::MessageHandler;
+use self::todo::TodoManager;
+
+/// Settings given to `raworc host`, on the command line or through the environment
+#[derive(Debug)]
+pub struct HostArgs {
+    pub api_url: String,
+    pub session_id: String,
+    pub api_key: String,
+    pub claude_api_key: Option<String>,
+    pub poll_interval_ms: u64,
+    pub poll_limit: u32,
+}
+
+pub async fn run(args: HostArgs) -> Result<()> {
+    info!("Starting Raworc Host Agent...");
+    info!("Connecting to API: {}", args.api_url);
+    info!("Session ID: {}", args.session_id);
+
+    let config = Arc::new(Config {
+        session_id: args.session_id,
+        api_url: args.api_url.trim_end_matches('/').to_string(),
+        api_token: args.api_key,
+        claude_api_key: args.claude_api_key.filter(|key| !key.is_empty()),
+        polling_interval: Duration::from_millis(args.poll_interval_ms),
+        poll_limit: args.poll_limit,
+        retry: RetryPolicy::default(),
+    });
+    config.validate()?;
+
+    let api_client = Arc::new(RaworcClient::new(config.clone()));
+    let claude_client = Arc::new(ClaudeClient::new(config.claude_api_key.as_deref())?);
+    if config.claude_api_key.is_none() {
+        report_missing_api_key(&api_client).await;
+    }
+    let todo_manager = Arc::new(Mutex::new(TodoManager::new("todo.txt").await?));
+    let guardrails = Arc::new(Guardrails::new());
+
+    let mut handler = MessageHandler::new(api_client.clone(), claude_client, todo_manager, guardrails)
+        .with_poll_limit(config.poll_limit)
+        .with_cancel_check_interval(config.polling_interval);
+
+    // Replies are attributed to the session's first agent, whose configuration can set the context window
+    match api_client.get_session_agents().await {
+        Ok(agents) => {
+            if let Some(agent) = agents.first() {
+                info!("Answering as agent {} ({})", agent.name, agent.id);
+                handler = handler.with_agent_id(agent.id);
+                if let Some(context_window) = ContextWindow::from_agent_config(&agent.configuration) {
+                    handler = handler.with_context_window(context_window);
+                }
+            }
+        }
+        Err(e) => warn!("Failed to load session agents, using defaults: {}", e),
+    }
+
+    info!("Starting message polling loop");
 
-pub async fn run(api_url: &str, session_id: &str, _api_key: &str) -> Result<()> {
-    tracing::info!("Starting Raworc Host Agent...");
-    tracing::info!("Connecting to API: {}", api_url);
-    tracing::info!("Session ID: {}", session_id);
-    
-    // For now, just loop and wait
-    // TODO: Implement actual host agent logic
     loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        tracing::debug!("Host agent running...");
+        match handler.poll_and_process().await {
+            Ok(processed) => {
+                if processed > 0 {
+                    info!("Processed {} messages", processed);
+                }
+            }
+            Err(e) => {
+                error!("Error processing messages: {}", e);
+            }
+        }
+
+        tokio::time::sleep(config.polling_interval).await;
+    }
+}
+
+/// Keep running without an Anthropic key, but say in the session why no replies will come
+async fn report_missing_api_key(api_client: &RaworcClient) {
+    warn!("{}", MISSING_API_KEY);
+    let metadata = serde_json::json!({ "type": "host_error" });
+    if let Err(e) = api_client.send_system_message(MISSING_API_KEY.to_string(), Some(metadata)).await {
+        error!("Failed to report the missing API key: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::test_support::{config, MockApi};
+
+    #[tokio::test]
+    async fn a_missing_api_key_is_reported_in_the_session() {
+        let api = MockApi::start(Vec::new(), 0).await;
+        let api_client = RaworcClient::new(config(&api.url));
+
+        report_missing_api_key(&api_client).await;
+
+        let requests = api.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.ends_with("/messages"));
+        assert_eq!(requests[0].body["role"], "SYSTEM");
+        assert_eq!(requests[0].body["content"], MISSING_API_KEY);
+        assert_eq!(requests[0].body["metadata"]["type"], "host_error");
+    }
+
+    #[tokio::test]
+    async fn replies_fail_without_an_api_key() {
+        let claude = ClaudeClient::new(None).unwrap();
+
+        let error = claude.complete(vec![("user".to_string(), "hello".to_string())], None).await.unwrap_err();
+
+        assert!(matches!(error, error::HostError::Config(ref message) if message == MISSING_API_KEY));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,179 @@
+//! Fixtures for host tests
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use super::api::{Message, MessageRole, RaworcClient};
+use super::claude::ClaudeClient;
+use super::config::{Config, RetryPolicy, DEFAULT_POLL_LIMIT};
+use super::guardrails::Guardrails;
+use super::message_handler::MessageHandler;
+use super::todo::TodoManager;
+
+/// Configuration talking to `api_url`, with retries quick enough for tests
+pub fn config(api_url: &str) -> Arc<Config> {
+    Arc::new(Config {
+        session_id: uuid::Uuid::new_v4().to_string(),
+        api_url: api_url.to_string(),
+        api_token: "test-token".to_string(),
+        claude_api_key: Some("test-key".to_string()),
+        polling_interval: Duration::from_secs(1),
+        poll_limit: DEFAULT_POLL_LIMIT,
+        retry: RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        },
+    })
+}
+
+/// A request received by [`MockApi`]
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: Method,
+    /// Path and query
+    pub uri: String,
+    pub body: serde_json::Value,
+}
+
+#[derive(Default)]
+struct MockState {
+    requests: StdMutex<Vec<Recorded>>,
+    /// Requests still to be answered with 503
+    failures: StdMutex<usize>,
+    messages: StdMutex<Vec<Message>>,
+    /// How long posts wait before they are answered, standing in for slow work
+    post_delay: StdMutex<Duration>,
+}
+
+/// Raworc API stand-in on a local port that records every request. Message listings return
+/// the given messages, oldest first unless `order=desc` is asked for; posts echo the created
+/// messages and other calls succeed empty.
+pub struct MockApi {
+    pub url: String,
+    state: Arc<MockState>,
+}
+
+impl MockApi {
+    /// Serve `messages`, answering the first `failures` requests with 503
+    pub async fn start(messages: Vec<Message>, failures: usize) -> Self {
+        let state = Arc::new(MockState {
+            failures: StdMutex::new(failures),
+            messages: StdMutex::new(messages),
+            ..Default::default()
+        });
+        let app = Router::new().fallback(mock_endpoint).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self { url, state }
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Serve `message` after the others from now on, as if it was just sent
+    pub fn add_message(&self, message: Message) {
+        self.state.messages.lock().unwrap().push(message);
+    }
+
+    /// Hold every later post for `delay` before answering it
+    pub fn delay_posts(&self, delay: Duration) {
+        *self.state.post_delay.lock().unwrap() = delay;
+    }
+
+    /// States the host reported, in order
+    pub fn reported_states(&self) -> Vec<String> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == Method::PUT && request.uri.ends_with("/state"))
+            .map(|request| request.body["state"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+}
+
+async fn mock_endpoint(State(state): State<Arc<MockState>>, method: Method, uri: Uri, body: Bytes) -> Response {
+    let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+    state.requests.lock().unwrap().push(Recorded {
+        method: method.clone(),
+        uri: uri.to_string(),
+        body: body.clone(),
+    });
+
+    {
+        let mut failures = state.failures.lock().unwrap();
+        if *failures > 0 {
+            *failures -= 1;
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+    }
+
+    if method == Method::POST {
+        let delay = *state.post_delay.lock().unwrap();
+        tokio::time::sleep(delay).await;
+    }
+
+    let path = uri.path();
+    match method {
+        Method::GET if path.ends_with("/messages") => {
+            let mut messages = state.messages.lock().unwrap().clone();
+            if uri.query().is_some_and(|query| query.contains("order=desc")) {
+                messages.reverse();
+            }
+            Json(messages).into_response()
+        }
+        Method::GET if path.ends_with("/agents") => Json(serde_json::json!([])).into_response(),
+        Method::POST if path.ends_with("/messages") => (StatusCode::CREATED, Json(created_message(&body))).into_response(),
+        Method::POST if path.ends_with("/messages/batch") => {
+            let created: Vec<_> = body.as_array().into_iter().flatten().map(created_message).collect();
+            (StatusCode::CREATED, Json(created)).into_response()
+        }
+        _ => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+fn created_message(request: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "session_id": "",
+        "role": request["role"],
+        "content": request["content"],
+        "agent_id": null,
+        "agent_name": null,
+        "metadata": request["metadata"],
+        "created_at": "",
+    })
+}
+
+/// Handler for a session served at `api_url`, keeping its todos in a fresh temporary file
+pub async fn handler(api_url: &str) -> MessageHandler {
+    let todo_file = std::env::temp_dir().join(format!("raworc-todo-{}.txt", uuid::Uuid::new_v4()));
+    MessageHandler::new(
+        Arc::new(RaworcClient::new(config(api_url))),
+        Arc::new(ClaudeClient::new(Some("test-key")).unwrap()),
+        Arc::new(Mutex::new(TodoManager::new(todo_file.to_str().unwrap()).await.unwrap())),
+        Arc::new(Guardrails::new()),
+    )
+}
+
+pub fn message(id: &str, role: MessageRole, content: &str) -> Message {
+    Message {
+        id: id.to_string(),
+        session_id: String::new(),
+        role,
+        content: content.to_string(),
+        agent_id: None,
+        agent_name: None,
+        metadata: None,
+        created_at: String::new(),
+    }
+}
@@ -5,6 +5,7 @@ mod host;
 mod builder;
 mod cli_auth;
 mod cli_connect;
+mod cli_ping;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -44,12 +45,39 @@ enum Commands {
     /// Connect to server interactively (default command)
     Connect,
     
-    /// Authenticate with the API server
-    Auth,
+    /// Authenticate with the API server; prompts unless flags are given
+    Auth {
+        /// Server URL, e.g. http://localhost:9000
+        #[arg(long)]
+        server: Option<String>,
+        
+        /// Service account to log in as
+        #[arg(long)]
+        user: Option<String>,
+        
+        /// Read the service account password from the first line of stdin instead of prompting
+        #[arg(long, conflicts_with = "password_file")]
+        password_stdin: bool,
+        
+        /// File whose first line is the service account password
+        #[arg(long, value_name = "PATH")]
+        password_file: Option<std::path::PathBuf>,
+        
+        /// Store this JWT instead of logging in
+        #[arg(long, env = "RAWORC_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
     
     /// Show authentication status
     Status,
     
+    /// Check that a server is reachable and running raworc, without credentials
+    Ping {
+        /// Server URL, e.g. http://localhost:9000
+        #[arg(value_name = "URL")]
+        url: String,
+    },
+    
     /// Start the host agent (runs inside containers)
     Host {
         /// API server URL
@@ -63,6 +91,18 @@ enum Commands {
         /// API Key for authentication
         #[arg(long, env = "RAWORC_API_KEY")]
         api_key: String,
+        
+        /// Anthropic API key used to answer messages; without it the host starts but cannot reply
+        #[arg(long, env = "ANTHROPIC_API_KEY", hide_env_values = true)]
+        claude_api_key: Option<String>,
+        
+        /// Milliseconds between message polls
+        #[arg(long, env = "RAWORC_HOST_POLL_INTERVAL_MS", default_value_t = 2000)]
+        poll_interval_ms: u64,
+        
+        /// Messages fetched per poll
+        #[arg(long, env = "RAWORC_HOST_POLL_LIMIT", default_value_t = 50)]
+        poll_limit: u32,
     },
     
     /// Run the API server (internal use)
@@ -71,6 +111,22 @@ enum Commands {
     /// Run the operator (internal use)
     Operator,
     
+    /// Create the initial admin account instead of the default admin/admin
+    BootstrapAdmin {
+        /// Admin service account name
+        #[arg(long, env = "RAWORC_ADMIN_USER", default_value = "admin")]
+        user: String,
+        
+        /// Read the admin password (at least 8 characters) from the first line of stdin
+        /// instead of prompting for it
+        #[arg(long)]
+        password_stdin: bool,
+        
+        /// Bootstrap even if an admin account already exists
+        #[arg(long)]
+        force: bool,
+    },
+    
     /// Build Docker images for Raworc components
     Build {
         /// Components to build (server, operator, host, all)
@@ -99,7 +155,8 @@ enum Commands {
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
-    let _ = shared::logging::init_logging("./logs", "raworc");
+    let logging = shared::config::LoggingConfig::from_env()?;
+    let _ = shared::logging::init_logging(&logging, "./logs", "raworc");
     
     // Parse CLI arguments
     let cli = Cli::parse();
@@ -146,7 +203,7 @@ async fn main() -> Result<()> {
                         
                         // Show running containers
                         let ps_cmd = Command::new("docker")
-                            .args(&["compose", "ps"])
+                            .args(["compose", "ps"])
                             .output();
                         
                         if let Ok(ps_output) = ps_cmd {
@@ -180,7 +237,7 @@ async fn main() -> Result<()> {
                 tracing::info!("Stopping all services with Docker Compose...");
                 
                 let mut cmd = Command::new("docker");
-                cmd.args(&["compose", "down"]);
+                cmd.args(["compose", "down"]);
                 
                 match cmd.output() {
                     Ok(output) => {
@@ -215,7 +272,7 @@ async fn main() -> Result<()> {
                     tracing::info!("Stopping {}...", service_name);
                     
                     let mut cmd = Command::new("docker");
-                    cmd.args(&["compose", "stop", service_name]);
+                    cmd.args(["compose", "stop", service_name]);
                     
                     match cmd.output() {
                         Ok(output) => {
@@ -236,14 +293,17 @@ async fn main() -> Result<()> {
         Commands::Connect => {
             cli_connect::connect_to_server().await?;
         }
-        Commands::Auth => {
-            cli_auth::auth_interactive().await?;
+        Commands::Auth { server, user, password_stdin, password_file, token } => {
+            cli_auth::auth(cli_auth::AuthArgs { server, user, password_stdin, password_file, token }).await?;
         }
         Commands::Status => {
             cli_auth::show_auth_status().await?;
         }
-        Commands::Host { api_url, session_id, api_key } => {
-            host::run(&api_url, &session_id, &api_key).await?;
+        Commands::Ping { url } => {
+            cli_ping::ping_command(&url).await?;
+        }
+        Commands::Host { api_url, session_id, api_key, claude_api_key, poll_interval_ms, poll_limit } => {
+            host::run(host::HostArgs { api_url, session_id, api_key, claude_api_key, poll_interval_ms, poll_limit }).await?;
         }
         Commands::Server => {
             server::rest::server::run_rest_server().await?;
@@ -251,6 +311,14 @@ async fn main() -> Result<()> {
         Commands::Operator => {
             operator::run().await?;
         }
+        Commands::BootstrapAdmin { user, password_stdin, force } => {
+            let password = if password_stdin {
+                cli_auth::read_password_stdin()?
+            } else {
+                cli_auth::prompt_new_password()?
+            };
+            server::rest::server::run_bootstrap_admin(&user, &password, force).await?;
+        }
         Commands::Build { 
             components, 
             tag, 
@@ -1,109 +1,417 @@
 use anyhow::Result;
 use bollard::{
-    container::{Config, CreateContainerOptions, RemoveContainerOptions},
-    exec::{CreateExecOptions, StartExecResults},
+    container::{
+        Config, CreateContainerOptions, DownloadFromContainerOptions, ListContainersOptions, LogOutput,
+        LogsOptions, RemoveContainerOptions, StopContainerOptions, UploadToContainerOptions,
+    },
+    exec::{CreateExecOptions, StartExecOptions, StartExecResults},
+    image::{CreateImageOptions, ListImagesOptions},
     Docker,
 };
-use futures::StreamExt;
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tracing::{error, info};
+use std::io::Read;
+use std::path::Path;
+use std::pin::Pin;
+use tokio::io::AsyncWrite;
+use tracing::info;
 use uuid::Uuid;
 
+use crate::shared::config::{ContainerConfig, ContainerLogConfig, ResourceDefaults};
+use crate::shared::models::{Session, WorkspaceTier};
+
+const SESSION_LABEL: &str = "raworc.session";
+const WORKSPACE_LABEL: &str = "raworc.workspace";
+const INSTANCE_LABEL: &str = "raworc.instance";
+
+/// A raworc-managed container as reported by Docker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionContainer {
+    pub id: String,
+    pub name: String,
+    /// From the `raworc.session` label; None when the label is missing or malformed
+    pub session_id: Option<Uuid>,
+    /// From the `raworc.workspace` label; None on containers created before it was added
+    pub workspace: Option<String>,
+    pub state: ContainerStatus,
+    pub status: String,
+}
+
+/// Docker's container state, from the `State` field of a container listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerStatus {
+    Created,
+    Running,
+    /// Being restarted by Docker under its restart policy; it comes back without our help
+    Restarting,
+    /// Frozen by `docker pause`; its processes and filesystem are intact
+    Paused,
+    Removing,
+    Exited,
+    Dead,
+    /// A state this version doesn't know about
+    Unknown,
+}
+
+impl ContainerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContainerStatus::Created => "created",
+            ContainerStatus::Running => "running",
+            ContainerStatus::Restarting => "restarting",
+            ContainerStatus::Paused => "paused",
+            ContainerStatus::Removing => "removing",
+            ContainerStatus::Exited => "exited",
+            ContainerStatus::Dead => "dead",
+            ContainerStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the container is serving its session or on its way back to it.
+    /// A restart is part of normal operation, so it isn't treated as a lost container.
+    pub fn is_live(&self) -> bool {
+        matches!(self, ContainerStatus::Running | ContainerStatus::Restarting)
+    }
+}
+
+impl From<&str> for ContainerStatus {
+    fn from(state: &str) -> Self {
+        match state {
+            "created" => ContainerStatus::Created,
+            "running" => ContainerStatus::Running,
+            "restarting" => ContainerStatus::Restarting,
+            "paused" => ContainerStatus::Paused,
+            "removing" => ContainerStatus::Removing,
+            "exited" => ContainerStatus::Exited,
+            "dead" => ContainerStatus::Dead,
+            _ => ContainerStatus::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for ContainerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An image present in the local Docker image store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalImage {
+    pub id: String,
+    pub tags: Vec<String>,
+    pub size: i64,
+    /// Unix timestamp
+    pub created: i64,
+    /// True for the image session containers are created from
+    pub session_image: bool,
+}
+
+/// A container's output, in chunks as Docker sends them
+pub type LogStream = Pin<Box<dyn Stream<Item = Result<axum::body::Bytes, bollard::errors::Error>> + Send>>;
+
+/// An interactive shell running in a session container, attached to a TTY
+pub struct ShellExec {
+    /// Terminal output; ends when the shell exits
+    pub output: Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>,
+    /// The shell's stdin
+    pub input: Pin<Box<dyn AsyncWrite + Send>>,
+}
+
+#[derive(Clone)]
 pub struct DockerManager {
     docker: Docker,
     host_image: String,
-    cpu_limit: f64,
-    memory_limit: i64,
+    resources: ResourceDefaults,
+    logging: ContainerLogConfig,
+    /// Session containers are named `{name_prefix}-{session id}`
+    name_prefix: String,
+    /// Value of the `raworc.instance` label; only containers carrying it are listed
+    instance_id: String,
+    /// Also list managed containers without a `raworc.instance` label, which were created
+    /// before the label existed. Only the default instance, without RAWORC_INSTANCE_ID, owns them.
+    adopt_unlabelled: bool,
 }
 
 impl DockerManager {
-    pub fn new(docker: Docker) -> Self {
+    pub fn new(docker: Docker, config: &ContainerConfig, instance_id: String, adopt_unlabelled: bool) -> Self {
         Self {
             docker,
-            host_image: std::env::var("HOST_AGENT_IMAGE")
-                .unwrap_or_else(|_| "raworc-host:latest".to_string()),
-            cpu_limit: std::env::var("HOST_AGENT_CPU_LIMIT")
-                .unwrap_or_else(|_| "0.5".to_string())
-                .parse()
-                .unwrap_or(0.5),
-            memory_limit: std::env::var("HOST_AGENT_MEMORY_LIMIT")
-                .unwrap_or_else(|_| "536870912".to_string())
-                .parse()
-                .unwrap_or(536870912),
+            host_image: config.image.clone(),
+            resources: config.resources.clone(),
+            logging: config.logging.clone(),
+            name_prefix: config.name_prefix.clone(),
+            instance_id,
+            adopt_unlabelled,
         }
     }
 
-    pub async fn create_container(&self, session_id: Uuid) -> Result<String> {
-        let container_name = format!("raworc-session-{}", session_id);
+    /// `tier` is the session's workspace tier, which picks the container's resource limits.
+    /// `host_token` is what the host agent authenticates to the API with.
+    /// `extra_env` holds additional `KEY=value` entries, e.g. decrypted workspace secrets
+    pub async fn create_container(
+        &self,
+        session: &Session,
+        tier: Option<WorkspaceTier>,
+        host_token: &str,
+        extra_env: Vec<String>,
+    ) -> Result<String> {
+        let container_name = self.container_name(session.id);
         
-        info!("Creating container {} with image {}", container_name, self.host_image);
+        info!("Creating container {} with image {} ({})", container_name, self.host_image, self.resources.for_tier(tier));
+
+        let config = self.container_config(session, tier, host_token, extra_env);
+
+        let options = CreateContainerOptions {
+            name: container_name.clone(),
+            ..Default::default()
+        };
+
+        let container = self.docker.create_container(Some(options), config).await?;
+        
+        self.docker
+            .start_container::<String>(&container.id, None)
+            .await?;
+
+        info!("Container {} created and started", container_name);
+        Ok(container.id)
+    }
+
+    /// Name of a session's container. Containers are found by name for a known session;
+    /// listings go by the `raworc.*` labels instead.
+    fn container_name(&self, session_id: Uuid) -> String {
+        format!("{}-{}", self.name_prefix, session_id)
+    }
+
+    /// Docker config of a session's container. Besides the session, its labels name the
+    /// session's workspace and creator so containers can be triaged and filtered per tenant.
+    fn container_config(
+        &self,
+        session: &Session,
+        tier: Option<WorkspaceTier>,
+        host_token: &str,
+        extra_env: Vec<String>,
+    ) -> Config<String> {
+        let resources = self.resources.for_tier(tier);
 
         let mut labels = HashMap::new();
-        labels.insert("raworc.session".to_string(), session_id.to_string());
+        labels.insert(SESSION_LABEL.to_string(), session.id.to_string());
         labels.insert("raworc.managed".to_string(), "true".to_string());
+        labels.insert(INSTANCE_LABEL.to_string(), self.instance_id.clone());
+        labels.insert(WORKSPACE_LABEL.to_string(), session.workspace.clone());
+        labels.insert("raworc.created_by".to_string(), session.created_by.clone());
 
         // Set environment variables for the host agent
-        let env = vec![
+        let mut env = vec![
             format!("RAWORC_API_URL=http://raworc-server:9000"),
-            format!("RAWORC_SESSION_ID={}", session_id),
-            format!("RAWORC_API_KEY=session-{}", session_id),  // TODO: Generate proper API key
+            format!("RAWORC_SESSION_ID={}", session.id),
+            format!("RAWORC_API_KEY={}", host_token),
         ];
+        env.extend(extra_env);
 
-        let config = Config {
+        Config {
             image: Some(self.host_image.clone()),
-            hostname: Some(format!("session-{}", &session_id.to_string()[..8])),
+            hostname: Some(format!("session-{}", &session.id.to_string()[..8])),
             labels: Some(labels),
             env: Some(env),
             host_config: Some(bollard::models::HostConfig {
-                cpu_quota: Some((self.cpu_limit * 100000.0) as i64),
+                cpu_quota: Some((resources.cpu_limit * 100000.0) as i64),
                 cpu_period: Some(100000),
-                memory: Some(self.memory_limit),
-                memory_swap: Some(self.memory_limit),
+                memory: Some(resources.memory_limit),
+                memory_swap: Some(resources.memory_limit),
+                storage_opt: resources
+                    .disk_limit
+                    .map(|bytes| HashMap::from([("size".to_string(), bytes.to_string())])),
                 network_mode: Some("raworc-network".to_string()),
+                log_config: Some(bollard::models::HostConfigLogConfig {
+                    typ: Some(self.logging.driver.clone()),
+                    config: Some(self.logging.options.clone().into_iter().collect()),
+                }),
                 ..Default::default()
             }),
             ..Default::default()
-        };
+        }
+    }
 
-        let options = CreateContainerOptions {
-            name: container_name.clone(),
-            ..Default::default()
-        };
+    /// Remove a session's container and its volumes. Safe to repeat, e.g. for a duplicate
+    /// destroy task: a container that is already gone counts as destroyed.
+    pub async fn destroy_container(&self, session_id: Uuid) -> Result<()> {
+        self.remove_container_if_exists(session_id).await
+    }
 
-        let container = self.docker.create_container(Some(options), config).await?;
-        
-        self.docker
-            .start_container::<String>(&container.id, None)
-            .await?;
+    /// Stop a session's container but keep it, so a later restart resumes with its filesystem.
+    /// A container that is missing or already stopped is not an error.
+    pub async fn stop_container(&self, session_id: Uuid) -> Result<()> {
+        let container_name = self.container_name(session_id);
 
-        info!("Container {} created and started", container_name);
-        Ok(container.id)
+        match self.docker.stop_container(&container_name, None::<StopContainerOptions>).await {
+            Ok(()) => {
+                info!("Container {} stopped", container_name);
+                Ok(())
+            }
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 304 | 404, .. }) => Ok(()),
+            Err(e) => Err(anyhow::anyhow!("Failed to stop container {}: {}", container_name, e)),
+        }
     }
 
-    pub async fn destroy_container(&self, session_id: Uuid) -> Result<()> {
-        let container_name = format!("raworc-session-{}", session_id);
-        
-        info!("Destroying container {}", container_name);
+    /// Start a session's stopped container again. Returns false when the container
+    /// no longer exists, in which case the caller has to create a new one.
+    pub async fn restart_stopped_container(&self, session_id: Uuid) -> Result<bool> {
+        let container_name = self.container_name(session_id);
+
+        match self.docker.start_container::<String>(&container_name, None).await {
+            Ok(()) | Err(bollard::errors::Error::DockerResponseServerError { status_code: 304, .. }) => {
+                info!("Container {} started", container_name);
+                Ok(true)
+            }
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => Ok(false),
+            Err(e) => Err(anyhow::anyhow!("Failed to start container {}: {}", container_name, e)),
+        }
+    }
+
+    /// Force-remove a session's container along with its anonymous volumes.
+    /// A container that no longer exists is not an error.
+    pub async fn remove_container_if_exists(&self, session_id: Uuid) -> Result<()> {
+        self.remove_managed_container(&self.container_name(session_id)).await
+    }
 
+    /// Same as `remove_container_if_exists`, addressing the container by name or id
+    pub async fn remove_managed_container(&self, container_name: &str) -> Result<()> {
         let options = RemoveContainerOptions {
             force: true,
+            v: true,
             ..Default::default()
         };
 
-        match self.docker.remove_container(&container_name, Some(options)).await {
+        match self.docker.remove_container(container_name, Some(options)).await {
             Ok(_) => {
-                info!("Container {} destroyed", container_name);
+                info!("Container {} and its volumes removed", container_name);
+                Ok(())
+            }
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => {
+                info!("Container {} was already removed", container_name);
                 Ok(())
             }
-            Err(e) => {
-                error!("Failed to destroy container {}: {}", container_name, e);
-                Err(anyhow::anyhow!("Failed to destroy container: {}", e))
+            // Another remove of the same container got there first
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 409, message })
+                if message.contains("already in progress") =>
+            {
+                info!("Container {} is already being removed", container_name);
+                Ok(())
+            }
+            Err(e) => Err(anyhow::anyhow!("Failed to remove container {}: {}", container_name, e)),
+        }
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        self.docker.ping().await?;
+        Ok(())
+    }
+
+    /// All containers managed by this instance, running or not; with `workspace`, only those
+    /// labelled with it. Containers of other deployments sharing the Docker daemon carry a
+    /// different `raworc.instance` label.
+    pub async fn list_session_containers(&self, workspace: Option<&str>) -> Result<Vec<SessionContainer>> {
+        // Docker can't filter on a missing label, so the instance is checked below
+        let mut labels = vec!["raworc.managed=true".to_string()];
+        if let Some(workspace) = workspace {
+            labels.push(format!("{}={}", WORKSPACE_LABEL, workspace));
+        }
+        let mut filters = HashMap::new();
+        filters.insert("label".to_string(), labels);
+
+        let options = ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
+        };
+
+        let containers = self.docker.list_containers(Some(options)).await?;
+
+        Ok(containers
+            .into_iter()
+            .filter(|c| {
+                let instance = c.labels.as_ref().and_then(|labels| labels.get(INSTANCE_LABEL));
+                belongs_to_instance(instance.map(String::as_str), &self.instance_id, self.adopt_unlabelled)
+            })
+            .map(|c| {
+                let label = |name: &str| c.labels.as_ref().and_then(|labels| labels.get(name)).cloned();
+                let session_id = label(SESSION_LABEL).and_then(|id| Uuid::parse_str(&id).ok());
+                let workspace = label(WORKSPACE_LABEL);
+                let name = c
+                    .names
+                    .and_then(|names| names.into_iter().next())
+                    .map(|name| name.trim_start_matches('/').to_string())
+                    .unwrap_or_default();
+
+                SessionContainer {
+                    id: c.id.unwrap_or_default(),
+                    name,
+                    session_id,
+                    workspace,
+                    state: c.state.as_deref().map_or(ContainerStatus::Unknown, ContainerStatus::from),
+                    status: c.status.unwrap_or_default(),
+                }
+            })
+            .collect())
+    }
+
+    pub fn session_image(&self) -> &str {
+        &self.host_image
+    }
+
+    /// Pull an image so the first container using it doesn't wait on the download.
+    /// Waits for the pull to finish; a reference without a tag pulls `latest`.
+    /// The node API runs it in the background.
+    pub async fn pull_image(&self, image: &str) -> Result<()> {
+        let (from_image, tag) = split_image_reference(image);
+        let options = CreateImageOptions {
+            from_image,
+            tag,
+            ..Default::default()
+        };
+
+        info!("Pulling image {}", image);
+        let mut stream = self.docker.create_image(Some(options), None, None);
+        while let Some(progress) = stream.next().await {
+            if let Some(error) = progress?.error {
+                return Err(anyhow::anyhow!("Failed to pull image {}: {}", image, error));
             }
         }
+        info!("Pulled image {}", image);
+        Ok(())
+    }
+
+    /// Tagged images in the local store, the session image first
+    pub async fn list_images(&self) -> Result<Vec<LocalImage>> {
+        let images = self
+            .docker
+            .list_images(Some(ListImagesOptions::<String> {
+                all: false,
+                ..Default::default()
+            }))
+            .await?;
+
+        let session_image = qualified_image_reference(&self.host_image);
+        let mut images: Vec<LocalImage> = images
+            .into_iter()
+            .filter(|image| image.repo_tags.iter().any(|tag| tag != "<none>:<none>"))
+            .map(|image| LocalImage {
+                session_image: image.repo_tags.contains(&session_image),
+                id: image.id,
+                tags: image.repo_tags,
+                size: image.size,
+                created: image.created,
+            })
+            .collect();
+        images.sort_by(|a, b| b.session_image.cmp(&a.session_image).then(b.created.cmp(&a.created)));
+        Ok(images)
     }
 
     pub async fn execute_command(&self, session_id: Uuid, command: &str) -> Result<String> {
-        let container_name = format!("raworc-session-{}", session_id);
+        let container_name = self.container_name(session_id);
         
         info!("Executing command in container {}: {}", container_name, command);
 
@@ -130,4 +438,301 @@ impl DockerManager {
 
         Ok(output_str)
     }
-}
\ No newline at end of file
+
+    /// Start a login shell with a TTY in a running session container and attach to it
+    pub async fn open_shell(&self, session_id: Uuid) -> Result<ShellExec> {
+        let container_name = self.container_name(session_id);
+
+        info!("Opening interactive shell in container {}", container_name);
+
+        let exec_config = CreateExecOptions {
+            cmd: Some(vec!["/bin/bash", "-l"]),
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            tty: Some(true),
+            env: Some(vec!["TERM=xterm-256color"]),
+            ..Default::default()
+        };
+
+        let exec = self.docker
+            .create_exec(&container_name, exec_config)
+            .await?;
+
+        let options = StartExecOptions {
+            tty: true,
+            ..Default::default()
+        };
+        match self.docker.start_exec(&exec.id, Some(options)).await? {
+            StartExecResults::Attached { output, input } => Ok(ShellExec { output, input }),
+            StartExecResults::Detached => anyhow::bail!("Shell exec in {} started detached", container_name),
+        }
+    }
+
+    /// Everything a session container has written to stdout and stderr, stopped or not, read
+    /// from Docker as the stream is polled. None when the container no longer exists.
+    pub async fn container_logs(&self, session_id: Uuid) -> Result<Option<LogStream>> {
+        let container_name = self.container_name(session_id);
+
+        let options = LogsOptions::<String> {
+            stdout: true,
+            stderr: true,
+            tail: "all".to_string(),
+            ..Default::default()
+        };
+        let mut output = self
+            .docker
+            .logs(&container_name, Some(options))
+            .map(|chunk| chunk.map(LogOutput::into_bytes));
+
+        // Docker reports a missing container on the first read
+        let first = match output.next().await {
+            None => return Ok(Some(stream::empty().boxed())),
+            Some(Ok(chunk)) => chunk,
+            Some(Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. })) => return Ok(None),
+            Some(Err(e)) => return Err(anyhow::anyhow!("Failed to read logs of container {}: {}", container_name, e)),
+        };
+        Ok(Some(stream::once(async move { Ok(first) }).chain(output).boxed()))
+    }
+
+    /// Write a file into a running session container through the Docker archive API.
+    /// Works regardless of how the image lays out its volumes.
+    pub async fn upload_file(&self, session_id: Uuid, path: &str, contents: &[u8]) -> Result<()> {
+        let container_name = self.container_name(session_id);
+        let (parent, file_name) = split_container_path(path)?;
+
+        info!("Uploading {} ({} bytes) to container {}", path, contents.len(), container_name);
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp() as u64);
+        header.set_cksum();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append_data(&mut header, file_name, contents)?;
+        let archive = builder.into_inner()?;
+
+        let options = UploadToContainerOptions {
+            path: parent.to_string(),
+            ..Default::default()
+        };
+
+        self.docker
+            .upload_to_container(&container_name, Some(options), archive.into())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read a single file out of a running session container through the Docker archive API.
+    pub async fn download_file(&self, session_id: Uuid, path: &str) -> Result<Vec<u8>> {
+        let container_name = self.container_name(session_id);
+
+        info!("Downloading {} from container {}", path, container_name);
+
+        let options = DownloadFromContainerOptions { path: path.to_string() };
+        let mut stream = self.docker.download_from_container(&container_name, Some(options));
+
+        let mut archive = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            archive.extend_from_slice(&chunk?);
+        }
+
+        let mut entries = tar::Archive::new(archive.as_slice());
+        for entry in entries.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type().is_file() {
+                let mut contents = Vec::new();
+                entry.read_to_end(&mut contents)?;
+                return Ok(contents);
+            }
+        }
+
+        Err(anyhow::anyhow!("{} is not a regular file in container {}", path, container_name))
+    }
+}
+
+/// Whether a managed container with `instance` as its `raworc.instance` label belongs to this
+/// instance. Unlabelled containers predate the label and go to the instance adopting them.
+fn belongs_to_instance(instance: Option<&str>, instance_id: &str, adopt_unlabelled: bool) -> bool {
+    match instance {
+        Some(instance) => instance == instance_id,
+        None => adopt_unlabelled,
+    }
+}
+
+/// Split `name[:tag]` into the name and tag Docker's pull API expects, defaulting to
+/// `latest`. Digest references are passed whole. A colon before the last `/` belongs
+/// to a registry port, not a tag.
+fn split_image_reference(image: &str) -> (&str, &str) {
+    if image.contains('@') {
+        return (image, "");
+    }
+    let name_start = image.rfind('/').map_or(0, |i| i + 1);
+    match image[name_start..].rfind(':') {
+        Some(i) => (&image[..name_start + i], &image[name_start + i + 1..]),
+        None => (image, "latest"),
+    }
+}
+
+/// The reference as Docker lists it in `RepoTags`, i.e. with an explicit tag
+fn qualified_image_reference(image: &str) -> String {
+    match split_image_reference(image) {
+        (name, "") => name.to_string(),
+        (name, tag) => format!("{}:{}", name, tag),
+    }
+}
+
+/// Split an absolute container path into its directory and file name
+fn split_container_path(path: &str) -> Result<(&str, &str)> {
+    let path_ref = Path::new(path);
+    if !path_ref.is_absolute() {
+        return Err(anyhow::anyhow!("Container path must be absolute: {}", path));
+    }
+
+    let file_name = path_ref
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Container path has no file name: {}", path))?;
+    let parent = path_ref
+        .parent()
+        .and_then(|parent| parent.to_str())
+        .unwrap_or("/");
+
+    Ok((parent, file_name))
+}
+
+#[cfg(test)]
+impl DockerManager {
+    /// Manager whose Docker daemon is never reachable, for tests that stop short of Docker
+    pub(crate) fn unreachable(name_prefix: &str) -> Self {
+        Self::with_daemon("http://127.0.0.1:1", name_prefix)
+    }
+
+    /// Manager talking to the Docker API at `url`, e.g. a test's stand-in daemon
+    pub(crate) fn with_daemon(url: &str, name_prefix: &str) -> Self {
+        use crate::shared::config::ContainerResources;
+        use std::collections::BTreeMap;
+
+        DockerManager {
+            docker: Docker::connect_with_http(url, 1, bollard::API_DEFAULT_VERSION).unwrap(),
+            host_image: "raworc_host:latest".to_string(),
+            resources: ResourceDefaults {
+                untiered: ContainerResources {
+                    cpu_limit: 0.5,
+                    memory_limit: 512 * 1024 * 1024,
+                    disk_limit: None,
+                },
+                tiers: HashMap::new(),
+            },
+            logging: ContainerLogConfig {
+                driver: "json-file".to_string(),
+                options: BTreeMap::from([("max-size".to_string(), "10m".to_string())]),
+            },
+            name_prefix: name_prefix.to_string(),
+            instance_id: "test-instance".to_string(),
+            adopt_unlabelled: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::models::SessionState;
+
+    fn manager(name_prefix: &str) -> DockerManager {
+        DockerManager::unreachable(name_prefix)
+    }
+
+    fn session() -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            name: "test".to_string(),
+            workspace: "team-a".to_string(),
+            starting_prompt: "test".to_string(),
+            state: SessionState::Init,
+            waiting_timeout_seconds: None,
+            container_id: None,
+            persistent_volume_id: None,
+            created_by: "alice".to_string(),
+            parent_session_id: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            started_at: None,
+            last_activity_at: None,
+            terminated_at: None,
+            termination_reason: None,
+            termination_cause: None,
+            terminated_by: None,
+            metadata: serde_json::json!({}),
+            deleted_at: None,
+            node_selector: None,
+        }
+    }
+
+    #[test]
+    fn container_name_uses_configured_prefix() {
+        let session = session();
+        assert_eq!(manager("raworc-session").container_name(session.id), format!("raworc-session-{}", session.id));
+        assert_eq!(manager("team-a.raworc").container_name(session.id), format!("team-a.raworc-{}", session.id));
+    }
+
+    #[test]
+    fn container_config_labels_session_workspace_and_creator() {
+        let session = session();
+        let labels = manager("raworc-session").container_config(&session, None, "rwh_token", Vec::new()).labels.unwrap();
+        assert_eq!(labels[SESSION_LABEL], session.id.to_string());
+        assert_eq!(labels[WORKSPACE_LABEL], "team-a");
+        assert_eq!(labels["raworc.created_by"], "alice");
+        assert_eq!(labels["raworc.instance"], "test-instance");
+        assert_eq!(labels["raworc.managed"], "true");
+    }
+
+    #[test]
+    fn container_config_gives_the_host_its_token_and_extra_env() {
+        let session = session();
+        let env = manager("raworc-session")
+            .container_config(&session, None, "rwh_token", vec!["ANTHROPIC_API_KEY=key".to_string()])
+            .env
+            .unwrap();
+        assert!(env.contains(&format!("RAWORC_SESSION_ID={}", session.id)));
+        assert!(env.contains(&"RAWORC_API_KEY=rwh_token".to_string()));
+        assert!(env.contains(&"ANTHROPIC_API_KEY=key".to_string()));
+    }
+
+    #[test]
+    fn container_config_sets_logging_driver_and_options() {
+        let config = manager("raworc-session").container_config(&session(), None, "rwh_token", Vec::new());
+        let log_config = config.host_config.unwrap().log_config.unwrap();
+        assert_eq!(log_config.typ.as_deref(), Some("json-file"));
+        assert_eq!(log_config.config, Some(HashMap::from([("max-size".to_string(), "10m".to_string())])));
+    }
+
+    #[test]
+    fn container_status_round_trips_docker_states() {
+        for state in ["created", "running", "restarting", "paused", "removing", "exited", "dead"] {
+            assert_eq!(ContainerStatus::from(state).as_str(), state);
+        }
+        assert_eq!(ContainerStatus::from("Running"), ContainerStatus::Unknown);
+        assert_eq!(ContainerStatus::from("").to_string(), "unknown");
+    }
+
+    #[test]
+    fn only_running_and_restarting_are_live() {
+        assert!(ContainerStatus::Running.is_live());
+        assert!(ContainerStatus::Restarting.is_live());
+        for status in [ContainerStatus::Created, ContainerStatus::Paused, ContainerStatus::Removing, ContainerStatus::Exited, ContainerStatus::Dead, ContainerStatus::Unknown] {
+            assert!(!status.is_live(), "{} should not be live", status);
+        }
+    }
+
+    #[test]
+    fn unlabelled_containers_belong_to_the_adopting_instance() {
+        assert!(belongs_to_instance(Some("a"), "a", false));
+        assert!(!belongs_to_instance(Some("b"), "a", true));
+        assert!(belongs_to_instance(None, "a", true));
+        assert!(!belongs_to_instance(None, "a", false));
+    }
+}
@@ -0,0 +1,197 @@
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info};
+
+use super::docker_manager::DockerManager;
+
+/// The poll loop counts as stalled when it hasn't completed an iteration for this long.
+/// Generous because a single iteration may wait on an image pull or a reaper run.
+const POLL_STALE_AFTER: Duration = Duration::from_secs(300);
+
+/// Timestamps the poll loop records as it runs
+#[derive(Default)]
+pub struct Activity {
+    last_poll: Mutex<Option<DateTime<Utc>>>,
+    last_processed: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl Activity {
+    pub fn record_poll(&self) {
+        *self.last_poll.lock().unwrap() = Some(Utc::now());
+    }
+
+    pub fn record_processed(&self) {
+        *self.last_processed.lock().unwrap() = Some(Utc::now());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub ready: bool,
+    pub database: bool,
+    pub docker: bool,
+    pub poll_loop: bool,
+    pub last_poll_at: Option<String>,
+    /// When the operator last finished a task
+    pub last_processed_at: Option<String>,
+}
+
+/// Combine dependency checks and loop activity into a readiness verdict
+pub fn evaluate(
+    database: bool,
+    docker: bool,
+    last_poll: Option<DateTime<Utc>>,
+    last_processed: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> HealthReport {
+    let poll_loop = last_poll.is_some_and(|at| {
+        now.signed_duration_since(at)
+            .to_std()
+            .ok()
+            .is_none_or(|age| age <= POLL_STALE_AFTER)
+    });
+
+    HealthReport {
+        ready: database && docker && poll_loop,
+        database,
+        docker,
+        poll_loop,
+        last_poll_at: last_poll.map(|dt| dt.to_rfc3339()),
+        last_processed_at: last_processed.map(|dt| dt.to_rfc3339()),
+    }
+}
+
+/// What the health endpoint needs to check the operator's dependencies
+#[derive(Clone)]
+pub struct HealthProbe {
+    pub pool: Pool<Postgres>,
+    pub docker_manager: DockerManager,
+    pub activity: Arc<Activity>,
+}
+
+impl HealthProbe {
+    pub async fn check(&self) -> HealthReport {
+        let database = sqlx::query("SELECT 1").execute(&self.pool).await.is_ok();
+        let docker = self.docker_manager.ping().await.is_ok();
+        let last_poll = *self.activity.last_poll.lock().unwrap();
+        let last_processed = *self.activity.last_processed.lock().unwrap();
+
+        evaluate(database, docker, last_poll, last_processed, Utc::now())
+    }
+}
+
+async fn health(State(probe): State<HealthProbe>) -> (StatusCode, Json<HealthReport>) {
+    let report = probe.check().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// `GET /health`: 200 when ready, 503 otherwise
+pub fn router(probe: HealthProbe) -> Router {
+    Router::new().route("/health", get(health)).with_state(probe)
+}
+
+/// Serve the operator's HTTP endpoints, the health check and the node API, on the given port
+pub fn spawn(port: u16, app: Router) {
+    tokio::spawn(async move {
+        let addr = format!("0.0.0.0:{}", port);
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind operator health endpoint on {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("Operator health endpoint: http://{}/health", addr);
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("Operator health endpoint stopped: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::server::rest::test_support::{body_json, serve, TestApp};
+
+    #[test]
+    fn readiness_needs_both_dependencies_and_a_recent_poll() {
+        let now = Utc::now();
+        let recent = Some(now - chrono::Duration::seconds(5));
+
+        let report = evaluate(true, true, recent, None, now);
+        assert!(report.ready && report.poll_loop);
+        assert!(!evaluate(false, true, recent, None, now).ready);
+        assert!(!evaluate(true, false, recent, None, now).ready);
+
+        // Before the first iteration, and once the loop has stalled
+        assert!(!evaluate(true, true, None, None, now).poll_loop);
+        let stalled = evaluate(true, true, Some(now - chrono::Duration::minutes(10)), recent, now);
+        assert!(!stalled.ready && !stalled.poll_loop);
+        assert!(stalled.last_processed_at.is_some());
+    }
+
+    /// Stand-in Docker daemon answering every request, including `/_ping`, with `status`
+    async fn docker_daemon(status: StatusCode) -> DockerManager {
+        let url = serve(Router::new().fallback(move || async move { (status, "OK") })).await;
+        DockerManager::with_daemon(&url, "raworc-session")
+    }
+
+    async fn check(pool: Pool<Postgres>, docker_manager: DockerManager) -> (StatusCode, serde_json::Value) {
+        let activity = Arc::new(Activity::default());
+        activity.record_poll();
+        let probe = HealthProbe { pool, docker_manager, activity };
+        let response = router(probe)
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        (response.status(), body_json(response).await)
+    }
+
+    #[tokio::test]
+    async fn unreachable_dependencies_make_the_operator_unready() {
+        let unreachable = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_secs(1))
+            .connect_lazy("postgres://raworc@127.0.0.1:1/raworc")
+            .unwrap();
+
+        let (status, report) = check(unreachable.clone(), docker_daemon(StatusCode::OK).await).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["database"], false);
+        assert_eq!(report["docker"], true);
+
+        let (status, report) = check(unreachable, docker_daemon(StatusCode::INTERNAL_SERVER_ERROR).await).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["docker"], false);
+        assert_eq!(report["poll_loop"], true);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn the_operator_is_ready_when_every_dependency_answers() {
+        let app = TestApp::new().await;
+
+        let (status, report) = check((*app.state.db).clone(), docker_daemon(StatusCode::OK).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["ready"], true);
+        assert_eq!(report["database"], true);
+        assert!(report["last_poll_at"].is_string());
+
+        let (status, report) = check((*app.state.db).clone(), docker_daemon(StatusCode::INTERNAL_SERVER_ERROR).await).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["database"], true);
+        assert_eq!(report["docker"], false);
+    }
+}
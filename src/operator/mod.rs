@@ -1,17 +1,34 @@
 mod docker_manager;
+mod health;
+mod node_api;
+mod reaper;
+mod reconciler;
 mod session_manager;
 
+pub use docker_manager::{LocalImage, SessionContainer};
+pub use node_api::PullStarted;
+pub use reconciler::{detect_drift, Drift};
 pub use session_manager::SessionManager;
 
 use anyhow::Result;
 
+use crate::shared::{Config, Service};
+
 pub async fn run() -> Result<()> {
     tracing::info!("Starting Raworc Operator...");
     
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
+    let config = Config::from_env(Service::Operator).map_err(|e| {
+        tracing::error!("{}", e);
+        anyhow::anyhow!(e)
+    })?;
+    config.log_effective(Service::Operator);
     
-    let manager = SessionManager::new(&database_url).await?;
+    let manager = SessionManager::new(&config).await?;
+    let mut app = health::router(manager.health_probe());
+    if let Some(api_key) = &config.node_api_key {
+        app = app.merge(node_api::router(manager.docker_manager().clone(), api_key));
+    }
+    health::spawn(config.health_port, app);
     manager.run().await?;
     
     Ok(())
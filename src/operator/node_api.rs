@@ -0,0 +1,282 @@
+//! Docker access for the API server. The server has no Docker socket of its own; for container
+//! and image administration, logs, shells and files it calls the node API of the operator that runs
+//! the container, authenticating with the shared RAWORC_NODE_API_KEY.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, Request, State,
+    },
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::docker_manager::{DockerManager, LocalImage, SessionContainer, ShellExec};
+
+/// Errors are plain text; the server maps their status onto its own error responses
+type NodeResult<T> = Result<T, (StatusCode, String)>;
+
+#[derive(Clone)]
+struct NodeApi {
+    docker: DockerManager,
+    key_digest: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContainerQuery {
+    pub workspace: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileQuery {
+    /// Absolute path in the container
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequest {
+    /// Defaults to the session image
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullStarted {
+    pub image: String,
+}
+
+fn digest(value: &str) -> Vec<u8> {
+    ring::digest::digest(&ring::digest::SHA256, value.as_bytes()).as_ref().to_vec()
+}
+
+/// Routes under `/node`, each requiring `Authorization: Bearer <api_key>`
+pub fn router(docker: DockerManager, api_key: &str) -> Router {
+    let api = NodeApi {
+        docker,
+        key_digest: digest(api_key),
+    };
+
+    Router::new()
+        .route("/node/containers", get(list_containers))
+        .route("/node/containers/{id}", delete(remove_container))
+        .route("/node/images", get(list_images))
+        .route("/node/images/pull", post(pull_image))
+        .route("/node/sessions/{id}/logs", get(container_logs))
+        .route("/node/sessions/{id}/shell", get(open_shell))
+        .route("/node/sessions/{id}/files", get(read_file).put(write_file))
+        .route_layer(middleware::from_fn_with_state(api.clone(), require_key))
+        .with_state(api)
+}
+
+async fn require_key(State(api): State<NodeApi>, request: Request, next: Next) -> Response {
+    // Comparing digests keeps the comparison time independent of the key
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(digest);
+    if presented.as_deref() != Some(api.key_digest.as_slice()) {
+        return (StatusCode::UNAUTHORIZED, "Invalid node API key").into_response();
+    }
+    next.run(request).await
+}
+
+/// 404 for containers or images Docker doesn't have, 500 for anything else
+fn docker_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    match e.downcast_ref::<bollard::errors::Error>() {
+        Some(bollard::errors::Error::DockerResponseServerError { status_code: 404, message }) => {
+            (StatusCode::NOT_FOUND, format!("{}: {}", context, message))
+        }
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e)),
+    }
+}
+
+async fn list_containers(
+    State(api): State<NodeApi>,
+    Query(query): Query<ContainerQuery>,
+) -> NodeResult<Json<Vec<SessionContainer>>> {
+    api.docker
+        .list_session_containers(query.workspace.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| docker_error("Failed to list containers", e))
+}
+
+async fn remove_container(State(api): State<NodeApi>, Path(id): Path<String>) -> NodeResult<StatusCode> {
+    api.docker
+        .remove_managed_container(&id)
+        .await
+        .map_err(|e| docker_error("Failed to remove container", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_images(State(api): State<NodeApi>) -> NodeResult<Json<Vec<LocalImage>>> {
+    api.docker
+        .list_images()
+        .await
+        .map(Json)
+        .map_err(|e| docker_error("Failed to list images", e))
+}
+
+/// Start pulling in the background, answering with the image being pulled; the outcome is
+/// only logged, and the image shows up in the listing once it is there
+async fn pull_image(State(api): State<NodeApi>, Json(req): Json<PullRequest>) -> (StatusCode, Json<PullStarted>) {
+    let image = req.image.unwrap_or_else(|| api.docker.session_image().to_string());
+    let docker = api.docker.clone();
+    let pulling = image.clone();
+    tokio::spawn(async move {
+        if let Err(e) = docker.pull_image(&pulling).await {
+            warn!("Failed to pull image {}: {}", pulling, e);
+        }
+    });
+    (StatusCode::ACCEPTED, Json(PullStarted { image }))
+}
+
+/// Streamed as Docker sends it, so long logs are never held in memory
+async fn container_logs(State(api): State<NodeApi>, Path(session_id): Path<Uuid>) -> NodeResult<Body> {
+    api.docker
+        .container_logs(session_id)
+        .await
+        .map_err(|e| docker_error("Failed to fetch container logs", e))?
+        .map(Body::from_stream)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Session has no container".to_string()))
+}
+
+/// Through the Docker archive API, so it works whatever volumes the image has
+async fn read_file(
+    State(api): State<NodeApi>,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<FileQuery>,
+) -> NodeResult<Vec<u8>> {
+    api.docker
+        .download_file(session_id, &query.path)
+        .await
+        .map_err(|e| docker_error("Failed to read file", e))
+}
+
+async fn write_file(
+    State(api): State<NodeApi>,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<FileQuery>,
+    contents: Bytes,
+) -> NodeResult<StatusCode> {
+    api.docker
+        .upload_file(session_id, &query.path, &contents)
+        .await
+        .map_err(|e| docker_error("Failed to write file", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn open_shell(
+    State(api): State<NodeApi>,
+    Path(session_id): Path<Uuid>,
+    ws: WebSocketUpgrade,
+) -> NodeResult<Response> {
+    // Start the shell before upgrading so failures are still reported as HTTP errors
+    let shell = api
+        .docker
+        .open_shell(session_id)
+        .await
+        .map_err(|e| docker_error("Failed to open shell", e))?;
+
+    Ok(ws.on_upgrade(move |socket| pipe_shell(socket, shell, session_id)))
+}
+
+/// Copy client frames to the shell's stdin and its output back until either side ends
+async fn pipe_shell(socket: WebSocket, shell: ShellExec, session_id: Uuid) {
+    let (mut sender, mut receiver) = socket.split();
+    let ShellExec { mut output, mut input } = shell;
+
+    loop {
+        tokio::select! {
+            chunk = output.next() => match chunk {
+                Some(Ok(chunk)) => {
+                    if sender.send(Message::Binary(chunk.into_bytes())).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Err(e)) => {
+                    warn!("Shell output for session {} failed: {}", session_id, e);
+                    break;
+                }
+                None => break,
+            },
+            message = receiver.next() => {
+                let bytes = match message {
+                    Some(Ok(Message::Text(text))) => text.as_bytes().to_vec(),
+                    Some(Ok(Message::Binary(bytes))) => bytes.to_vec(),
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                };
+                if input.write_all(&bytes).await.is_err() || input.flush().await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = input.shutdown().await;
+    let _ = sender.send(Message::Close(None)).await;
+    info!("Closed interactive shell in session {}", session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::{router, DockerManager};
+
+    const KEY: &str = "node-api-key-that-is-long-enough-for-tests";
+
+    fn app() -> axum::Router {
+        router(DockerManager::unreachable("raworc-session"), KEY)
+    }
+
+    async fn status(authorization: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/node/images");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn node_api_requires_the_shared_key() {
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer not-the-key")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some(KEY)).await, StatusCode::UNAUTHORIZED);
+        // Past the check, the request reaches Docker, which isn't there
+        assert_eq!(status(Some(&format!("Bearer {}", KEY))).await, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn pulls_are_accepted_before_they_finish() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/node/images/pull")
+            .header(header::AUTHORIZATION, format!("Bearer {}", KEY))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        // Accepted although the pull itself fails without Docker
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let started: super::PullStarted = serde_json::from_slice(&body).unwrap();
+        assert_eq!(started.image, "raworc_host:latest");
+    }
+}
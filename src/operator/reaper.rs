@@ -0,0 +1,169 @@
+use anyhow::Result;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::docker_manager::DockerManager;
+use crate::shared::config::RetentionConfig;
+
+/// Hard-deletes soft-deleted rows once they fall outside their retention window
+pub struct Reaper {
+    config: RetentionConfig,
+}
+
+impl Reaper {
+    pub fn new(config: RetentionConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    pub async fn run_once(&self, pool: &Pool<Postgres>, docker_manager: &DockerManager) -> Result<()> {
+        let sessions = self.purge_deleted_sessions(pool, docker_manager).await?;
+        let tasks = purge_finished_tasks(pool, self.config.finished_tasks).await?;
+        let agents = match self.config.deleted_agents {
+            Some(retention) => purge_deleted_agents(pool, retention).await?,
+            None => 0,
+        };
+
+        if sessions + tasks + agents > 0 {
+            info!(
+                "Reaper purged {} sessions, {} tasks and {} agents",
+                sessions, tasks, agents
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove containers and volumes of expired sessions, then delete the rows.
+    /// Messages, tasks and agent assignments go with them via ON DELETE CASCADE.
+    async fn purge_deleted_sessions(&self, pool: &Pool<Postgres>, docker_manager: &DockerManager) -> Result<u64> {
+        let expired = expired_deleted_sessions(pool, self.config.deleted_sessions).await?;
+
+        let mut purged = 0;
+        for session_id in expired {
+            // Shows why the container went if the row outlives this run
+            sqlx::query("UPDATE sessions SET termination_cause = 'ttl_expired', termination_reason = 'Retention period after deletion expired' WHERE id = $1")
+                .bind(session_id)
+                .execute(pool)
+                .await?;
+
+            // Keep the row if the container can't be removed so a later run retries it
+            if let Err(e) = docker_manager.remove_container_if_exists(session_id).await {
+                warn!("Skipping purge of session {}: {}", session_id, e);
+                continue;
+            }
+
+            purged += sqlx::query("DELETE FROM sessions WHERE id = $1 AND deleted_at IS NOT NULL")
+                .bind(session_id)
+                .execute(pool)
+                .await?
+                .rows_affected();
+        }
+        Ok(purged)
+    }
+}
+
+/// Soft-deleted sessions whose `deleted_at` is older than the retention window
+pub async fn expired_deleted_sessions(pool: &Pool<Postgres>, retention: Duration) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT id
+        FROM sessions
+        WHERE deleted_at IS NOT NULL
+          AND deleted_at < NOW() - make_interval(secs => $1)
+        ORDER BY deleted_at
+        LIMIT 100
+        "#
+    )
+    .bind(retention.as_secs_f64())
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete tasks that completed or failed longer ago than the retention window
+pub async fn purge_finished_tasks(pool: &Pool<Postgres>, retention: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM session_tasks
+        WHERE status IN ('completed', 'failed')
+          AND completed_at < NOW() - make_interval(secs => $1)
+        "#
+    )
+    .bind(retention.as_secs_f64())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Delete agents soft-deleted longer ago than the retention window. Deactivated agents
+/// were switched off on purpose and are kept.
+pub async fn purge_deleted_agents(pool: &Pool<Postgres>, retention: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM agents
+        WHERE deleted_at IS NOT NULL
+          AND deleted_at < NOW() - make_interval(secs => $1)
+        "#
+    )
+    .bind(retention.as_secs_f64())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    use super::purge_deleted_agents;
+    use crate::server::rest::test_support::{unique, TestApp};
+
+    /// Insert an agent, soft-deleted and deactivated the given number of days ago
+    async fn agent(app: &TestApp, deleted_days_ago: Option<i32>, deactivated_days_ago: Option<i32>) -> Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO agents (name, instructions, model, active, updated_at, deleted_at)
+            VALUES ($1, 'test', 'test-model', $3 IS NULL,
+                    NOW() - make_interval(days => COALESCE($3, 0)),
+                    NOW() - make_interval(days => $2))
+            RETURNING id
+            "#,
+        )
+        .bind(unique("agent"))
+        .bind(deleted_days_ago)
+        .bind(deactivated_days_ago)
+        .fetch_one(&*app.state.db)
+        .await
+        .unwrap()
+    }
+
+    async fn exists(app: &TestApp, id: Uuid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM agents WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&*app.state.db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn only_agents_deleted_before_the_window_are_purged() {
+        let app = TestApp::new().await;
+        let expired = agent(&app, Some(40), None).await;
+        let recent = agent(&app, Some(5), None).await;
+        let deactivated = agent(&app, None, Some(40)).await;
+
+        purge_deleted_agents(&app.state.db, Duration::from_secs(30 * 86_400)).await.unwrap();
+
+        assert!(!exists(&app, expired).await);
+        assert!(exists(&app, recent).await);
+        assert!(exists(&app, deactivated).await);
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::docker_manager::{DockerManager, SessionContainer};
+use crate::shared::models::{Session, SessionState, TerminationCause};
+
+/// A disagreement between a session's recorded state and what Docker reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// Session is READY or BUSY but its container is gone or neither running nor restarting
+    LostContainer { session_id: Uuid },
+    /// Managed container with no live INIT, READY, BUSY or IDLE session behind it;
+    /// `workspace` is its `raworc.workspace` label, for telling whose container it was
+    OrphanedContainer { container_id: String, name: String, workspace: Option<String> },
+}
+
+/// Compare Docker's managed containers with the sessions that should own them.
+/// INIT and IDLE sessions own their container without requiring it to run, so a
+/// container that is still being created or is stopped while idle is never reported.
+/// A restarting container counts as running; a paused or exited one doesn't.
+pub fn detect_drift(containers: &[SessionContainer], sessions: &[(Uuid, SessionState)]) -> Vec<Drift> {
+    let owners: HashSet<Uuid> = sessions.iter().map(|(id, _)| *id).collect();
+    let running: HashSet<Uuid> = containers
+        .iter()
+        .filter(|c| c.state.is_live())
+        .filter_map(|c| c.session_id)
+        .collect();
+
+    let lost = sessions
+        .iter()
+        .filter(|(id, state)| matches!(state, SessionState::Ready | SessionState::Busy) && !running.contains(id))
+        .map(|(id, _)| Drift::LostContainer { session_id: *id });
+
+    let orphaned = containers
+        .iter()
+        .filter(|c| !matches!(c.session_id, Some(id) if owners.contains(&id)))
+        .map(|c| Drift::OrphanedContainer {
+            container_id: c.id.clone(),
+            name: c.name.clone(),
+            workspace: c.workspace.clone(),
+        });
+
+    lost.chain(orphaned).collect()
+}
+
+/// Consecutive reconcile runs in which each session's container was found lost
+#[derive(Debug, Default)]
+pub struct LostContainerCounts {
+    counts: HashMap<Uuid, u32>,
+}
+
+impl LostContainerCounts {
+    /// Record one run's lost sessions and return those lost for `threshold` runs in a row.
+    /// Sessions missing from `lost` had a healthy check, which resets their count.
+    pub fn observe(&mut self, lost: &[Uuid], threshold: u32) -> Vec<Uuid> {
+        self.counts.retain(|id, _| lost.contains(id));
+
+        let mut confirmed = Vec::new();
+        for id in lost {
+            let count = self.counts.entry(*id).or_insert(0);
+            *count += 1;
+            if *count >= threshold {
+                confirmed.push(*id);
+            }
+        }
+        for id in &confirmed {
+            self.counts.remove(id);
+        }
+        confirmed
+    }
+
+    pub fn count(&self, session_id: Uuid) -> u32 {
+        self.counts.get(&session_id).copied().unwrap_or(0)
+    }
+}
+
+/// Periodically corrects drift between session rows and Docker
+pub struct Reconciler {
+    interval: Duration,
+    /// Consecutive lost-container checks before a session is marked ERROR,
+    /// so a container that briefly stops between restarts doesn't fail its session
+    failure_threshold: u32,
+    lost_counts: Mutex<LostContainerCounts>,
+    /// Remove orphaned containers instead of only logging them
+    remove_orphans: bool,
+    /// Node this operator runs on; sessions whose container another node created are skipped
+    node_name: Option<String>,
+}
+
+impl Reconciler {
+    pub fn new(interval: Duration, failure_threshold: u32, remove_orphans: bool, node_name: Option<String>) -> Self {
+        Self {
+            interval,
+            failure_threshold,
+            lost_counts: Mutex::new(LostContainerCounts::default()),
+            remove_orphans,
+            node_name,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub async fn run_once(&self, pool: &Pool<Postgres>, docker_manager: &DockerManager) -> Result<()> {
+        let containers = docker_manager.list_session_containers(None).await?;
+        let sessions: Vec<(Uuid, SessionState)> = Session::find_expecting_container(pool, self.node_name.as_deref())
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.state))
+            .collect();
+
+        let mut lost = Vec::new();
+        for drift in detect_drift(&containers, &sessions) {
+            match drift {
+                Drift::LostContainer { session_id } => lost.push(session_id),
+                Drift::OrphanedContainer { container_id, name, workspace } => {
+                    let workspace = workspace.as_deref().unwrap_or("unknown");
+                    if !self.remove_orphans {
+                        warn!("Container {} (workspace {}) has no live session; leaving it in place", name, workspace);
+                        continue;
+                    }
+                    info!("Removing orphaned container {} (workspace {})", name, workspace);
+                    if let Err(e) = docker_manager.remove_managed_container(&container_id).await {
+                        warn!("Failed to remove orphaned container {}: {}", name, e);
+                    }
+                }
+            }
+        }
+
+        let confirmed = {
+            let mut counts = self.lost_counts.lock().unwrap();
+            let confirmed = counts.observe(&lost, self.failure_threshold);
+            for session_id in lost.iter().filter(|id| !confirmed.contains(id)) {
+                info!(
+                    "Container for session {} is not running ({} of {} checks)",
+                    session_id,
+                    counts.count(*session_id),
+                    self.failure_threshold
+                );
+            }
+            confirmed
+        };
+
+        for session_id in confirmed {
+            // A container that is still there stopped on its own; one that is gone was removed
+            let cause = if containers.iter().any(|c| c.session_id == Some(session_id)) {
+                TerminationCause::HealthCheckFailed
+            } else {
+                TerminationCause::ContainerLost
+            };
+            warn!("Container for session {} is no longer running; marking it ERROR", session_id);
+            mark_container_lost(pool, session_id, cause).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Move a READY/BUSY session whose container disappeared or stopped to ERROR.
+/// The state guard leaves sessions alone that changed since the drift was detected.
+async fn mark_container_lost(pool: &Pool<Postgres>, session_id: Uuid, cause: TerminationCause) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE sessions
+        SET state = 'ERROR',
+            terminated_at = NOW(),
+            termination_reason = 'Container is no longer running',
+            termination_cause = $2
+        WHERE id = $1 AND state IN ('READY', 'BUSY') AND deleted_at IS NULL
+        "#
+    )
+    .bind(session_id)
+    .bind(cause)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::docker_manager::ContainerStatus;
+
+    fn container(session_id: Uuid, state: ContainerStatus) -> SessionContainer {
+        SessionContainer {
+            id: format!("c-{}", session_id),
+            name: format!("raworc-session-{}", session_id),
+            session_id: Some(session_id),
+            workspace: Some("team-a".to_string()),
+            state,
+            status: String::new(),
+        }
+    }
+
+    #[test]
+    fn restarting_container_is_not_lost() {
+        let id = Uuid::new_v4();
+        let drift = detect_drift(&[container(id, ContainerStatus::Restarting)], &[(id, SessionState::Busy)]);
+        assert!(drift.is_empty());
+    }
+
+    #[test]
+    fn exited_container_of_ready_session_is_lost() {
+        let id = Uuid::new_v4();
+        let drift = detect_drift(&[container(id, ContainerStatus::Exited)], &[(id, SessionState::Ready)]);
+        assert_eq!(drift, vec![Drift::LostContainer { session_id: id }]);
+    }
+
+    #[test]
+    fn container_without_session_is_orphaned() {
+        let id = Uuid::new_v4();
+        let drift = detect_drift(&[container(id, ContainerStatus::Running)], &[]);
+        assert_eq!(
+            drift,
+            vec![Drift::OrphanedContainer {
+                container_id: format!("c-{}", id),
+                name: format!("raworc-session-{}", id),
+                workspace: Some("team-a".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn lost_container_is_confirmed_after_consecutive_runs() {
+        let id = Uuid::new_v4();
+        let mut counts = LostContainerCounts::default();
+        assert!(counts.observe(&[id], 3).is_empty());
+        assert!(counts.observe(&[id], 3).is_empty());
+        assert_eq!(counts.count(id), 2);
+        assert_eq!(counts.observe(&[id], 3), vec![id]);
+        assert_eq!(counts.count(id), 0);
+    }
+
+    #[test]
+    fn healthy_check_resets_lost_count() {
+        let (flapping, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut counts = LostContainerCounts::default();
+        counts.observe(&[flapping], 2);
+        counts.observe(&[other], 2);
+        assert_eq!(counts.count(flapping), 0);
+        assert!(counts.observe(&[flapping], 2).is_empty());
+    }
+}
@@ -2,13 +2,22 @@ use anyhow::Result;
 use bollard::Docker;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
-use std::time::Duration;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use super::docker_manager::DockerManager;
+use super::health::{Activity, HealthProbe};
+use super::reaper::Reaper;
+use super::reconciler::Reconciler;
+use crate::shared::{connect_with_retry, host_token, pool_options, resolve_instance_id, Config};
+use crate::shared::models::{find_denied_env_var, Secret, Session, TaskPayload, WorkspaceSettings};
+use crate::shared::models::node::register_node;
+use crate::shared::models::secret::requested_secret_names;
+use crate::shared::secrets::SecretsCipher;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 struct SessionTask {
@@ -27,29 +36,99 @@ struct SessionTask {
 pub struct SessionManager {
     pool: Pool<Postgres>,
     docker_manager: DockerManager,
+    secrets: Option<SecretsCipher>,
+    reaper: Reaper,
+    reconciler: Reconciler,
+    activity: Arc<Activity>,
+    /// Ceiling on running session containers; create tasks wait in the queue while it is reached
+    max_running_containers: Option<u64>,
+    /// Globally denied container variables, checked again here in case the server was bypassed
+    denied_env_vars: Vec<String>,
+    /// Claims tasks of sessions pinned to this node as well as unpinned ones
+    node_name: Option<String>,
+    /// Registered with `node_name` so the server can reach this operator's node API
+    node_url: Option<String>,
 }
 
+/// Tasks claimed per poll
+const TASK_BATCH_SIZE: i64 = 5;
+
 impl SessionManager {
-    pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
-            .await?;
+    pub async fn new(config: &Config) -> Result<Self> {
+        let pool = connect_with_retry(pool_options(&config.database), &config.database_url, config.database.connect_attempts).await?;
+
+        let instance_id = resolve_instance_id(&pool, config.instance_id.as_deref()).await?;
+        info!("Instance id: {}", instance_id);
 
         let docker = Docker::connect_with_socket_defaults()?;
-        let docker_manager = DockerManager::new(docker);
+        let docker_manager = DockerManager::new(docker, &config.containers, instance_id, config.instance_id.is_none());
+        Self::with_docker(pool, docker_manager, config)
+    }
 
+    /// Separate from `new` so tests can hand in a manager for a stand-in Docker daemon
+    fn with_docker(pool: Pool<Postgres>, docker_manager: DockerManager, config: &Config) -> Result<Self> {
         Ok(Self {
             pool,
             docker_manager,
+            secrets: config.secrets_key.as_deref().map(SecretsCipher::new).transpose()?,
+            reaper: Reaper::new(config.retention.clone()),
+            reconciler: Reconciler::new(
+                config.reconcile_interval,
+                config.container_failure_threshold,
+                config.remove_orphaned_containers,
+                config.node_name.clone(),
+            ),
+            activity: Arc::new(Activity::default()),
+            max_running_containers: config.containers.max_running,
+            denied_env_vars: config.containers.denied_env_vars.clone(),
+            node_name: config.node_name.clone(),
+            node_url: config.node_url.clone(),
         })
     }
 
+    /// Docker client shared with the node API
+    pub fn docker_manager(&self) -> &DockerManager {
+        &self.docker_manager
+    }
+
+    pub fn health_probe(&self) -> HealthProbe {
+        HealthProbe {
+            pool: self.pool.clone(),
+            docker_manager: self.docker_manager.clone(),
+            activity: self.activity.clone(),
+        }
+    }
+
     pub async fn run(&self) -> Result<()> {
         info!("Session Manager started, polling for tasks...");
 
+        let mut last_reap: Option<Instant> = None;
+        let mut last_reconcile: Option<Instant> = None;
         loop {
-            match self.process_pending_tasks().await {
+            if last_reap.is_none_or(|at| at.elapsed() >= self.reaper.interval()) {
+                if let Err(e) = self.reaper.run_once(&self.pool, &self.docker_manager).await {
+                    error!("Error purging expired rows: {}", e);
+                }
+                last_reap = Some(Instant::now());
+            }
+
+            if last_reconcile.is_none_or(|at| at.elapsed() >= self.reconciler.interval()) {
+                // Registering the node lets sessions be pinned to it
+                if let Some(node_name) = &self.node_name {
+                    if let Err(e) = register_node(&self.pool, node_name, self.node_url.as_deref()).await {
+                        error!("Error registering node {}: {}", node_name, e);
+                    }
+                }
+                if let Err(e) = self.reconciler.run_once(&self.pool, &self.docker_manager).await {
+                    error!("Error reconciling sessions with Docker: {}", e);
+                }
+                last_reconcile = Some(Instant::now());
+            }
+
+            let result = self.process_pending_tasks().await;
+            self.activity.record_poll();
+
+            match result {
                 Ok(processed) => {
                     if processed == 0 {
                         sleep(Duration::from_secs(2)).await;
@@ -69,7 +148,10 @@ impl SessionManager {
 
         for task in tasks {
             match self.process_task(task).await {
-                Ok(_) => processed += 1,
+                Ok(_) => {
+                    processed += 1;
+                    self.activity.record_processed();
+                }
                 Err(e) => error!("Failed to process task: {}", e),
             }
         }
@@ -78,40 +160,25 @@ impl SessionManager {
     }
 
     async fn fetch_pending_tasks(&self) -> Result<Vec<SessionTask>> {
-        let tasks = sqlx::query_as::<_, SessionTask>(
-            r#"
-            UPDATE session_tasks
-            SET status = 'processing',
-                started_at = NOW(),
-                updated_at = NOW()
-            WHERE id IN (
-                SELECT id
-                FROM session_tasks
-                WHERE status = 'pending'
-                ORDER BY created_at
-                LIMIT 5
-                FOR UPDATE SKIP LOCKED
-            )
-            RETURNING *
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        // Only claim as many create and reactivate tasks as there is container capacity; the rest stay pending
+        let create_slots = match self.max_running_containers {
+            Some(max) => {
+                let running = Session::count_running_containers(&self.pool).await?;
+                (max as i64 - running).clamp(0, TASK_BATCH_SIZE)
+            }
+            None => TASK_BATCH_SIZE,
+        };
 
-        Ok(tasks)
+        claim_tasks(&self.pool, TASK_BATCH_SIZE, create_slots, self.node_name.as_deref()).await
     }
 
     async fn process_task(&self, task: SessionTask) -> Result<()> {
         info!("Processing task {} of type {}", task.id, task.task_type);
 
-        let result = match task.task_type.as_str() {
-            "create_session" => self.handle_create_session(task.clone()).await,
-            "destroy_session" => self.handle_destroy_session(task.clone()).await,
-            "execute_command" => self.handle_execute_command(task.clone()).await,
-            _ => {
-                warn!("Unknown task type: {}", task.task_type);
-                Err(anyhow::anyhow!("Unknown task type"))
-            }
+        let result = match TaskPayload::parse(&task.task_type, &task.payload) {
+            Ok(payload) => self.dispatch(task.session_id, payload).await,
+            // Unknown task types and payloads missing fields fail the task with serde's reason
+            Err(e) => Err(anyhow::anyhow!("Invalid {} task: {}", task.task_type, e)),
         };
 
         match result {
@@ -119,6 +186,11 @@ impl SessionManager {
                 self.mark_task_completed(task.id).await?;
                 info!("Task {} completed successfully", task.id);
             }
+            // The session waits in the queue for resources to free up, as it does for a container slot
+            Err(e) if is_out_of_capacity(&e) => {
+                self.requeue_task(task.id).await?;
+                warn!("Task {} waits for capacity on this node: {}", task.id, e);
+            }
             Err(e) => {
                 self.mark_task_failed(task.id, &e.to_string()).await?;
                 error!("Task {} failed: {}", task.id, e);
@@ -128,30 +200,91 @@ impl SessionManager {
         Ok(())
     }
 
-    async fn handle_create_session(&self, task: SessionTask) -> Result<()> {
-        let session_id = task.session_id;
+    async fn dispatch(&self, session_id: Uuid, payload: TaskPayload) -> Result<()> {
+        match payload {
+            TaskPayload::CreateSession { .. } => self.handle_create_session(session_id).await,
+            TaskPayload::DestroySession {} => self.handle_destroy_session(session_id).await,
+            TaskPayload::StopSession {} => self.handle_stop_session(session_id).await,
+            TaskPayload::ReactivateSession {} => self.handle_reactivate_session(session_id).await,
+            TaskPayload::ExecuteCommand { command } => self.handle_execute_command(session_id, &command).await,
+        }
+    }
+
+    async fn handle_create_session(&self, session_id: Uuid) -> Result<()> {
+        
+        let session = self.find_session(session_id).await?;
+        let secret_env = self.resolve_secret_env(&session).await?;
+        let tier = WorkspaceSettings::tier_for(&self.pool, &session.workspace).await?;
+        
+        let host_token = self.issue_host_token(session_id).await?;
         
         info!("Creating container for session {}", session_id);
-        self.docker_manager.create_container(session_id).await?;
+        let container_id = self.docker_manager.create_container(&session, tier, &host_token, secret_env).await?;
         
         sqlx::query(
-            "UPDATE sessions SET state = 'READY', started_at = NOW(), last_activity_at = NOW() WHERE id = $1"
+            "UPDATE sessions SET state = 'READY', container_id = $2, node_name = $3, started_at = NOW(), last_activity_at = NOW() WHERE id = $1"
         )
         .bind(session_id)
+        .bind(&container_id)
+        .bind(&self.node_name)
         .execute(&self.pool)
         .await?;
         
         Ok(())
     }
 
-    async fn handle_destroy_session(&self, task: SessionTask) -> Result<()> {
-        let session_id = task.session_id;
+    async fn find_session(&self, session_id: Uuid) -> Result<Session> {
+        Session::find_by_id(&self.pool, session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Session {} not found", session_id))
+    }
+
+    /// New token for the host of a container about to be created; the previous container's stops working
+    async fn issue_host_token(&self, session_id: Uuid) -> Result<String> {
+        let token = host_token::generate(session_id)?;
+        Session::set_host_token_hash(&self.pool, session_id, &host_token::hash(&token)).await?;
+        Ok(token)
+    }
+
+    /// Decrypt the workspace secrets named in the session's `metadata.secrets` into `NAME=value` entries
+    async fn resolve_secret_env(&self, session: &Session) -> Result<Vec<String>> {
+        let names = requested_secret_names(&session.metadata)
+            .map_err(|_| anyhow::anyhow!("Session metadata 'secrets' must be a list of secret names"))?;
+        
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let denied = WorkspaceSettings::denied_env_vars_for(&self.pool, &session.workspace, &self.denied_env_vars).await?;
+        if let Some(name) = find_denied_env_var(&names, &denied) {
+            anyhow::bail!("Environment variable '{}' is not allowed in session containers", name);
+        }
+        
+        let cipher = self.secrets.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Session references secrets but RAWORC_SECRETS_KEY is not set on the operator")
+        })?;
+        
+        let mut env = Vec::with_capacity(names.len());
+        for name in names {
+            let secret = Secret::find_by_name(&self.pool, &session.workspace, &name)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Secret '{}' not found in workspace '{}'", name, session.workspace))?;
+            let value = cipher.decrypt(&secret.workspace, &secret.name, &secret.nonce, &secret.ciphertext)?;
+            env.push(format!("{}={}", secret.name, value));
+        }
+        
+        info!("Injecting {} secrets into session {}", env.len(), session.id);
+        Ok(env)
+    }
+
+    async fn handle_destroy_session(&self, session_id: Uuid) -> Result<()> {
         
         info!("Destroying container for session {}", session_id);
         self.docker_manager.destroy_container(session_id).await?;
         
+        // Containers are only destroyed once their session is deleted
         sqlx::query(
-            "UPDATE sessions SET state = 'IDLE', terminated_at = NOW() WHERE id = $1"
+            "UPDATE sessions SET state = 'IDLE', terminated_at = NOW(), termination_cause = COALESCE(termination_cause, 'deleted') WHERE id = $1"
         )
         .bind(session_id)
         .execute(&self.pool)
@@ -160,12 +293,44 @@ impl SessionManager {
         Ok(())
     }
 
-    async fn handle_execute_command(&self, task: SessionTask) -> Result<()> {
-        let session_id = task.session_id;
-        let command = task.payload["command"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing command in payload"))?;
-        
+    async fn handle_stop_session(&self, session_id: Uuid) -> Result<()> {
+        info!("Stopping container for session {}", session_id);
+        self.docker_manager.stop_container(session_id).await
+    }
+
+    async fn handle_reactivate_session(&self, session_id: Uuid) -> Result<()> {
+        if self.docker_manager.restart_stopped_container(session_id).await? {
+            sqlx::query(
+                "UPDATE sessions SET state = CASE WHEN state = 'INIT' THEN 'READY'::session_state ELSE state END, last_activity_at = NOW() WHERE id = $1"
+            )
+                .bind(session_id)
+                .execute(&self.pool)
+                .await?;
+            return Ok(());
+        }
+
+        let session = self.find_session(session_id).await?;
+        let secret_env = self.resolve_secret_env(&session).await?;
+        let tier = WorkspaceSettings::tier_for(&self.pool, &session.workspace).await?;
+
+        let host_token = self.issue_host_token(session_id).await?;
+
+        info!("Container for session {} is gone; creating a new one", session_id);
+        let container_id = self.docker_manager.create_container(&session, tier, &host_token, secret_env).await?;
+
+        sqlx::query(
+            "UPDATE sessions SET container_id = $2, node_name = $3, state = CASE WHEN state = 'INIT' THEN 'READY'::session_state ELSE state END, last_activity_at = NOW() WHERE id = $1"
+        )
+        .bind(session_id)
+        .bind(&container_id)
+        .bind(&self.node_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn handle_execute_command(&self, session_id: Uuid, command: &str) -> Result<()> {
         info!("Executing command in session {}: {}", session_id, command);
         let output = self.docker_manager.execute_command(session_id, command).await?;
         
@@ -202,6 +367,23 @@ impl SessionManager {
         Ok(())
     }
 
+    async fn requeue_task(&self, task_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE session_tasks
+            SET status = 'pending',
+                started_at = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn mark_task_failed(&self, task_id: Uuid, error: &str) -> Result<()> {
         sqlx::query(
             r#"
@@ -220,4 +402,240 @@ impl SessionManager {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// Whether Docker refused a container for lack of memory, disk or processes on the node, which
+/// frees up as other sessions end, unlike a missing image or a bad configuration
+fn is_out_of_capacity(e: &anyhow::Error) -> bool {
+    const MARKERS: &[&str] = &[
+        "cannot allocate memory",
+        "out of memory",
+        "no space left on device",
+        "insufficient",
+        "resource temporarily unavailable",
+    ];
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<bollard::errors::Error>())
+        .any(|docker| {
+            let message = docker.to_string().to_lowercase();
+            MARKERS.iter().any(|marker| message.contains(marker))
+        })
+}
+
+/// Claim up to `batch_size` pending tasks plus `create_slots` tasks that start a container
+/// (create or reactivate) for the operator on
+/// `node_name`. Once a session has a container its tasks go to the node that created it, since
+/// no other node's Docker has it; until then to its pinned node, or to any node when unpinned.
+async fn claim_tasks(
+    pool: &Pool<Postgres>,
+    batch_size: i64,
+    create_slots: i64,
+    node_name: Option<&str>,
+) -> Result<Vec<SessionTask>> {
+    let tasks = sqlx::query_as::<_, SessionTask>(
+        r#"
+        WITH claimable_sessions AS (
+            SELECT id FROM sessions
+            WHERE CASE WHEN container_id IS NULL
+                       THEN node_selector IS NULL OR node_selector = $3
+                       ELSE node_name IS NOT DISTINCT FROM $3
+                  END
+        ),
+        other_tasks AS (
+            SELECT id
+            FROM session_tasks
+            WHERE status = 'pending' AND task_type NOT IN ('create_session', 'reactivate_session')
+              AND session_id IN (SELECT id FROM claimable_sessions)
+            ORDER BY created_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        ),
+        create_tasks AS (
+            SELECT id
+            FROM session_tasks
+            WHERE status = 'pending' AND task_type IN ('create_session', 'reactivate_session')
+              AND session_id IN (SELECT id FROM claimable_sessions)
+            ORDER BY created_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE session_tasks
+        SET status = 'processing',
+            started_at = NOW(),
+            updated_at = NOW()
+        WHERE id IN (SELECT id FROM other_tasks UNION ALL SELECT id FROM create_tasks)
+        RETURNING *
+        "#,
+    )
+    .bind(batch_size)
+    .bind(create_slots)
+    .bind(node_name)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use axum::{http::StatusCode, Json, Router};
+
+    use super::{claim_tasks, is_out_of_capacity, SessionManager};
+    use crate::operator::docker_manager::DockerManager;
+    use crate::server::rest::test_support::{serve, unique, TestApp};
+    use crate::shared::models::{Session, TaskPayload};
+
+    fn docker_error(message: &str) -> anyhow::Error {
+        anyhow::Error::new(bollard::errors::Error::DockerResponseServerError {
+            status_code: 500,
+            message: message.to_string(),
+        })
+    }
+
+    #[test]
+    fn only_resource_exhaustion_waits_for_capacity() {
+        assert!(is_out_of_capacity(&docker_error(
+            "failed to create task for container: OCI runtime create failed: cannot allocate memory"
+        )));
+        assert!(is_out_of_capacity(&docker_error("write /var/lib/docker/tmp: no space left on device").context("Creating container")));
+
+        assert!(!is_out_of_capacity(&docker_error("No such image: raworc-host:latest")));
+        // The same words outside a Docker error, e.g. in a session's own failure, don't count
+        assert!(!is_out_of_capacity(&anyhow::anyhow!("out of memory")));
+    }
+
+    /// Claim every pending task, however many earlier tests left behind, returning the sessions
+    async fn claimed_sessions(app: &TestApp, node_name: &str) -> Vec<Uuid> {
+        claim_tasks(&app.state.db, 100_000, 100_000, Some(node_name))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.session_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn tasks_go_to_the_pinned_node_and_then_to_the_node_with_the_container() {
+        let app = TestApp::new().await;
+        let (node_a, node_b) = (unique("node"), unique("node"));
+
+        let pinned = app.create_session(&unique("user")).await;
+        sqlx::query("UPDATE sessions SET node_selector = $2 WHERE id = $1")
+            .bind(pinned)
+            .bind(&node_a)
+            .execute(&*app.state.db)
+            .await
+            .unwrap();
+        let create = TaskPayload::CreateSession { user_id: "user".to_string(), agent_ids: Vec::new() };
+        Session::enqueue_task(&*app.state.db, pinned, create).await.unwrap();
+
+        // Unpinned, but its container runs on node A
+        let placed = app.create_session(&unique("user")).await;
+        sqlx::query("UPDATE sessions SET container_id = 'container', node_name = $2 WHERE id = $1")
+            .bind(placed)
+            .bind(&node_a)
+            .execute(&*app.state.db)
+            .await
+            .unwrap();
+        Session::enqueue_task(&*app.state.db, placed, TaskPayload::DestroySession {}).await.unwrap();
+
+        let claimed = claimed_sessions(&app, &node_b).await;
+        assert!(!claimed.contains(&pinned));
+        assert!(!claimed.contains(&placed));
+
+        let claimed = claimed_sessions(&app, &node_a).await;
+        assert!(claimed.contains(&pinned));
+        assert!(claimed.contains(&placed));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn reconciling_skips_containers_other_nodes_created() {
+        let app = TestApp::new().await;
+        let (node_a, node_b) = (unique("node"), unique("node"));
+        let session_id = app.create_session(&unique("user")).await;
+        sqlx::query("UPDATE sessions SET state = 'READY', container_id = 'container', node_name = $2 WHERE id = $1")
+            .bind(session_id)
+            .bind(&node_a)
+            .execute(&*app.state.db)
+            .await
+            .unwrap();
+
+        let expecting = |node: Option<String>| {
+            let db = app.state.db.clone();
+            async move {
+                Session::find_expecting_container(&db, node.as_deref())
+                    .await
+                    .unwrap()
+                    .iter()
+                    .any(|session| session.id == session_id)
+            }
+        };
+        assert!(expecting(Some(node_a)).await);
+        assert!(!expecting(Some(node_b)).await);
+        assert!(!expecting(None).await);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn reactivations_wait_for_a_container_slot() {
+        let app = TestApp::new().await;
+        let node = unique("node");
+        let (idle, stopping) = (app.create_session(&unique("user")).await, app.create_session(&unique("user")).await);
+        for session_id in [idle, stopping] {
+            sqlx::query("UPDATE sessions SET container_id = 'container', node_name = $2 WHERE id = $1")
+                .bind(session_id)
+                .bind(&node)
+                .execute(&*app.state.db)
+                .await
+                .unwrap();
+        }
+        Session::enqueue_task(&*app.state.db, idle, TaskPayload::ReactivateSession {}).await.unwrap();
+        Session::enqueue_task(&*app.state.db, stopping, TaskPayload::DestroySession {}).await.unwrap();
+
+        let claimed: Vec<Uuid> = claim_tasks(&app.state.db, 100_000, 0, Some(&node))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.session_id)
+            .collect();
+        assert!(claimed.contains(&stopping));
+        assert!(!claimed.contains(&idle));
+
+        assert!(claimed_sessions(&app, &node).await.contains(&idle));
+    }
+
+    /// Stand-in Docker daemon answering every request with `status`
+    async fn docker_daemon(status: StatusCode, message: &'static str) -> String {
+        serve(Router::new().fallback(move || async move { (status, Json(serde_json::json!({ "message": message }))) })).await
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn destroying_a_session_whose_container_is_gone_succeeds() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session(&unique("user")).await;
+        let state = || {
+            sqlx::query_as::<_, (String, Option<String>)>("SELECT state::text, termination_cause::text FROM sessions WHERE id = $1")
+                .bind(session_id)
+                .fetch_one(&*app.state.db)
+        };
+        let manager = |url: String| {
+            SessionManager::with_docker((*app.state.db).clone(), DockerManager::with_daemon(&url, "raworc-session"), &app.state.config).unwrap()
+        };
+
+        // Other failures leave the session for the retried task
+        let failing = manager(docker_daemon(StatusCode::INTERNAL_SERVER_ERROR, "driver failed").await);
+        assert!(failing.handle_destroy_session(session_id).await.is_err());
+        assert_eq!(state().await.unwrap(), ("INIT".to_string(), None));
+
+        let gone = manager(docker_daemon(StatusCode::NOT_FOUND, "No such container").await);
+        gone.handle_destroy_session(session_id).await.unwrap();
+        assert_eq!(state().await.unwrap(), ("IDLE".to_string(), Some("deleted".to_string())));
+
+        // A duplicate destroy task finds nothing left to do
+        gone.handle_destroy_session(session_id).await.unwrap();
+    }
+}
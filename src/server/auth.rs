@@ -1,4 +1,5 @@
 use crate::shared::{AppState};
+use crate::shared::config::DEFAULT_JWT_ISSUER;
 use crate::shared::models::DatabaseError;
 use crate::server::rbac::{
     AuthPrincipal, PermissionContext, RbacAuthz, RbacClaims, ServiceAccount, SubjectType,
@@ -6,7 +7,60 @@ use crate::server::rbac::{
 };
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use jsonwebtoken::{decode, encode, errors::ErrorKind, DecodingKey, EncodingKey, Header, TokenData, Validation};
+
+/// JWT signing secrets. Tokens are signed with the primary secret and verified against the
+/// primary and any previous secrets, so the secret can be rotated without invalidating
+/// tokens issued before the rotation. Tokens also carry this deployment's issuer and,
+/// when configured, audience, so a token minted for another environment is rejected.
+#[derive(Clone)]
+pub struct JwtKeySet {
+    primary: String,
+    previous: Vec<String>,
+    issuer: String,
+    audience: Option<String>,
+}
+
+impl JwtKeySet {
+    pub fn new(primary: String, previous: Vec<String>) -> Self {
+        Self {
+            primary,
+            previous,
+            issuer: DEFAULT_JWT_ISSUER.to_string(),
+            audience: None,
+        }
+    }
+
+    pub fn with_claims(mut self, issuer: String, audience: Option<String>) -> Self {
+        self.issuer = issuer;
+        self.audience = audience;
+        self
+    }
+
+    fn validation(&self) -> Validation {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.issuer]);
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+            }
+            None => {
+                validation.validate_aud = false;
+                validation.set_required_spec_claims(&["exp", "iss"]);
+            }
+        }
+        validation
+    }
+
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    fn verification_secrets(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.primary.as_str()).chain(self.previous.iter().map(String::as_str))
+    }
+}
 
 
 
@@ -17,7 +71,7 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData,
 // JWT utility functions for RBAC
 pub fn create_service_account_jwt(
     service_account: &ServiceAccount,
-    secret: &str,
+    keys: &JwtKeySet,
     duration_hours: i64,
 ) -> Result<TokenResponse> {
     let exp = Utc::now()
@@ -30,13 +84,14 @@ pub fn create_service_account_jwt(
         workspace: None, // Service accounts are global now
         exp: exp.timestamp() as usize,
         iat: Utc::now().timestamp() as usize,
-        iss: "raworc-rbac".to_string(),
+        iss: keys.issuer.clone(),
+        aud: keys.audience.clone(),
     };
 
     let token = encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
+        &EncodingKey::from_secret(keys.primary().as_ref()),
     )?;
 
     Ok(TokenResponse {
@@ -47,7 +102,7 @@ pub fn create_service_account_jwt(
 
 pub fn create_subject_jwt(
     subject_name: &str,
-    secret: &str,
+    keys: &JwtKeySet,
     duration_hours: i64,
 ) -> Result<TokenResponse> {
     let exp = Utc::now()
@@ -60,13 +115,14 @@ pub fn create_subject_jwt(
         workspace: None,
         exp: exp.timestamp() as usize,
         iat: Utc::now().timestamp() as usize,
-        iss: "raworc-rbac".to_string(),
+        iss: keys.issuer.clone(),
+        aud: keys.audience.clone(),
     };
 
     let token = encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
+        &EncodingKey::from_secret(keys.primary().as_ref()),
     )?;
 
     Ok(TokenResponse {
@@ -75,14 +131,29 @@ pub fn create_subject_jwt(
     })
 }
 
-pub fn decode_rbac_jwt(token: &str, secret: &str) -> Result<RbacClaims> {
-    let token_data: TokenData<RbacClaims> = decode(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::default(),
-    )?;
+pub fn decode_rbac_jwt(token: &str, keys: &JwtKeySet) -> Result<RbacClaims> {
+    let mut last_error = None;
+    let validation = keys.validation();
 
-    Ok(token_data.claims)
+    for secret in keys.verification_secrets() {
+        let result: jsonwebtoken::errors::Result<TokenData<RbacClaims>> = decode(
+            token,
+            &DecodingKey::from_secret(secret.as_ref()),
+            &validation,
+        );
+
+        match result {
+            Ok(token_data) => return Ok(token_data.claims),
+            // Only a signature mismatch means another secret might verify it;
+            // anything else (expired, malformed) fails the same way for every key
+            Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => last_error = Some(e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(last_error
+        .map(Into::into)
+        .unwrap_or_else(|| anyhow::anyhow!("No JWT secrets configured")))
 }
 
 // Permission checking function
@@ -126,12 +197,11 @@ pub async fn authenticate_service_account(
 }
 
 // Exported JWT functions for REST API
-pub fn decode_jwt(token: &str, secret: &str) -> Result<RbacClaims> {
-    decode_rbac_jwt(token, secret)
+pub fn decode_jwt(token: &str, keys: &JwtKeySet) -> Result<RbacClaims> {
+    decode_rbac_jwt(token, keys)
 }
 
-// Get permissions for a principal
-#[allow(dead_code)]
+// Get permissions for a principal as sorted, distinct `api_group/resource/verb` strings
 pub async fn get_permissions_for_principal(
     principal: &AuthPrincipal,
     app_state: &AppState,
@@ -163,5 +233,53 @@ pub async fn get_permissions_for_principal(
         }
     }
     
+    permissions.sort();
+    permissions.dedup();
     Ok(permissions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{create_subject_jwt, decode_rbac_jwt, JwtKeySet};
+
+    const SECRET: &str = "test-secret-that-is-long-enough-for-hs256";
+
+    fn keys(issuer: &str, audience: Option<&str>) -> JwtKeySet {
+        JwtKeySet::new(SECRET.to_string(), Vec::new()).with_claims(issuer.to_string(), audience.map(str::to_string))
+    }
+
+    #[test]
+    fn tokens_are_only_accepted_by_their_own_issuer_and_audience() {
+        let staging = keys("raworc-staging", Some("api"));
+        let token = create_subject_jwt("alice", &staging, 1).unwrap().token;
+        assert_eq!(decode_rbac_jwt(&token, &staging).unwrap().sub, "alice");
+
+        // Same secret, but minted for another environment
+        assert!(decode_rbac_jwt(&token, &keys("raworc-production", Some("api"))).is_err());
+        assert!(decode_rbac_jwt(&token, &keys("raworc-staging", Some("console"))).is_err());
+
+        // A deployment that requires an audience rejects tokens minted without one
+        let untargeted = create_subject_jwt("alice", &keys("raworc-staging", None), 1).unwrap().token;
+        assert!(decode_rbac_jwt(&untargeted, &staging).is_err());
+        assert!(decode_rbac_jwt(&untargeted, &keys("raworc-staging", None)).is_ok());
+    }
+
+    #[test]
+    fn rotated_secrets_still_verify_while_new_tokens_use_the_primary() {
+        let old = JwtKeySet::new(SECRET.to_string(), Vec::new());
+        let rotated = JwtKeySet::new("new-secret-that-is-long-enough-for-hs256".to_string(), vec![SECRET.to_string()]);
+
+        // Issued before the rotation, signed with what is now the previous secret
+        let issued_before = create_subject_jwt("alice", &old, 1).unwrap().token;
+        assert_eq!(decode_rbac_jwt(&issued_before, &rotated).unwrap().sub, "alice");
+
+        // Issued after it, signed with the new primary only
+        let issued_after = create_subject_jwt("bob", &rotated, 1).unwrap().token;
+        assert_eq!(decode_rbac_jwt(&issued_after, &rotated).unwrap().sub, "bob");
+        assert!(decode_rbac_jwt(&issued_after, &old).is_err());
+
+        let unknown = JwtKeySet::new("some-other-secret-that-is-long-enough".to_string(), Vec::new());
+        let forged = create_subject_jwt("mallory", &unknown, 1).unwrap().token;
+        assert!(decode_rbac_jwt(&forged, &rotated).is_err());
+    }
+}
@@ -52,11 +52,14 @@ pub enum SubjectType {
     ServiceAccount,
 }
 
-// Role Binding Subject
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoleBindingSubject {
-    pub kind: SubjectType,
-    pub name: String,
+/// Which bindings a listing covers: global (no workspace), workspace-scoped, or both
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BindingScope {
+    Global,
+    Workspace,
+    #[default]
+    All,
 }
 
 // Role Binding - Links roles to subjects and specifies WHERE they apply
@@ -72,14 +75,6 @@ pub struct RoleBinding {
 }
 
 
-// Role Reference for bindings
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoleRef {
-    pub kind: String, // "Role" or "ClusterRole"
-    pub name: String,
-    pub api_group: String, // API group for permissions, typically "rbac"
-}
-
 // Authentication Principal - Represents authenticated entity
 #[derive(Debug, Clone)]
 pub enum AuthPrincipal {
@@ -88,7 +83,6 @@ pub enum AuthPrincipal {
 }
 
 impl AuthPrincipal {
-    #[allow(dead_code)]
     pub fn name(&self) -> &str {
         match self {
             AuthPrincipal::Subject(s) => &s.name,
@@ -102,7 +96,6 @@ impl AuthPrincipal {
         None
     }
 
-    #[allow(dead_code)]
     pub fn subject_type(&self) -> SubjectType {
         match self {
             AuthPrincipal::Subject(_) => SubjectType::Subject,
@@ -120,6 +113,8 @@ pub struct RbacClaims {
     pub exp: usize,                // Expiration time
     pub iat: usize,                // Issued at
     pub iss: String,               // Issuer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,       // Audience, when one is configured
 }
 
 // Input types for API requests
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
-use crate::server::auth::{authenticate_service_account, create_service_account_jwt, create_subject_jwt};
+use crate::server::auth::{authenticate_service_account, create_service_account_jwt, create_subject_jwt, get_permissions_for_principal};
 use crate::shared::models::AppState;
 use crate::server::rbac::TokenResponse;
 use crate::server::rest::error::{ApiError, ApiResult};
@@ -57,7 +57,7 @@ pub async fn login(
     // Update last login timestamp
     let _ = state.update_last_login(&req.user).await;
 
-    let token_response = create_service_account_jwt(&service_account, &state.jwt_secret, 24)?;
+    let token_response = create_service_account_jwt(&service_account, &state.jwt_keys, 24)?;
     
     Ok(Json(token_response.into()))
 }
@@ -67,24 +67,89 @@ pub async fn external_login(
     Json(req): Json<ExternalLoginRequest>,
 ) -> ApiResult<Json<LoginResponse>> {
     // This endpoint requires admin authentication - checked by middleware
-    let token_response = create_subject_jwt(&req.subject, &state.jwt_secret, 24)?;
+    let token_response = create_subject_jwt(&req.subject, &state.jwt_keys, 24)?;
     
     Ok(Json(token_response.into()))
 }
 
+/// A role binding of the current principal, as listed by `/auth/me`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MeRoleBinding {
+    pub role_name: String,
+    /// None for a global binding
+    pub workspace: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MeResponse {
+    pub user: String,
+    /// Always null; kept for clients written against the original response
+    pub namespace: Option<String>,
+    /// `Subject` or `ServiceAccount`
+    #[serde(rename = "type")]
+    pub principal_type: String,
+    pub role_bindings: Vec<MeRoleBinding>,
+    /// `api_group/resource/verb` granted by any binding; `role_bindings` tells which workspaces each applies to
+    pub permissions: Vec<String>,
+}
+
 pub async fn me(
+    State(state): State<Arc<AppState>>,
     Extension(auth): Extension<crate::server::rest::middleware::AuthContext>,
-) -> ApiResult<Json<serde_json::Value>> {
+) -> ApiResult<Json<MeResponse>> {
     use crate::server::rbac::AuthPrincipal;
     
-    let (user, namespace, principal_type) = match &auth.principal {
-        AuthPrincipal::Subject(s) => (&s.name, None::<String>, "Subject"),
-        AuthPrincipal::ServiceAccount(sa) => (&sa.user, None::<String>, "ServiceAccount"),
+    let (user, principal_type) = match &auth.principal {
+        AuthPrincipal::Subject(s) => (&s.name, "Subject"),
+        AuthPrincipal::ServiceAccount(sa) => (&sa.user, "ServiceAccount"),
     };
+
+    let role_bindings = state
+        .get_role_bindings_for_subject(auth.principal.name(), auth.principal.subject_type(), None)
+        .await?
+        .into_iter()
+        .map(|binding| MeRoleBinding {
+            role_name: binding.role_name,
+            workspace: binding.workspace,
+        })
+        .collect();
+    let permissions = get_permissions_for_principal(&auth.principal, &state).await?;
     
-    Ok(Json(serde_json::json!({
-        "user": user,
-        "namespace": namespace,
-        "type": principal_type
-    })))
-}
\ No newline at end of file
+    Ok(Json(MeResponse {
+        user: user.clone(),
+        namespace: None,
+        principal_type: principal_type.to_string(),
+        role_bindings,
+        permissions,
+    }))
+}
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn me_lists_the_callers_bindings_and_permissions() {
+        let app = TestApp::new().await;
+
+        let response = app.request(Method::GET, "/api/v0/auth/me", &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let me = body_json(response).await;
+        assert_eq!(me["user"], "admin");
+        assert_eq!(me["type"], "ServiceAccount");
+        assert!(me["role_bindings"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({"role_name": "admin", "workspace": null})));
+        assert!(!me["permissions"].as_array().unwrap().is_empty());
+
+        let user = unique("user");
+        let response = app.request(Method::GET, "/api/v0/auth/me", &app.user_token(&user), None).await;
+        let me = body_json(response).await;
+        assert_eq!(me["type"], "Subject");
+        assert_eq!(me["role_bindings"], serde_json::json!([]));
+        assert_eq!(me["permissions"], serde_json::json!([]));
+    }
+}
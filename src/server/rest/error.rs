@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -41,6 +41,23 @@ pub enum ApiError {
     #[error("Conflict: {0}")]
     Conflict(String),
     
+    #[error("Not acceptable: {0}")]
+    NotAcceptable(String),
+    
+    #[error("Validation failed")]
+    Validation(HashMap<String, String>),
+    
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+    
+    /// Out of capacity for now; the client should retry after the given number of seconds
+    #[error("Capacity exceeded: {message}")]
+    CapacityExceeded { message: String, retry_after_secs: u64 },
+    
+    /// The principal exceeded its request rate; retry after the given number of seconds
+    #[error("Rate limit exceeded")]
+    RateLimited { retry_after_secs: u64 },
+    
     #[error("Internal server error")]
     Internal(#[from] anyhow::Error),
     
@@ -54,14 +71,49 @@ pub enum ApiError {
     Bcrypt(#[from] bcrypt::BcryptError),
 }
 
+impl From<validator::ValidationErrors> for ApiError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let fields = errors
+            .field_errors()
+            .into_iter()
+            .filter_map(|(field, errors)| {
+                let error = errors.first()?;
+                let message = error
+                    .message
+                    .as_ref()
+                    .map(|message| message.to_string())
+                    .unwrap_or_else(|| format!("is invalid ({})", error.code));
+                Some((field.to_string(), message))
+            })
+            .collect();
+        ApiError::Validation(fields)
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        // Field-level failures carry a { field: message } map in `details`
+        let details = match &self {
+            ApiError::Validation(fields) => Some(
+                fields
+                    .iter()
+                    .map(|(field, message)| (field.clone(), serde_json::Value::String(message.clone())))
+                    .collect(),
+            ),
+            _ => None,
+        };
+
         let (status, code, message) = match &self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg.to_string()),
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "Authentication required".to_string()),
             ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, "FORBIDDEN", msg.to_string()),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND", msg.to_string()),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg.to_string()),
+            ApiError::NotAcceptable(msg) => (StatusCode::NOT_ACCEPTABLE, "NOT_ACCEPTABLE", msg.to_string()),
+            ApiError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, "VALIDATION_ERROR", "Request validation failed".to_string()),
+            ApiError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE", msg.to_string()),
+            ApiError::CapacityExceeded { message, .. } => (StatusCode::TOO_MANY_REQUESTS, "CAPACITY_EXCEEDED", message.to_string()),
+            ApiError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED", "Too many requests; slow down and retry later".to_string()),
             ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", "An internal error occurred".to_string()),
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DATABASE_ERROR", "Database operation failed".to_string()),
             ApiError::Jwt(_) => (StatusCode::UNAUTHORIZED, "JWT_ERROR", "Invalid or expired token".to_string()),
@@ -72,10 +124,14 @@ impl IntoResponse for ApiError {
             error: ErrorDetails {
                 code: code.to_string(),
                 message,
-                details: None,
+                details,
             },
         };
 
-        (status, Json(error_response)).into_response()
+        let mut response = (status, Json(error_response)).into_response();
+        if let ApiError::CapacityExceeded { retry_after_secs, .. } | ApiError::RateLimited { retry_after_secs } = &self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(*retry_after_secs));
+        }
+        response
     }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Weak ETag derived from the serialized representation, so any change to the
+/// response (state, timestamps, attached agents, ...) produces a new tag.
+pub fn weak_etag<T: Serialize>(body: &T) -> Result<String, serde_json::Error> {
+    let bytes = serde_json::to_vec(body)?;
+    let digest = ring::digest::digest(&ring::digest::SHA256, &bytes);
+    let hex: String = digest.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("W/\"{}\"", hex))
+}
+
+/// Whether `If-None-Match` lists the given tag, using weak comparison
+fn if_none_match_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let current = opaque(etag);
+
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == current)
+}
+
+/// Respond with the body and its ETag, or 304 Not Modified when the client already has it
+pub fn conditional_json<T: Serialize>(headers: &HeaderMap, body: T) -> Result<Response, serde_json::Error> {
+    let etag = weak_etag(&body)?;
+    let etag_header = HeaderValue::from_str(&etag).expect("ETag is ASCII");
+
+    let mut response = if if_none_match_matches(headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Json(body).into_response()
+    };
+    response.headers_mut().insert(header::ETAG, etag_header);
+    Ok(response)
+}
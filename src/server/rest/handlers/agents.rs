@@ -1,17 +1,38 @@
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Response,
     Extension,
     Json,
 };
 use serde::Serialize;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 use utoipa::ToSchema;
+use validator::Validate;
 
-use crate::shared::models::{Agent, AppState, CreateAgentRequest, UpdateAgentRequest};
+use crate::shared::models::message::FROM_HOST_KEY;
+use crate::shared::models::validation::check_length;
+use crate::shared::models::{
+    Agent, AgentRevision, AppState, CreateAgentRequest, CreateMessageRequest, CreateSessionRequest, CreatedRange, MessageRole,
+    Session, SessionMessage, SessionState, TestAgentRequest, UpdateAgentRequest,
+};
 use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::etag::conditional_json;
+use crate::server::rest::handlers::sessions::{create_session_with_messages, delete_session_and_container};
+use crate::server::rest::handlers::workspaces::validate_workspace_name;
 use crate::server::rest::middleware::AuthContext;
-use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, get_user_workspace};
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, get_user_workspace, PermissionRequirement};
+
+/// Unique constraint on an agent's name within its workspace
+const AGENT_NAME_CONSTRAINT: &str = "agents_unique_name_workspace";
+
+/// How often an agent test checks its session for the agent's reply
+const AGENT_TEST_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Retry-After sent when every agent test slot is taken
+const AGENT_TEST_RETRY_AFTER_SECS: u64 = 10;
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AgentResponse {
@@ -30,6 +51,52 @@ pub struct AgentResponse {
     pub updated_at: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentRevisionResponse {
+    pub revision: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub instructions: String,
+    pub model: String,
+    pub tools: serde_json::Value,
+    pub routes: serde_json::Value,
+    pub guardrails: serde_json::Value,
+    pub knowledge_bases: serde_json::Value,
+    /// Principal whose update replaced this definition
+    pub replaced_by: String,
+    /// When this definition was replaced
+    pub created_at: String,
+}
+
+/// Outcome of a one-off agent test
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentTestResponse {
+    /// Throwaway session the prompt ran in; it is already deleted
+    pub session_id: String,
+    /// The agent's reply; None when a guardrail blocked the prompt or the reply
+    pub response: Option<String>,
+    /// Guardrail events the agent reported while handling the prompt, e.g. redactions
+    pub guardrail_events: Vec<String>,
+}
+
+impl From<AgentRevision> for AgentRevisionResponse {
+    fn from(revision: AgentRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            name: revision.name,
+            description: revision.description,
+            instructions: revision.instructions,
+            model: revision.model,
+            tools: revision.tools,
+            routes: revision.routes,
+            guardrails: revision.guardrails,
+            knowledge_bases: revision.knowledge_bases,
+            replaced_by: revision.replaced_by,
+            created_at: revision.created_at.to_rfc3339(),
+        }
+    }
+}
+
 impl From<Agent> for AgentResponse {
     fn from(agent: Agent) -> Self {
         Self {
@@ -53,6 +120,8 @@ impl From<Agent> for AgentResponse {
 #[derive(Debug, serde::Deserialize)]
 pub struct ListAgentsQuery {
     pub workspace: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
 }
 
 pub async fn list_agents(
@@ -74,7 +143,10 @@ pub async fn list_agents(
             _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
         })?;
 
-    let agents = Agent::find_all(&state.db, Some(target_workspace))
+    let created = CreatedRange::parse(query.created_after.as_deref(), query.created_before.as_deref())
+        .map_err(ApiError::BadRequest)?;
+
+    let agents = Agent::find_all(&state.db, Some(target_workspace), created)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to list agents: {}", e)))?;
     
@@ -86,7 +158,8 @@ pub async fn get_agent(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> ApiResult<Json<AgentResponse>> {
+    headers: HeaderMap,
+) -> ApiResult<Response> {
     // Try parsing as UUID first
     let agent = if let Ok(uuid) = Uuid::parse_str(&id) {
         Agent::find_by_id(&state.db, uuid).await
@@ -106,7 +179,8 @@ pub async fn get_agent(
             _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
         })?;
 
-    Ok(Json(agent.into()))
+    conditional_json(&headers, AgentResponse::from(agent))
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to encode agent: {}", e)))
 }
 
 pub async fn create_agent(
@@ -114,10 +188,13 @@ pub async fn create_agent(
     State(state): State<Arc<AppState>>,
     Json(mut req): Json<CreateAgentRequest>,
 ) -> ApiResult<Json<AgentResponse>> {
+    req.validate()?;
+
     // Use user's workspace if not specified
     if req.workspace.is_empty() {
         req.workspace = get_user_workspace(&auth).unwrap_or_else(|| "default".to_string());
     }
+    req.workspace = validate_workspace_name(&req.workspace)?;
 
     // Check permission for creating agents in the workspace
     check_api_permission(&auth, &state, &permissions::AGENT_CREATE, Some(&req.workspace))
@@ -145,6 +222,8 @@ pub async fn update_agent(
     Path(id): Path<String>,
     Json(req): Json<UpdateAgentRequest>,
 ) -> ApiResult<Json<AgentResponse>> {
+    req.validate()?;
+
     let uuid = Uuid::parse_str(&id)
         .map_err(|_| ApiError::BadRequest("Invalid agent ID format".to_string()))?;
 
@@ -171,7 +250,8 @@ pub async fn update_agent(
         }
     }
 
-    let agent = Agent::update(&state.db, uuid, req)
+    let revised_by = state.config.server.agent_revisions.then(|| auth.principal.name());
+    let agent = Agent::update(&state.db, uuid, req, revised_by)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update agent: {}", e)))?
         .ok_or(ApiError::NotFound("Agent not found".to_string()))?;
@@ -179,6 +259,71 @@ pub async fn update_agent(
     Ok(Json(agent.into()))
 }
 
+/// Fetch an agent by id and check the caller holds `requirement` in its workspace
+async fn find_agent_with_permission(
+    auth: &AuthContext,
+    state: &AppState,
+    id: &str,
+    requirement: &PermissionRequirement,
+) -> Result<Agent, ApiError> {
+    let uuid = Uuid::parse_str(id)
+        .map_err(|_| ApiError::BadRequest("Invalid agent ID format".to_string()))?;
+
+    let agent = Agent::find_by_id(&state.db, uuid)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch agent: {}", e)))?
+        .ok_or(ApiError::NotFound("Agent not found".to_string()))?;
+
+    check_api_permission(auth, state, requirement, Some(&agent.workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    Ok(agent)
+}
+
+pub async fn list_agent_versions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Vec<AgentRevisionResponse>>> {
+    let agent = find_agent_with_permission(&auth, &state, &id, &permissions::AGENT_GET).await?;
+
+    let revisions = AgentRevision::find_by_agent(&state.db, agent.id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch agent revisions: {}", e)))?;
+
+    Ok(Json(revisions.into_iter().map(Into::into).collect()))
+}
+
+pub async fn restore_agent_version(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((id, revision)): Path<(String, i32)>,
+) -> ApiResult<Json<AgentResponse>> {
+    let agent = find_agent_with_permission(&auth, &state, &id, &permissions::AGENT_UPDATE).await?;
+
+    let revised_by = state.config.server.agent_revisions.then(|| auth.principal.name());
+    let restored = Agent::restore_revision(&state.db, agent.id, revision, revised_by)
+        .await
+        .map_err(|e| {
+            if e.as_database_error().and_then(|db| db.constraint()) == Some(AGENT_NAME_CONSTRAINT) {
+                return ApiError::Conflict(format!(
+                    "Revision {} is named after another agent in workspace '{}'",
+                    revision, agent.workspace
+                ));
+            }
+            ApiError::Internal(anyhow::anyhow!("Failed to restore agent revision: {}", e))
+        })?
+        .ok_or_else(|| ApiError::NotFound(format!("Revision {} not found", revision)))?;
+
+    tracing::info!("Restored agent {} to revision {}", agent.id, revision);
+
+    Ok(Json(restored.into()))
+}
+
 pub async fn delete_agent(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
@@ -210,4 +355,314 @@ pub async fn delete_agent(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Run one prompt against an agent without keeping a session. The prompt goes to a throwaway
+/// session whose container runs the agent like any other, guardrails included; the session
+/// and its container are removed once the agent answers, is blocked or runs out of time.
+/// Each test holds a container, so only `max_concurrent_agent_tests` run at once.
+pub async fn test_agent(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<TestAgentRequest>,
+) -> ApiResult<Json<AgentTestResponse>> {
+    req.validate()?;
+    let agent = find_agent_with_permission(&auth, &state, &id, &permissions::AGENT_TEST).await?;
+
+    let prompt = CreateMessageRequest {
+        role: MessageRole::User,
+        content: req.prompt,
+        agent_id: None,
+        metadata: serde_json::json!({}),
+    };
+    check_length("prompt", &prompt.content, state.config.server.max_message_length)?;
+
+    // The permit moves into the cleanup task, so a test counts until its container is gone
+    let permit = state.agent_tests.clone().try_acquire_owned().map_err(|_| ApiError::CapacityExceeded {
+        message: "Too many agent tests are running; retry once one finishes".to_string(),
+        retry_after_secs: AGENT_TEST_RETRY_AFTER_SECS,
+    })?;
+
+    let username = auth.principal.name().to_string();
+    let session_req = CreateSessionRequest {
+        name: format!("agent-test-{}", Uuid::new_v4()),
+        workspace: agent.workspace.clone(),
+        starting_prompt: prompt.content.clone(),
+        agent_ids: vec![agent.id],
+        waiting_timeout_seconds: None,
+        metadata: serde_json::json!({ "agent_test": true, "secrets": req.secrets }),
+        node_selector: None,
+    };
+    let session = create_session_with_messages(&state, &auth, session_req, vec![prompt]).await?;
+    tracing::info!("Testing agent {} in session {} for {}", agent.id, session.id, username);
+
+    // Waiting and cleanup run on their own task so the session is removed even if the client hangs up
+    let timeout = state.config.server.agent_test_timeout;
+    let task_state = state.clone();
+    let outcome = tokio::spawn(async move {
+        let outcome = tokio::time::timeout(timeout, wait_for_agent_reply(&task_state, session.id)).await;
+        match delete_session_and_container(&task_state, session.id, &username).await {
+            Ok(_) => tracing::info!("Removed agent test session {}", session.id),
+            Err(e) => tracing::warn!("Failed to remove agent test session {}: {}", session.id, e),
+        }
+        drop(permit);
+        outcome
+    })
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!("Agent test task failed: {}", e)))?;
+
+    match outcome {
+        Ok(result) => result.map(Json),
+        Err(_) => Err(ApiError::ServiceUnavailable(format!(
+            "Agent {} did not answer within {} seconds",
+            agent.id,
+            timeout.as_secs()
+        ))),
+    }
+}
+
+/// Poll a test session until the agent replies or a guardrail blocks the exchange
+async fn wait_for_agent_reply(state: &AppState, session_id: Uuid) -> Result<AgentTestResponse, ApiError> {
+    let mut interval = tokio::time::interval(AGENT_TEST_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let session = Session::find_by_id(&state.db, session_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session: {}", e)))?
+            .ok_or_else(|| ApiError::Conflict("The agent test session was deleted".to_string()))?;
+        if session.state == SessionState::Error {
+            return Err(ApiError::ServiceUnavailable(format!(
+                "The agent test session failed: {}",
+                session.termination_reason.as_deref().unwrap_or("no reason given")
+            )));
+        }
+
+        let messages = SessionMessage::find_all_by_session(&state.db, session_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch messages: {}", e)))?;
+
+        // The host reports guardrail decisions as SYSTEM messages; a blocked exchange gets no reply.
+        // Only messages the host posted count, so nobody else can fake an answer or a block.
+        let from_host: Vec<&SessionMessage> = messages.iter().filter(|m| m.metadata[FROM_HOST_KEY] == true).collect();
+        let guardrail_events: Vec<&SessionMessage> = from_host
+            .iter()
+            .copied()
+            .filter(|m| m.role == MessageRole::System && m.metadata["type"] == "guardrail_event")
+            .collect();
+        let blocked = guardrail_events
+            .iter()
+            .any(|m| m.metadata["action"].as_str().is_some_and(|action| action.ends_with("_blocked")));
+        let reply = from_host.iter().find(|m| m.role == MessageRole::Agent);
+        if reply.is_some() || blocked {
+            return Ok(AgentTestResponse {
+                session_id: session_id.to_string(),
+                response: reply.map(|m| m.content.clone()),
+                guardrail_events: guardrail_events.iter().map(|m| m.content.clone()).collect(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, Method, StatusCode};
+    use serde_json::json;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    use super::wait_for_agent_reply;
+    use crate::server::rest::node_client::NodeClient;
+    use crate::server::rest::test_support::{body_bytes, body_json, unique, TestApp};
+    use crate::shared::models::Session;
+
+    async fn create_agent(app: &TestApp) -> String {
+        let agent = json!({"name": unique("agent"), "instructions": "Answer briefly", "model": "claude-3-5-sonnet-latest"});
+        let response = app.request(Method::POST, "/api/v0/agents", &app.admin_token(), Some(agent)).await;
+        body_json(response).await["id"].as_str().unwrap().to_string()
+    }
+
+    async fn set_instructions(app: &TestApp, agent_id: &str, instructions: &str) {
+        let uri = format!("/api/v0/agents/{}", agent_id);
+        let response = app.request(Method::PUT, &uri, &app.admin_token(), Some(json!({"instructions": instructions}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn versions(app: &TestApp, agent_id: &str) -> Vec<serde_json::Value> {
+        let uri = format!("/api/v0/agents/{}/versions", agent_id);
+        let response = app.request(Method::GET, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        body_json(response).await.as_array().unwrap().clone()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn unchanged_agents_are_not_sent_again() {
+        let app = TestApp::new().await;
+        let agent_id = create_agent(&app).await;
+        let uri = format!("/api/v0/agents/{}", agent_id);
+        let get_if_none_match = |etag: header::HeaderValue| {
+            let request = axum::http::Request::get(&uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", app.admin_token()))
+                .header(header::IF_NONE_MATCH, etag)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            app.send(request)
+        };
+
+        let response = app.request(Method::GET, &uri, &app.admin_token(), None).await;
+        let etag = response.headers().get(header::ETAG).cloned().expect("GET sets an ETag");
+
+        let response = get_if_none_match(etag.clone()).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG), Some(&etag));
+        assert!(body_bytes(response).await.is_empty());
+
+        set_instructions(&app, &agent_id, "Answer at length").await;
+        let response = get_if_none_match(etag.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers().get(header::ETAG), Some(&etag));
+        assert_eq!(body_json(response).await["instructions"], "Answer at length");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn an_explicit_null_clears_the_description_and_an_absent_one_keeps_it() {
+        let app = TestApp::new().await;
+        let agent_id = create_agent(&app).await;
+        let uri = format!("/api/v0/agents/{}", agent_id);
+        let update = |body: serde_json::Value| {
+            let (app, uri) = (&app, &uri);
+            async move {
+                let response = app.request(Method::PUT, uri, &app.admin_token(), Some(body)).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response).await
+            }
+        };
+
+        assert_eq!(update(json!({"description": "Answers questions"})).await["description"], "Answers questions");
+        // Updating something else leaves it alone
+        assert_eq!(update(json!({"instructions": "Answer at length"})).await["description"], "Answers questions");
+        assert_eq!(update(json!({"description": null})).await["description"], serde_json::Value::Null);
+
+        let response = app.request(Method::GET, &uri, &app.admin_token(), None).await;
+        assert_eq!(body_json(response).await["description"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn updates_save_the_previous_definition_as_a_revision() {
+        let app = TestApp::with_config(|config| config.server.agent_revisions = true).await;
+        let agent_id = create_agent(&app).await;
+        set_instructions(&app, &agent_id, "Second").await;
+        set_instructions(&app, &agent_id, "Third").await;
+
+        let versions = versions(&app, &agent_id).await;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0]["revision"], 2);
+        assert_eq!(versions[0]["instructions"], "Second");
+        assert_eq!(versions[1]["revision"], 1);
+        assert_eq!(versions[1]["instructions"], "Answer briefly");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn restoring_a_revision_saves_the_replaced_definition() {
+        let app = TestApp::with_config(|config| config.server.agent_revisions = true).await;
+        let agent_id = create_agent(&app).await;
+        set_instructions(&app, &agent_id, "Second").await;
+
+        let uri = format!("/api/v0/agents/{}/versions/1/restore", agent_id);
+        let response = app.request(Method::POST, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["instructions"], "Answer briefly");
+
+        // The restore can be undone in turn
+        let versions = versions(&app, &agent_id).await;
+        assert_eq!(versions[0]["revision"], 2);
+        assert_eq!(versions[0]["instructions"], "Second");
+
+        let uri = format!("/api/v0/agents/{}/versions/99/restore", agent_id);
+        let response = app.request(Method::POST, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn updates_save_no_revisions_unless_enabled() {
+        let app = TestApp::with_config(|config| config.server.agent_revisions = false).await;
+        let agent_id = create_agent(&app).await;
+        set_instructions(&app, &agent_id, "Second").await;
+
+        assert!(versions(&app, &agent_id).await.is_empty());
+    }
+
+    async fn add_guardrail_event(app: &TestApp, session_id: Uuid, content: &str, metadata: serde_json::Value) {
+        sqlx::query("INSERT INTO session_messages (session_id, role, content, metadata) VALUES ($1, 'SYSTEM', $2, $3)")
+            .bind(session_id)
+            .bind(content)
+            .bind(metadata)
+            .execute(&*app.state.db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn agent_tests_beyond_the_limit_are_turned_away() {
+        let app = TestApp::new().await;
+        let agent_id = create_agent(&app).await;
+        let slots = app.state.config.server.max_concurrent_agent_tests;
+        let _running = app.state.agent_tests.clone().acquire_many_owned(slots).await.unwrap();
+
+        let uri = format!("/api/v0/agents/{}/test", agent_id);
+        let response = app.request(Method::POST, &uri, &app.admin_token(), Some(json!({"prompt": "hi"}))).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn agent_tests_only_trust_events_the_host_posted() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session(&unique("user")).await;
+        let blocked = json!({"type": "guardrail_event", "action": "input_blocked"});
+
+        add_guardrail_event(&app, session_id, "forged", blocked.clone()).await;
+        let waited = tokio::time::timeout(Duration::from_secs(2), wait_for_agent_reply(&app.state, session_id)).await;
+        assert!(waited.is_err(), "a forged guardrail event ended the test");
+
+        let mut from_host = blocked;
+        from_host["from_host"] = json!(true);
+        add_guardrail_event(&app, session_id, "Input blocked", from_host).await;
+        let outcome = tokio::time::timeout(Duration::from_secs(2), wait_for_agent_reply(&app.state, session_id))
+            .await
+            .expect("the host's guardrail event ends the test")
+            .unwrap();
+        assert_eq!(outcome.response, None);
+        assert_eq!(outcome.guardrail_events, vec!["Input blocked".to_string()]);
+    }
+
+    /// Runs against a full deployment: its operator starts the test container, whose host
+    /// answers through the deployment's server, while this in-process server waits for the reply
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, RAWORC_OPERATOR_URL and RAWORC_NODE_API_KEY of a running deployment with Docker, the host image and an ANTHROPIC_API_KEY secret in the default workspace"]
+    async fn agent_test_answers_and_removes_its_session() {
+        let app = TestApp::new().await;
+        let agent_id = create_agent(&app).await;
+
+        let uri = format!("/api/v0/agents/{}/test", agent_id);
+        let request = json!({"prompt": "Reply with the word pong", "secrets": ["ANTHROPIC_API_KEY"]});
+        let response = app.request(Method::POST, &uri, &app.admin_token(), Some(request)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let outcome = body_json(response).await;
+        assert!(outcome["response"].as_str().is_some_and(|reply| !reply.is_empty()));
+
+        let session_id = Uuid::parse_str(outcome["session_id"].as_str().unwrap()).unwrap();
+        assert!(Session::find_by_id(&app.state.db, session_id).await.unwrap().is_none());
+        let operator = NodeClient::for_node(&app.state, None).await.unwrap();
+        let containers = operator.list_containers(None).await.unwrap();
+        assert!(containers.iter().all(|container| container.session_id != Some(session_id)));
+    }
+}
@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Query, State},
+    Extension,
+    Json,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::operator::{detect_drift, Drift, SessionContainer};
+use crate::shared::models::validation::is_valid_node_name;
+use crate::shared::models::{AppState, Session, SessionState};
+use crate::server::rest::handlers::workspaces::validate_workspace_name;
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::node_client::NodeClient;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, PermissionRequirement};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    /// Session named by the container's `raworc.session` label
+    pub session_id: Option<String>,
+    /// Workspace named by the container's `raworc.workspace` label
+    pub workspace: Option<String>,
+    /// Docker state, e.g. `running` or `exited`
+    pub state: String,
+    pub status: String,
+    /// True when no live session owns this container
+    pub orphan: bool,
+}
+
+/// A live session that should have a running container but doesn't
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GhostSession {
+    pub session_id: String,
+    pub name: String,
+    pub workspace: String,
+    pub state: SessionState,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ContainerWorkspaceQuery {
+    /// Only consider containers labelled with this workspace and the workspace's sessions
+    pub workspace: Option<String>,
+    /// Node whose Docker to inspect; defaults to the operator without a node name
+    pub node: Option<String>,
+}
+
+/// Check a `node` query parameter against the node name rules
+pub(crate) fn validate_node_param(node: Option<&str>) -> Result<Option<&str>, ApiError> {
+    match node {
+        Some(node) if !is_valid_node_name(node) => Err(ApiError::BadRequest(format!("Invalid node name '{}'", node))),
+        node => Ok(node),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContainerReport {
+    pub containers: Vec<ContainerInfo>,
+    pub ghosts: Vec<GhostSession>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReconcileResponse {
+    /// Orphaned containers that were removed
+    pub removed: Vec<ContainerInfo>,
+    /// Ids of orphaned containers that could not be removed
+    pub failed: Vec<String>,
+    /// Ghost sessions are reported but left for the operator or an admin to resolve
+    pub ghosts: Vec<GhostSession>,
+}
+
+/// Shape the operator's drift detection into the admin report
+fn build_report(containers: Vec<SessionContainer>, sessions: &[Session]) -> ContainerReport {
+    let states: Vec<(Uuid, SessionState)> = sessions.iter().map(|s| (s.id, s.state)).collect();
+
+    let mut orphans = HashSet::new();
+    let mut lost = HashSet::new();
+    for drift in detect_drift(&containers, &states) {
+        match drift {
+            Drift::OrphanedContainer { container_id, .. } => {
+                orphans.insert(container_id);
+            }
+            Drift::LostContainer { session_id } => {
+                lost.insert(session_id);
+            }
+        }
+    }
+
+    let containers = containers
+        .into_iter()
+        .map(|c| ContainerInfo {
+            orphan: orphans.contains(&c.id),
+            id: c.id,
+            name: c.name,
+            session_id: c.session_id.map(|id| id.to_string()),
+            workspace: c.workspace,
+            state: c.state.to_string(),
+            status: c.status,
+        })
+        .collect();
+
+    let ghosts = sessions
+        .iter()
+        .filter(|s| lost.contains(&s.id))
+        .map(|s| GhostSession {
+            session_id: s.id.to_string(),
+            name: s.name.clone(),
+            workspace: s.workspace.clone(),
+            state: s.state,
+        })
+        .collect();
+
+    ContainerReport { containers, ghosts }
+}
+
+async fn require_permission(auth: &AuthContext, state: &AppState, requirement: &PermissionRequirement) -> Result<(), ApiError> {
+    check_api_permission(auth, state, requirement, None)
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })
+}
+
+/// Drift report of one node, scoped to one workspace when given. A scoped report only sees
+/// containers carrying that workspace's label, so reconciling it can't remove another tenant's container.
+async fn current_report(node: &NodeClient, state: &AppState, node_name: Option<&str>, workspace: Option<&str>) -> Result<ContainerReport, ApiError> {
+    let containers = node.list_containers(workspace).await?;
+
+    let mut sessions = Session::find_expecting_container(&state.db, node_name)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch sessions: {}", e)))?;
+    if let Some(workspace) = workspace {
+        sessions.retain(|s| s.workspace == workspace);
+    }
+
+    Ok(build_report(containers, &sessions))
+}
+
+pub async fn list_containers(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ContainerWorkspaceQuery>,
+) -> ApiResult<Json<ContainerReport>> {
+    require_permission(&auth, &state, &permissions::CONTAINER_LIST).await?;
+
+    let workspace = query.workspace.as_deref().map(validate_workspace_name).transpose()?;
+    let node_name = validate_node_param(query.node.as_deref())?;
+    let node = NodeClient::for_node(&state, node_name).await?;
+    Ok(Json(current_report(&node, &state, node_name, workspace.as_deref()).await?))
+}
+
+pub async fn reconcile_containers(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ContainerWorkspaceQuery>,
+) -> ApiResult<Json<ReconcileResponse>> {
+    require_permission(&auth, &state, &permissions::CONTAINER_RECONCILE).await?;
+
+    let workspace = query.workspace.as_deref().map(validate_workspace_name).transpose()?;
+    let node_name = validate_node_param(query.node.as_deref())?;
+    let node = NodeClient::for_node(&state, node_name).await?;
+    let report = current_report(&node, &state, node_name, workspace.as_deref()).await?;
+
+    let mut removed = Vec::new();
+    let mut failed = Vec::new();
+    for container in report.containers.into_iter().filter(|c| c.orphan) {
+        match node.remove_container(&container.id).await {
+            Ok(()) => removed.push(container),
+            Err(e) => {
+                warn!("Failed to remove orphaned container {}: {:?}", container.name, e);
+                failed.push(container.id);
+            }
+        }
+    }
+
+    info!("Reconciled containers: {} orphans removed, {} failed", removed.len(), failed.len());
+
+    Ok(Json(ReconcileResponse {
+        removed,
+        failed,
+        ghosts: report.ghosts,
+    }))
+}
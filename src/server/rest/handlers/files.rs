@@ -0,0 +1,168 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::shared::models::{AppState, Session};
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::node_client::NodeClient;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, PermissionRequirement};
+
+#[derive(Debug, Deserialize)]
+pub struct SessionFileQuery {
+    /// Absolute path in the session's container, e.g. `/workspace/todo.txt`
+    pub path: String,
+}
+
+/// Reject paths the Docker archive API can't address as a single file
+fn check_file_path(path: &str) -> Result<(), ApiError> {
+    if !path.starts_with('/') || path.ends_with('/') || path.contains('\0') {
+        return Err(ApiError::BadRequest("path must be an absolute path to a file".to_string()));
+    }
+    Ok(())
+}
+
+/// Look up the session, requiring `permission` in its workspace, and a client for the operator
+/// on the node that runs its container
+async fn session_node(
+    state: &AppState,
+    auth: &AuthContext,
+    id: &str,
+    permission: &PermissionRequirement,
+) -> Result<(Uuid, NodeClient), ApiError> {
+    let session_id = Uuid::parse_str(id)
+        .map_err(|_| ApiError::BadRequest("Invalid session ID format".to_string()))?;
+
+    let session = Session::find_by_id(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session: {}", e)))?
+        .ok_or(ApiError::NotFound("Session not found".to_string()))?;
+
+    // Files reach everything in the container, like a shell, so owning the session isn't enough
+    check_api_permission(auth, state, permission, Some(&session.workspace))
+        .await
+        .map_err(|e| match e {
+            StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    let node_name = Session::container_node(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session node: {}", e)))?;
+    let node = NodeClient::for_node(state, node_name.as_deref()).await?;
+    Ok((session_id, node))
+}
+
+/// Copy a file out of a session's container through the Docker archive API, so it works
+/// whether or not the image bind-mounts its workspace
+pub async fn read_session_file(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<SessionFileQuery>,
+) -> ApiResult<Response> {
+    check_file_path(&query.path)?;
+    let (session_id, node) = session_node(&state, &auth, &id, &permissions::SESSION_FILES_READ).await?;
+
+    let contents = node.read_file(session_id, &query.path).await?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], contents).into_response())
+}
+
+/// Create or replace a file in a session's container with the request body
+pub async fn write_session_file(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<SessionFileQuery>,
+    contents: Bytes,
+) -> ApiResult<StatusCode> {
+    check_file_path(&query.path)?;
+    let (session_id, node) = session_node(&state, &auth, &id, &permissions::SESSION_FILES_WRITE).await?;
+
+    let size = contents.len();
+    node.write_file(session_id, &query.path, contents).await?;
+
+    if let Err(e) = state
+        .record_audit_event(
+            "write-file",
+            "session",
+            Some(session_id),
+            &auth.principal,
+            serde_json::json!({ "path": query.path, "size": size }),
+        )
+        .await
+    {
+        warn!("Failed to record audit event for session {} file write: {}", session_id, e);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{Body, Bytes};
+    use axum::http::{header, Method, Request, StatusCode};
+    use axum::{routing::get, Router};
+
+    use crate::server::rest::test_support::{body_bytes, serve, unique, TestApp};
+
+    #[test]
+    fn only_absolute_file_paths_are_accepted() {
+        assert!(super::check_file_path("/workspace/todo.txt").is_ok());
+        assert!(super::check_file_path("workspace/todo.txt").is_err());
+        assert!(super::check_file_path("/workspace/").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn files_go_through_the_operator_and_need_their_own_permissions() {
+        let operator_url = serve(Router::new().route(
+            "/node/sessions/{id}/files",
+            get(|| async { "- [ ] write tests\n" }).put(|body: Bytes| async move {
+                assert_eq!(body.as_ref(), b"done");
+                StatusCode::NO_CONTENT
+            }),
+        ))
+        .await;
+        let app = TestApp::with_config(|config| {
+            config.node_api_key = Some("node-api-key-that-is-long-enough-for-tests".to_string());
+            config.server.operator_url = Some(operator_url.clone());
+        })
+        .await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let uri = format!("/api/v0/sessions/{}/files?path=/workspace/todo.txt", app.create_session(&user).await);
+
+        // Owning the session isn't enough
+        let response = app.request(Method::GET, &uri, &token, None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        app.grant(&user, Some("default"), "sessions", &["read-files"]).await;
+        let response = app.request(Method::GET, &uri, &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_bytes(response).await, b"- [ ] write tests\n");
+
+        let write = || {
+            Request::builder()
+                .method(Method::PUT)
+                .uri(&uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from("done"))
+                .unwrap()
+        };
+        assert_eq!(app.send(write()).await.status(), StatusCode::FORBIDDEN);
+        app.grant(&user, Some("default"), "sessions", &["write-files"]).await;
+        assert_eq!(app.send(write()).await.status(), StatusCode::NO_CONTENT);
+
+        let response = app.request(Method::GET, &uri.replace("/workspace", "workspace"), &token, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
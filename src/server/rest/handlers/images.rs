@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::operator::LocalImage;
+use crate::shared::models::validation::image_reference;
+use crate::shared::models::AppState;
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::containers::validate_node_param;
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::node_client::NodeClient;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, PermissionRequirement};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImageInfo {
+    pub id: String,
+    pub tags: Vec<String>,
+    /// Size in bytes
+    pub size: i64,
+    pub created_at: String,
+    /// True for the image session containers are created from (`HOST_AGENT_IMAGE`)
+    pub session_image: bool,
+}
+
+impl From<LocalImage> for ImageInfo {
+    fn from(image: LocalImage) -> Self {
+        Self {
+            id: image.id,
+            tags: image.tags,
+            size: image.size,
+            created_at: chrono::DateTime::from_timestamp(image.created, 0)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            session_image: image.session_image,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageNodeQuery {
+    /// Node whose image store to use; defaults to the operator without a node name
+    pub node: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct PullImageRequest {
+    /// Image reference, e.g. `raworc-host:latest` or `ghcr.io/org/image:1.2`; defaults to the session image
+    #[validate(custom(function = "image_reference"))]
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PullImageResponse {
+    /// Reference being pulled
+    pub image: String,
+}
+
+async fn require_permission(auth: &AuthContext, state: &AppState, requirement: &PermissionRequirement) -> Result<(), ApiError> {
+    check_api_permission(auth, state, requirement, None)
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })
+}
+
+async fn local_images(node: &NodeClient) -> Result<Vec<ImageInfo>, ApiError> {
+    Ok(node.list_images().await?.into_iter().map(ImageInfo::from).collect())
+}
+
+pub async fn list_images(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImageNodeQuery>,
+) -> ApiResult<Json<Vec<ImageInfo>>> {
+    require_permission(&auth, &state, &permissions::IMAGE_LIST).await?;
+
+    let node = NodeClient::for_node(&state, validate_node_param(query.node.as_deref())?).await?;
+    Ok(Json(local_images(&node).await?))
+}
+
+/// Start pulling an image ahead of time. The node's operator pulls in the background; the
+/// image appears in `GET /api/v0/admin/images` once it is there.
+pub async fn pull_image(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImageNodeQuery>,
+    Json(req): Json<PullImageRequest>,
+) -> ApiResult<(StatusCode, Json<PullImageResponse>)> {
+    require_permission(&auth, &state, &permissions::IMAGE_PULL).await?;
+    req.validate()?;
+
+    let node = NodeClient::for_node(&state, validate_node_param(query.node.as_deref())?).await?;
+    let image = node.pull_image(req.image.as_deref()).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(PullImageResponse { image })))
+}
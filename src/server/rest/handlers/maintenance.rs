@@ -0,0 +1,157 @@
+use axum::{
+    extract::State,
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::shared::models::{AppState, Session};
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions, PermissionRequirement};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadOnlyResponse {
+    pub read_only: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetReadOnlyRequest {
+    pub read_only: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReapIdleResponse {
+    /// Sessions moved to IDLE
+    pub reaped: usize,
+    /// Their IDs; the operator stops each one's container
+    pub session_ids: Vec<String>,
+}
+
+async fn require_permission(auth: &AuthContext, state: &AppState, requirement: &PermissionRequirement) -> Result<(), ApiError> {
+    check_api_permission(auth, state, requirement, None)
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })
+}
+
+pub async fn get_read_only(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<ReadOnlyResponse>> {
+    require_permission(&auth, &state, &permissions::MAINTENANCE_GET).await?;
+
+    Ok(Json(ReadOnlyResponse {
+        read_only: state.read_only.load(Ordering::Relaxed),
+    }))
+}
+
+/// Switch read-only mode on or off for this server process.
+/// The setting is not persisted; a restart goes back to RAWORC_READ_ONLY.
+pub async fn set_read_only(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SetReadOnlyRequest>,
+) -> ApiResult<Json<ReadOnlyResponse>> {
+    require_permission(&auth, &state, &permissions::MAINTENANCE_UPDATE).await?;
+
+    state.read_only.store(req.read_only, Ordering::Relaxed);
+    warn!("Read-only mode {} by {}", if req.read_only { "enabled" } else { "disabled" }, auth.principal.name());
+
+    Ok(Json(ReadOnlyResponse { read_only: req.read_only }))
+}
+
+/// Idle every READY session past its waiting timeout now, queueing the stop of its container
+pub async fn reap_idle_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<ReapIdleResponse>> {
+    require_permission(&auth, &state, &permissions::SESSION_REAP_IDLE).await?;
+
+    let candidates = Session::find_waiting_sessions_to_timeout(&state.db)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to find idle sessions: {}", e)))?;
+
+    let mut session_ids = Vec::new();
+    for candidate in candidates {
+        let expired = Session::expire_waiting(&state.db, candidate.id, auth.principal.name())
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to idle session {}: {}", candidate.id, e)))?;
+        if let Some(session) = expired {
+            session_ids.push(session.id.to_string());
+        }
+    }
+
+    info!("Reaped {} idle sessions for {}", session_ids.len(), auth.principal.name());
+
+    Ok(Json(ReapIdleResponse {
+        reaped: session_ids.len(),
+        session_ids,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use uuid::Uuid;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+    use crate::shared::models::{Session, SessionState, TerminationCause};
+
+    /// A READY session last active `idle_secs` ago with a one-minute waiting timeout
+    async fn ready_session(app: &TestApp, idle_secs: f64) -> Uuid {
+        let id = app.create_session(&unique("user")).await;
+        sqlx::query(
+            "UPDATE sessions SET state = 'READY', waiting_timeout_seconds = 60, last_activity_at = NOW() - make_interval(secs => $2) WHERE id = $1",
+        )
+        .bind(id)
+        .bind(idle_secs)
+        .execute(&*app.state.db)
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn reap_idle_idles_stale_ready_sessions_now() {
+        let app = TestApp::new().await;
+        let stale = ready_session(&app, 120.0).await;
+        let active = ready_session(&app, 10.0).await;
+
+        let response = app.request(Method::POST, "/api/v0/admin/sessions/reap-idle", &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let reaped = body_json(response).await;
+        let ids: Vec<&str> = reaped["session_ids"].as_array().unwrap().iter().filter_map(|id| id.as_str()).collect();
+        assert!(ids.contains(&stale.to_string().as_str()));
+        assert!(!ids.contains(&active.to_string().as_str()));
+
+        let session = Session::find_by_id(&app.state.db, stale).await.unwrap().unwrap();
+        assert_eq!(session.state, SessionState::Idle);
+        assert_eq!(session.termination_cause, Some(TerminationCause::IdleTimeout));
+        let stops: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM session_tasks WHERE session_id = $1 AND task_type = 'stop_session'")
+            .bind(stale)
+            .fetch_one(&*app.state.db)
+            .await
+            .unwrap();
+        assert_eq!(stops, 1);
+
+        let session = Session::find_by_id(&app.state.db, active).await.unwrap().unwrap();
+        assert_eq!(session.state, SessionState::Ready);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn reap_idle_is_admin_only() {
+        let app = TestApp::new().await;
+
+        let response = app.request(Method::POST, "/api/v0/admin/sessions/reap-idle", &app.user_token(&unique("user")), None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
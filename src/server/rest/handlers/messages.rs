@@ -1,68 +1,420 @@
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
+use utoipa::ToSchema;
+use validator::{ValidationError, ValidationErrors};
 use sqlx;
 
 use crate::shared::models::{
-    AppState, SessionMessage, CreateMessageRequest, MessageResponse, ListMessagesQuery
+    AppState, Session, SessionState, SessionMessage, MessageRole, CreateMessageRequest, CreateSystemMessageRequest, MessageResponse, ListMessagesQuery, MessageFilter, MessageOrder
 };
+use crate::shared::models::message::{batch_item_key, FROM_HOST_KEY, IMPORTED_KEY};
+use crate::shared::models::validation::check_length;
 use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::sessions::ensure_capacity;
 use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions};
+
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+// Raised by the session_messages insert trigger for an AGENT message from an unassigned agent
+const AGENT_ASSIGNED_CONSTRAINT: &str = "session_messages_agent_assigned";
+
+/// Most messages accepted in one batch
+const MAX_BATCH_MESSAGES: usize = 100;
+/// Longest `/{index}` suffix a batch item's idempotency key can get
+const BATCH_KEY_SUFFIX_LEN: usize = 3;
+/// Retry-After sent when a session's backlog of unanswered messages is full
+const BACKLOG_RETRY_AFTER_SECS: u64 = 10;
+/// Sent with accepted USER messages when RAWORC_MAX_PENDING_MESSAGES is set, so producers can
+/// slow down before they are turned away
+const BACKLOG_LIMIT_HEADER: &str = "X-Backlog-Limit";
+const BACKLOG_REMAINING_HEADER: &str = "X-Backlog-Remaining";
+
+/// Backlog limit headers for a response, when the request was checked against the limit
+type BacklogHeaders = Option<[(&'static str, String); 2]>;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageCountResponse {
+    pub count: i64,
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClearMessagesResponse {
+    pub deleted: u64,
+    pub session_id: String,
+}
 
 pub async fn create_message(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<Uuid>,
-    Extension(_auth): Extension<AuthContext>,
-    Json(req): Json<CreateMessageRequest>,
-) -> ApiResult<Json<MessageResponse>> {
-    // Validate that agent_id is provided when role is AGENT
-    if req.role == crate::shared::models::MessageRole::Agent && req.agent_id.is_none() {
-        return Err(ApiError::BadRequest("agent_id is required when role is AGENT".to_string()));
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+    Json(mut req): Json<CreateMessageRequest>,
+) -> ApiResult<(BacklogHeaders, Json<MessageResponse>)> {
+    validate_message(&req, state.config.server.max_message_length)?;
+    stamp_origin(&auth, std::slice::from_mut(&mut req));
+    
+    let idempotency_key = idempotency_key(&headers, MAX_IDEMPOTENCY_KEY_LEN)?;
+    
+    let session = find_session(&state, session_id).await?;
+    ensure_may_post_system(&state, &auth, &session.workspace, std::slice::from_ref(&req)).await?;
+    
+    // A repeated key replays the original message without touching session state again
+    if let Some(key) = &idempotency_key {
+        let existing = SessionMessage::find_by_idempotency_key(&*state.db, session_id, key)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Database error: {}", e)))?;
+        if let Some(message) = existing {
+            tracing::debug!("Returning existing message {} for idempotency key {}", message.id, key);
+            return Ok((None, Json(message_response(&state, message).await)));
+        }
+    }
+    
+    if let Some((_, agent_id)) = find_unassigned_agent(&state, session_id, std::slice::from_ref(&req)).await? {
+        return Err(unassigned_agent(agent_id, session_id));
+    }
+    let backlog = ensure_backlog_room(&state, session_id, std::slice::from_ref(&req)).await?;
+    
+    mark_session_busy(&state, &session).await?;
+    
+    // Create the message
+    let message = SessionMessage::create(&state.db, session_id, req, idempotency_key.as_deref())
+        .await
+        .map_err(|e| {
+            if let Some(rejection) = unassigned_agent_rejection(&e) {
+                return rejection;
+            }
+            eprintln!("Database error creating message: {:?}", e);
+            ApiError::Internal(anyhow::anyhow!("Failed to create message: {}", e))
+        })?;
+    
+    Ok((backlog, Json(message_response(&state, message).await)))
+}
+
+/// Store several messages in one transaction, returning them in request order
+pub async fn create_messages_batch(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+    Json(mut reqs): Json<Vec<CreateMessageRequest>>,
+) -> ApiResult<(BacklogHeaders, Json<Vec<MessageResponse>>)> {
+    if reqs.is_empty() || reqs.len() > MAX_BATCH_MESSAGES {
+        return Err(ApiError::BadRequest(format!(
+            "A batch must contain between 1 and {} messages",
+            MAX_BATCH_MESSAGES
+        )));
+    }
+    for (index, req) in reqs.iter().enumerate() {
+        validate_message(req, state.config.server.max_message_length).map_err(|errors| item_errors(index, errors))?;
+    }
+    stamp_origin(&auth, &mut reqs);
+    
+    // Leave room for the `/{index}` suffix each item's key gets
+    let idempotency_key = idempotency_key(&headers, MAX_IDEMPOTENCY_KEY_LEN - BATCH_KEY_SUFFIX_LEN)?;
+    
+    let session = find_session(&state, session_id).await?;
+    ensure_may_post_system(&state, &auth, &session.workspace, &reqs).await?;
+    
+    if let Some(key) = &idempotency_key {
+        if let Some(stored) = find_stored_batch(&state, session_id, key, reqs.len()).await? {
+            tracing::debug!("Returning existing batch of {} messages for idempotency key {}", stored.len(), key);
+            let mut responses = Vec::with_capacity(stored.len());
+            for message in stored {
+                responses.push(message_response(&state, message).await);
+            }
+            return Ok((None, Json(responses)));
+        }
+    }
+    
+    if let Some((index, agent_id)) = find_unassigned_agent(&state, session_id, &reqs).await? {
+        let e = unassigned_agent(agent_id, session_id);
+        return Err(ApiError::BadRequest(format!("messages[{}]: {}", index, e)));
     }
+    let backlog = ensure_backlog_room(&state, session_id, &reqs).await?;
+    mark_session_busy(&state, &session).await?;
+    
+    let messages = SessionMessage::create_batch(&state.db, session_id, reqs, idempotency_key.as_deref())
+        .await
+        .map_err(|e| {
+            if let Some(rejection) = unassigned_agent_rejection(&e) {
+                return rejection;
+            }
+            ApiError::Internal(anyhow::anyhow!("Failed to create messages: {}", e))
+        })?;
     
-    // Verify session exists and user has access
-    let session = crate::shared::models::Session::find_by_id(&state.db, session_id)
+    let mut responses = Vec::with_capacity(messages.len());
+    for message in messages {
+        responses.push(message_response(&state, message).await);
+    }
+    Ok((backlog, Json(responses)))
+}
+
+/// Post a SYSTEM message, e.g. a policy reminder, on behalf of an operator. Unlike the generic
+/// endpoint the role is fixed and the caller needs `sessions:post-system-message`, so ordinary
+/// users can't speak as the system. The session's state is left alone.
+pub async fn create_system_message(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+    Json(req): Json<CreateSystemMessageRequest>,
+) -> ApiResult<Json<MessageResponse>> {
+    let session = find_session(&state, session_id).await?;
+
+    check_api_permission(&auth, &state, &permissions::SESSION_MESSAGE_SYSTEM, Some(&session.workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    check_length("content", &req.content, state.config.server.max_message_length)?;
+    let serde_json::Value::Object(mut metadata) = req.metadata else {
+        return Err(ApiError::BadRequest("metadata must be a JSON object".to_string()));
+    };
+    metadata.insert("posted_by".to_string(), serde_json::json!(auth.principal.name()));
+
+    let idempotency_key = idempotency_key(&headers, MAX_IDEMPOTENCY_KEY_LEN)?;
+    let request = CreateMessageRequest {
+        role: MessageRole::System,
+        content: req.content,
+        agent_id: None,
+        metadata: serde_json::Value::Object(metadata),
+    };
+
+    // A repeated key returns the stored message, as with the generic endpoint
+    let message = SessionMessage::create(&state.db, session_id, request, idempotency_key.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to create message: {}", e)))?;
+
+    if let Err(e) = state
+        .record_audit_event("post-system-message", "session", Some(session_id), &auth.principal, serde_json::json!({ "message_id": message.id }))
+        .await
+    {
+        tracing::warn!("Failed to record audit event for session {} system message: {}", session_id, e);
+    }
+
+    Ok(Json(message_response(&state, message).await))
+}
+
+/// Checks every new message must pass, returning the problems by field
+pub(crate) fn validate_message(req: &CreateMessageRequest, max_length: usize) -> Result<(), ValidationErrors> {
+    let mut errors = match check_length("content", &req.content, max_length) {
+        Ok(()) => ValidationErrors::new(),
+        Err(errors) => errors,
+    };
+    if req.role == MessageRole::Agent && req.agent_id.is_none() {
+        errors.add("agent_id", ValidationError::new("required").with_message("is required when role is AGENT".into()));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Field errors of one message in a batch, keyed like `messages[2].content`
+fn item_errors(index: usize, errors: ValidationErrors) -> ApiError {
+    match ApiError::from(errors) {
+        ApiError::Validation(fields) => ApiError::Validation(
+            fields
+                .into_iter()
+                .map(|(field, message)| (format!("messages[{}].{}", index, field), message))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Mark messages posted with the session's host token as coming from the host, dropping any
+/// such mark a client sent itself
+pub(crate) fn stamp_origin(auth: &AuthContext, reqs: &mut [CreateMessageRequest]) {
+    for req in reqs {
+        if let serde_json::Value::Object(metadata) = &mut req.metadata {
+            metadata.remove(FROM_HOST_KEY);
+            metadata.remove(IMPORTED_KEY);
+            if auth.host_session.is_some() {
+                metadata.insert(FROM_HOST_KEY.to_string(), serde_json::Value::Bool(true));
+            }
+        }
+    }
+}
+
+/// SYSTEM messages speak for the platform, so the generic endpoints only accept them from
+/// callers who could post them through `create_system_message`, and from session hosts,
+/// whose tokens only reach their own session
+pub(crate) async fn ensure_may_post_system(
+    state: &AppState,
+    auth: &AuthContext,
+    workspace: &str,
+    reqs: &[CreateMessageRequest],
+) -> Result<(), ApiError> {
+    if auth.host_session.is_some() || !reqs.iter().any(|req| req.role == MessageRole::System) {
+        return Ok(());
+    }
+    check_api_permission(auth, state, &permissions::SESSION_MESSAGE_SYSTEM, Some(workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden(
+                "Posting SYSTEM messages requires the sessions/post-system-message permission".to_string(),
+            ),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })
+}
+
+/// Read the optional `Idempotency-Key` header, rejecting keys longer than `max_len`
+fn idempotency_key(headers: &HeaderMap, max_len: usize) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| ApiError::BadRequest("Idempotency-Key must be valid ASCII".to_string()))?
+        .trim();
+    if key.is_empty() || key.len() > max_len {
+        return Err(ApiError::BadRequest(format!(
+            "Idempotency-Key must be between 1 and {} characters",
+            max_len
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// The batch an earlier request stored under `key`, if there was one. Batches are stored
+/// atomically, so every item key of a replay must exist, and no item key beyond them: anything
+/// else means the key was used for a batch of a different size.
+async fn find_stored_batch(
+    state: &AppState,
+    session_id: Uuid,
+    key: &str,
+    len: usize,
+) -> Result<Option<Vec<SessionMessage>>, ApiError> {
+    let keys: Vec<String> = (0..=len).map(|index| batch_item_key(key, index)).collect();
+    let stored = SessionMessage::find_by_idempotency_keys(&*state.db, session_id, &keys)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Database error: {}", e)))?;
+    match stored.len() {
+        0 => Ok(None),
+        found if found == len => Ok(Some(stored)),
+        _ => Err(ApiError::Conflict(
+            "Idempotency-Key was already used for a batch with a different number of messages".to_string(),
+        )),
+    }
+}
+
+async fn find_session(state: &AppState, session_id: Uuid) -> Result<Session, ApiError> {
+    Session::find_by_id(&state.db, session_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Database error: {}", e)))?
-        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))
+}
+
+/// First AGENT message, by index, whose agent isn't assigned to the session
+async fn find_unassigned_agent(
+    state: &AppState,
+    session_id: Uuid,
+    reqs: &[CreateMessageRequest],
+) -> Result<Option<(usize, Uuid)>, ApiError> {
+    if !reqs.iter().any(|req| req.role == MessageRole::Agent) {
+        return Ok(None);
+    }
     
-    // Check if session is idle and needs reactivation
-    if session.state == crate::shared::models::SessionState::Idle {
-        tracing::info!("Reactivating idle session {} due to new message", session_id);
-        
-        // Update session state to READY first
-        sqlx::query(
-            "UPDATE sessions SET state = 'READY', last_activity_at = CURRENT_TIMESTAMP WHERE id = $1 AND state = 'IDLE'"
-        )
-        .bind(session_id)
-        .execute(&*state.db)
+    let assigned: HashSet<Uuid> = Session::get_agents(&state.db, session_id)
         .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update session state: {}", e)))?;
-        
-        // Add task to reactivate container
-        sqlx::query(
-            r#"
-            INSERT INTO session_tasks (session_id, task_type, payload, status)
-            VALUES ($1, 'reactivate_session', '{}', 'pending')
-            "#
-        )
-        .bind(session_id)
-        .execute(&*state.db)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session agents: {}", e)))?
+        .into_iter()
+        .map(|agent| agent.id)
+        .collect();
+    
+    Ok(reqs
+        .iter()
+        .enumerate()
+        .filter(|(_, req)| req.role == MessageRole::Agent)
+        .filter_map(|(index, req)| req.agent_id.map(|agent_id| (index, agent_id)))
+        .find(|(_, agent_id)| !assigned.contains(agent_id)))
+}
+
+fn unassigned_agent(agent_id: Uuid, session_id: Uuid) -> ApiError {
+    ApiError::BadRequest(format!("Agent {} is not assigned to session {}", agent_id, session_id))
+}
+
+/// The insert trigger's rejection of an agent detached since `find_unassigned_agent` ran, as a 400
+fn unassigned_agent_rejection(e: &sqlx::Error) -> Option<ApiError> {
+    e.as_database_error()
+        .filter(|db| db.constraint() == Some(AGENT_ASSIGNED_CONSTRAINT))
+        .map(|db| ApiError::BadRequest(db.message().to_string()))
+}
+
+/// Refuse USER messages that would grow the session's unanswered backlog past
+/// RAWORC_MAX_PENDING_MESSAGES, so a runaway producer can't bury the host, and report the room
+/// left once they are stored. The check is soft: concurrent requests can overshoot the limit
+/// by a few messages.
+async fn ensure_backlog_room(
+    state: &AppState,
+    session_id: Uuid,
+    reqs: &[CreateMessageRequest],
+) -> Result<BacklogHeaders, ApiError> {
+    let Some(max_pending) = state.config.server.max_pending_messages else {
+        return Ok(None);
+    };
+    // Agent replies drain the backlog, so only user input is held back
+    let incoming = reqs.iter().filter(|req| req.role == MessageRole::User).count() as i64;
+    if incoming == 0 {
+        return Ok(None);
+    }
+    // Waiting wouldn't help a batch that doesn't fit even an empty backlog
+    if incoming > max_pending as i64 {
+        return Err(ApiError::BadRequest(format!(
+            "The batch has {} user messages but a session accepts at most {} unanswered ones",
+            incoming, max_pending
+        )));
+    }
+    
+    let pending = SessionMessage::count_pending(&state.db, session_id)
         .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to create reactivate task: {}", e)))?;
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to count pending messages: {}", e)))?;
+    if pending + incoming <= max_pending as i64 {
+        let remaining = max_pending as i64 - pending - incoming;
+        return Ok(Some([
+            (BACKLOG_LIMIT_HEADER, max_pending.to_string()),
+            (BACKLOG_REMAINING_HEADER, remaining.to_string()),
+        ]));
+    }
+    
+    Err(ApiError::CapacityExceeded {
+        message: format!(
+            "Session {} has {} unanswered messages and accepts at most {}; wait for the agent to catch up",
+            session_id, pending, max_pending
+        ),
+        retry_after_secs: BACKLOG_RETRY_AFTER_SECS,
+    })
+}
+
+/// Move the session to BUSY for an incoming message, reactivating it first if it is idle
+async fn mark_session_busy(state: &AppState, session: &Session) -> Result<(), ApiError> {
+    let session_id = session.id;
+    
+    // An idle session's container is restarted when capacity allows; the host answers the
+    // message once the operator has moved the session from INIT back to READY
+    if session.state == SessionState::Idle {
+        tracing::info!("Reactivating idle session {} due to new message", session_id);
         
-        // Now transition to BUSY for message processing
-        sqlx::query(
-            "UPDATE sessions SET state = 'BUSY', last_activity_at = CURRENT_TIMESTAMP WHERE id = $1 AND state = 'READY'"
-        )
-        .bind(session_id)
-        .execute(&*state.db)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update session state: {}", e)))?;
-    } else if session.state == crate::shared::models::SessionState::Ready {
+        ensure_capacity(state).await?;
+        Session::reactivate(&state.db, session_id, "system", "Reactivated by a new message")
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to reactivate session: {}", e)))?;
+    } else if session.state == SessionState::Ready {
         // Update session to BUSY when processing a message
         sqlx::query(
             "UPDATE sessions SET state = 'BUSY', last_activity_at = CURRENT_TIMESTAMP WHERE id = $1 AND state = 'READY'"
@@ -73,14 +425,10 @@ pub async fn create_message(
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update session state: {}", e)))?;
     }
     
-    // Create the message
-    let message = SessionMessage::create(&state.db, session_id, req)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error creating message: {:?}", e);
-            ApiError::Internal(anyhow::anyhow!("Failed to create message: {}", e))
-        })?;
-    
+    Ok(())
+}
+
+async fn message_response(state: &AppState, message: SessionMessage) -> MessageResponse {
     // Get agent name if applicable
     let agent_name = if let Some(agent_id) = message.agent_id {
         crate::shared::models::Agent::find_by_id(&state.db, agent_id)
@@ -92,7 +440,7 @@ pub async fn create_message(
         None
     };
     
-    Ok(Json(MessageResponse {
+    MessageResponse {
         id: message.id.to_string(),
         session_id: message.session_id.to_string(),
         role: message.role,
@@ -101,7 +449,7 @@ pub async fn create_message(
         agent_name,
         metadata: message.metadata,
         created_at: message.created_at.to_rfc3339(),
-    }))
+    }
 }
 
 pub async fn list_messages(
@@ -116,10 +464,24 @@ pub async fn list_messages(
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Database error: {}", e)))?
         .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
     
+    let agent_id = query
+        .agent_id
+        .as_deref()
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid agent ID format".to_string()))?;
+    let filter = MessageFilter {
+        role: query.role,
+        since: query.since,
+        agent_id,
+    };
+    
     // Get messages with agent info
     let messages = SessionMessage::get_with_agent_info(
         &state.db, 
         session_id, 
+        &filter,
+        query.order,
         query.limit, 
         query.offset
     )
@@ -129,11 +491,45 @@ pub async fn list_messages(
     Ok(Json(messages))
 }
 
+/// The session's newest message, or 204 when it has none
+pub async fn get_latest_message(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+    Extension(auth): Extension<AuthContext>,
+) -> ApiResult<Response> {
+    let session = find_session(&state, session_id).await?;
+
+    if session.created_by != auth.principal.name() {
+        let is_admin = crate::server::auth::check_permission(
+            &auth.principal,
+            &state,
+            &permissions::SESSION_GET_ALL.context(),
+        )
+        .await
+        .unwrap_or(false);
+
+        if !is_admin {
+            return Err(ApiError::Forbidden("Cannot access other users' sessions".to_string()));
+        }
+    }
+
+    let latest = SessionMessage::get_with_agent_info(&state.db, session_id, &MessageFilter::default(), MessageOrder::Desc, Some(1), None)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch messages: {}", e)))?
+        .into_iter()
+        .next();
+
+    Ok(match latest {
+        Some(message) => Json(message).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    })
+}
+
 pub async fn get_message_count(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<Uuid>,
     Extension(_auth): Extension<AuthContext>,
-) -> ApiResult<Json<serde_json::Value>> {
+) -> ApiResult<Json<MessageCountResponse>> {
     // Verify session exists
     let _session = crate::shared::models::Session::find_by_id(&state.db, session_id)
         .await
@@ -144,17 +540,17 @@ pub async fn get_message_count(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to count messages: {}", e)))?;
     
-    Ok(Json(serde_json::json!({
-        "count": count,
-        "session_id": session_id.to_string()
-    })))
+    Ok(Json(MessageCountResponse {
+        count,
+        session_id: session_id.to_string(),
+    }))
 }
 
 pub async fn clear_messages(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<Uuid>,
     Extension(_auth): Extension<AuthContext>,
-) -> ApiResult<Json<serde_json::Value>> {
+) -> ApiResult<Json<ClearMessagesResponse>> {
     // Verify session exists
     let _session = crate::shared::models::Session::find_by_id(&state.db, session_id)
         .await
@@ -165,8 +561,340 @@ pub async fn clear_messages(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to delete messages: {}", e)))?;
     
-    Ok(Json(serde_json::json!({
-        "deleted": deleted_count,
-        "session_id": session_id.to_string()
-    })))
-}
\ No newline at end of file
+    Ok(Json(ClearMessagesResponse {
+        deleted: deleted_count,
+        session_id: session_id.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    fn keyed_post(uri: &str, token: &str, key: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("Idempotency-Key", key)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn idempotent_replay_needs_the_session_to_exist() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let session_id = app.create_session(&user).await;
+        let uri = format!("/api/v0/sessions/{}/messages", session_id);
+        let message = serde_json::json!({ "role": "USER", "content": "hello" });
+
+        let first = body_json(app.send(keyed_post(&uri, &app.user_token(&user), "retry-1", message.clone())).await).await;
+        let replay = app.send(keyed_post(&uri, &app.user_token(&user), "retry-1", message.clone())).await;
+        assert_eq!(replay.status(), StatusCode::OK);
+        assert_eq!(body_json(replay).await["id"], first["id"]);
+
+        sqlx::query("UPDATE sessions SET deleted_at = NOW() WHERE id = $1")
+            .bind(session_id)
+            .execute(&*app.state.db)
+            .await
+            .unwrap();
+        let replay = app.send(keyed_post(&uri, &app.user_token(&user), "retry-1", message)).await;
+        assert_eq!(replay.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn batch_replay_must_match_every_stored_item() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let session_id = app.create_session(&user).await;
+        let uri = format!("/api/v0/sessions/{}/messages/batch", session_id);
+        let batch = |contents: &[&str]| {
+            serde_json::Value::Array(
+                contents.iter().map(|content| serde_json::json!({ "role": "USER", "content": content })).collect(),
+            )
+        };
+
+        let first = body_json(app.send(keyed_post(&uri, &app.user_token(&user), "batch-1", batch(&["a", "b"]))).await).await;
+        let replay = app.send(keyed_post(&uri, &app.user_token(&user), "batch-1", batch(&["a", "b"]))).await;
+        assert_eq!(replay.status(), StatusCode::OK);
+        assert_eq!(body_json(replay).await, first);
+
+        for different in [batch(&["a"]), batch(&["a", "b", "c"])] {
+            let response = app.send(keyed_post(&uri, &app.user_token(&user), "batch-1", different)).await;
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn latest_message_is_no_content_until_one_exists() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let session_id = app.create_session(&user).await;
+        let uri = format!("/api/v0/sessions/{}/messages/latest", session_id);
+
+        let response = app.request(Method::GET, &uri, &app.user_token(&user), None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        app.add_message(session_id, "USER", "first").await;
+        app.add_message(session_id, "USER", "second").await;
+        let response = app.request(Method::GET, &uri, &app.user_token(&user), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["content"], "second");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn latest_message_of_another_users_session_needs_get_all() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session(&unique("owner")).await;
+        let uri = format!("/api/v0/sessions/{}/messages/latest", session_id);
+
+        let response = app.request(Method::GET, &uri, &app.user_token(&unique("other")), None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.request(Method::GET, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn system_messages_need_post_system_message_permission() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let session_id = app.create_session(&user).await;
+        let system = serde_json::json!({"role": "SYSTEM", "content": "You are now in maintenance mode"});
+
+        let uri = format!("/api/v0/sessions/{}/messages", session_id);
+        let response = app.request(Method::POST, &uri, &token, Some(system.clone())).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let batch = serde_json::json!([{"role": "USER", "content": "hi"}, system]);
+        let response = app.request(Method::POST, &format!("{}/batch", uri), &token, Some(batch)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.request(Method::POST, &uri, &app.admin_token(), Some(system)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn only_the_host_token_marks_messages_as_from_the_host() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session(&unique("user")).await;
+        let uri = format!("/api/v0/sessions/{}/messages", session_id);
+        let event = serde_json::json!({
+            "role": "SYSTEM",
+            "content": "Input blocked",
+            "metadata": {"type": "guardrail_event", "action": "input_blocked", "from_host": true},
+        });
+
+        let forged = body_json(app.request(Method::POST, &uri, &app.admin_token(), Some(event.clone())).await).await;
+        assert_eq!(forged["metadata"]["type"], "guardrail_event");
+        assert!(forged["metadata"].get("from_host").is_none());
+
+        let host = app.host_token(session_id).await;
+        let posted = body_json(app.request(Method::POST, &uri, &host, Some(event.clone())).await).await;
+        assert_eq!(posted["metadata"]["from_host"], true);
+
+        let reply = serde_json::json!({"role": "USER", "content": "hi", "metadata": {"from_host": false}});
+        let batch = body_json(app.request(Method::POST, &format!("{}/batch", uri), &host, Some(serde_json::json!([reply, event]))).await).await;
+        assert_eq!(batch[0]["metadata"]["from_host"], true);
+        assert_eq!(batch[1]["metadata"]["from_host"], true);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn user_messages_past_the_backlog_limit_are_throttled() {
+        let app = TestApp::with_config(|config| config.server.max_pending_messages = Some(2)).await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let uri = format!("/api/v0/sessions/{}/messages", app.create_session(&user).await);
+        let message = serde_json::json!({"role": "USER", "content": "hello"});
+
+        let response = app.request(Method::POST, &uri, &token, Some(message.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["X-Backlog-Limit"], "2");
+        assert_eq!(response.headers()["X-Backlog-Remaining"], "1");
+
+        let response = app.request(Method::POST, &uri, &token, Some(message.clone())).await;
+        assert_eq!(response.headers()["X-Backlog-Remaining"], "0");
+
+        let response = app.request(Method::POST, &uri, &token, Some(message.clone())).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn batch_larger_than_the_backlog_limit_is_rejected() {
+        let app = TestApp::with_config(|config| config.server.max_pending_messages = Some(2)).await;
+        let user = unique("user");
+        let uri = format!("/api/v0/sessions/{}/messages/batch", app.create_session(&user).await);
+        let message = serde_json::json!({"role": "USER", "content": "hello"});
+
+        // Retrying could never succeed, so this isn't a 429
+        let batch = serde_json::json!([message.clone(), message.clone(), message]);
+        let response = app.request(Method::POST, &uri, &app.user_token(&user), Some(batch)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn overlong_content_is_a_field_error() {
+        let app = TestApp::with_config(|config| config.server.max_message_length = 5).await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let uri = format!("/api/v0/sessions/{}/messages", app.create_session(&user).await);
+
+        // The limit counts characters, not bytes
+        let response = app.request(Method::POST, &uri, &token, Some(serde_json::json!({"role": "USER", "content": "héllo"}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.request(Method::POST, &uri, &token, Some(serde_json::json!({"role": "USER", "content": "hello!"}))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body_json(response).await["error"]["details"]["content"], "must be at most 5 characters");
+
+        let batch = serde_json::json!([{"role": "USER", "content": "hi"}, {"role": "USER", "content": "hello!"}]);
+        let response = app.request(Method::POST, &format!("{}/batch", uri), &token, Some(batch)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body_json(response).await["error"]["details"]["messages[1].content"], "must be at most 5 characters");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn agent_messages_need_an_agent_assigned_to_the_session() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let session_id = app.create_session(&user).await;
+        let uri = format!("/api/v0/sessions/{}/messages", session_id);
+        let insert_agent = || {
+            sqlx::query_scalar::<_, uuid::Uuid>("INSERT INTO agents (name, workspace, instructions, model) VALUES ($1, 'default', 'test', 'claude-3-haiku') RETURNING id")
+                .bind(unique("agent"))
+                .fetch_one(&*app.state.db)
+        };
+        let (assigned, unassigned) = (insert_agent().await.unwrap(), insert_agent().await.unwrap());
+        let response = app
+            .request(Method::POST, &format!("/api/v0/sessions/{}/agents", session_id), &token, Some(serde_json::json!({"agent_id": assigned})))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.request(Method::POST, &uri, &token, Some(serde_json::json!({"role": "AGENT", "content": "hi"}))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body_json(response).await["error"]["details"]["agent_id"], "is required when role is AGENT");
+
+        let message = serde_json::json!({"role": "AGENT", "content": "hi", "agent_id": unassigned});
+        let response = app.request(Method::POST, &uri, &token, Some(message.clone())).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.request(Method::POST, &format!("{}/batch", uri), &token, Some(serde_json::json!([message]))).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .request(Method::POST, &uri, &token, Some(serde_json::json!({"role": "AGENT", "content": "hi", "agent_id": assigned})))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The database holds writers that skip the API to the same rules
+        for agent_id in [None, Some(unassigned)] {
+            let inserted = sqlx::query("INSERT INTO session_messages (session_id, role, content, agent_id) VALUES ($1, 'AGENT', 'hi', $2)")
+                .bind(session_id)
+                .bind(agent_id)
+                .execute(&*app.state.db)
+                .await;
+            assert!(inserted.is_err(), "agent {:?}", agent_id);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn messages_can_be_listed_for_one_agent() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let session_id = app.create_session(&user).await;
+        let uri = format!("/api/v0/sessions/{}/messages", session_id);
+
+        let mut agents = Vec::new();
+        for _ in 0..2 {
+            let agent_id: uuid::Uuid = sqlx::query_scalar("INSERT INTO agents (name, workspace, instructions, model) VALUES ($1, 'default', 'test', 'claude-3-haiku') RETURNING id")
+                .bind(unique("agent"))
+                .fetch_one(&*app.state.db)
+                .await
+                .unwrap();
+            let response = app
+                .request(Method::POST, &format!("/api/v0/sessions/{}/agents", session_id), &token, Some(serde_json::json!({"agent_id": agent_id})))
+                .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            agents.push(agent_id);
+        }
+        app.add_message(session_id, "USER", "hello").await;
+        for (agent_id, content) in [(agents[0], "first"), (agents[1], "second"), (agents[0], "third")] {
+            let message = serde_json::json!({"role": "AGENT", "content": content, "agent_id": agent_id});
+            assert_eq!(app.request(Method::POST, &uri, &token, Some(message)).await.status(), StatusCode::OK);
+        }
+
+        let contents = |query: String| {
+            let (app, token, uri) = (&app, &token, &uri);
+            async move {
+                let response = app.request(Method::GET, &format!("{}?{}", uri, query), token, None).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response)
+                    .await
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|message| message["content"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(contents(format!("agent_id={}", agents[0])).await, ["first", "third"]);
+        assert_eq!(contents(format!("agent_id={}&role=AGENT&limit=1", agents[1])).await, ["second"]);
+        assert!(contents(format!("agent_id={}&role=USER", agents[0])).await.is_empty());
+
+        let response = app.request(Method::GET, &format!("{}?agent_id=not-a-uuid", uri), &token, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn messages_can_be_listed_newest_first() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        let token = app.user_token(&user);
+        let session_id = app.create_session(&user).await;
+        for content in ["first", "second", "third"] {
+            app.add_message(session_id, "USER", content).await;
+        }
+
+        let contents = |query: &'static str| {
+            let (app, token) = (&app, &token);
+            async move {
+                let uri = format!("/api/v0/sessions/{}/messages{}", session_id, query);
+                let response = app.request(Method::GET, &uri, token, None).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response)
+                    .await
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|message| message["content"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(contents("").await, ["first", "second", "third"]);
+        assert_eq!(contents("?order=desc").await, ["third", "second", "first"]);
+        assert_eq!(contents("?order=desc&limit=1").await, ["third"]);
+        assert_eq!(contents("?order=asc&offset=2").await, ["third"]);
+    }
+}
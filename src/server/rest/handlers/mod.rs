@@ -3,4 +3,12 @@ pub mod roles;
 pub mod role_bindings;
 pub mod agents;
 pub mod sessions;
-pub mod messages;
\ No newline at end of file
+pub mod messages;
+pub mod workspaces;
+pub mod secrets;
+pub mod containers;
+pub mod usage;
+pub mod maintenance;
+pub mod images;
+pub mod shell;
+pub mod files;
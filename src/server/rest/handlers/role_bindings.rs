@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Extension,
     Json,
 };
@@ -9,8 +9,9 @@ use std::sync::Arc;
 use utoipa::ToSchema;
 
 use crate::shared::models::AppState;
-use crate::server::rbac::{RoleBinding, SubjectType};
+use crate::server::rbac::{BindingScope, RoleBinding, SubjectType};
 use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::workspaces::validate_workspace_name;
 use crate::server::rest::middleware::AuthContext;
 use crate::server::rest::rbac_enforcement::{check_api_permission, permissions};
 
@@ -23,6 +24,24 @@ pub struct CreateRoleBindingRequest {
     pub workspace: Option<String>, // NULL = global, String = specific organization
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ListRoleBindingsQuery {
+    #[serde(default)]
+    pub scope: BindingScope,
+    pub workspace: Option<String>,
+}
+
+/// Largest number of bindings accepted by a single bulk request
+const MAX_BULK_ROLE_BINDINGS: usize = 100;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkRoleBindingResult {
+    /// Position of the binding in the request
+    pub index: usize,
+    /// False when an identical binding already existed
+    pub created: bool,
+    pub binding: RoleBindingResponse,
+}
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct RoleBindingResponse {
@@ -51,6 +70,7 @@ impl From<RoleBinding> for RoleBindingResponse {
 pub async fn list_role_bindings(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ListRoleBindingsQuery>,
 ) -> ApiResult<Json<Vec<RoleBindingResponse>>> {
     // Check permission
     check_api_permission(&auth, &state, &permissions::ROLE_BINDING_LIST, None)
@@ -60,7 +80,15 @@ pub async fn list_role_bindings(
             _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
         })?;
 
-    let bindings = state.get_all_role_bindings().await?;
+    let workspace = match query.workspace.as_deref() {
+        Some(_) if query.scope == BindingScope::Global => {
+            return Err(ApiError::BadRequest("workspace cannot be combined with scope=global".to_string()));
+        }
+        Some(workspace) => Some(validate_workspace_name(workspace)?),
+        None => None,
+    };
+
+    let bindings = state.get_role_bindings_in_scope(query.scope, workspace.as_deref()).await?;
     let response: Vec<RoleBindingResponse> = bindings.into_iter().map(Into::into).collect();
     Ok(Json(response))
 }
@@ -93,8 +121,12 @@ pub async fn get_role_binding(
 pub async fn create_role_binding(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateRoleBindingRequest>,
+    Json(mut req): Json<CreateRoleBindingRequest>,
 ) -> ApiResult<Json<RoleBindingResponse>> {
+    if let Some(workspace) = req.workspace.as_deref() {
+        req.workspace = Some(validate_workspace_name(workspace)?);
+    }
+
     // Check permission - need extra permissions for global bindings
     let target_workspace = req.workspace.as_deref();
     check_api_permission(&auth, &state, &permissions::ROLE_BINDING_CREATE, target_workspace)
@@ -112,10 +144,78 @@ pub async fn create_role_binding(
         created_at: Utc::now().to_rfc3339(),
     };
     
-    let created_binding = state.create_role_binding(&role_binding).await?;
+    let created_binding = state
+        .create_role_binding(&role_binding)
+        .await?
+        .ok_or_else(|| ApiError::Conflict(format!(
+            "Role '{}' is already bound to '{}'",
+            role_binding.role_name, role_binding.principal_name
+        )))?;
     Ok(Json(created_binding.into()))
 }
 
+pub async fn create_role_bindings_bulk(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(requests): Json<Vec<CreateRoleBindingRequest>>,
+) -> ApiResult<Json<Vec<BulkRoleBindingResult>>> {
+    if requests.is_empty() {
+        return Err(ApiError::BadRequest("At least one role binding is required".to_string()));
+    }
+    if requests.len() > MAX_BULK_ROLE_BINDINGS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} role bindings can be created per request",
+            MAX_BULK_ROLE_BINDINGS
+        )));
+    }
+
+    // Validate every item before writing anything so the batch is all-or-nothing
+    let mut role_bindings = Vec::with_capacity(requests.len());
+    for (index, req) in requests.into_iter().enumerate() {
+        let workspace = match req.workspace.as_deref() {
+            Some(workspace) => Some(validate_workspace_name(workspace).map_err(|e| match e {
+                ApiError::BadRequest(msg) => ApiError::BadRequest(format!("Item {}: {}", index, msg)),
+                other => other,
+            })?),
+            None => None,
+        };
+
+        check_api_permission(&auth, &state, &permissions::ROLE_BINDING_CREATE, workspace.as_deref())
+            .await
+            .map_err(|e| match e {
+                axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden(format!("Item {}: Insufficient permissions", index)),
+                _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+            })?;
+
+        if state.get_role(&req.role_name).await?.is_none() {
+            return Err(ApiError::BadRequest(format!("Item {}: Role '{}' not found", index, req.role_name)));
+        }
+
+        role_bindings.push(RoleBinding {
+            id: None,
+            role_name: req.role_name,
+            principal_name: req.principal_name,
+            principal_type: req.principal_type,
+            workspace,
+            created_at: Utc::now().to_rfc3339(),
+        });
+    }
+
+    let results = state
+        .create_role_bindings(&role_bindings)
+        .await?
+        .into_iter()
+        .enumerate()
+        .map(|(index, (binding, created))| BulkRoleBindingResult {
+            index,
+            created,
+            binding: binding.into(),
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
 pub async fn delete_role_binding(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
@@ -128,17 +228,9 @@ pub async fn delete_role_binding(
             axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
             _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
         })?;
-    let deleted = if uuid::Uuid::parse_str(&id).is_ok() {
-        // For UUID, we need to find the binding first
-        if let Some(binding) = state.get_all_role_bindings().await?
-            .into_iter()
-            .find(|rb| rb.id == Some(uuid::Uuid::parse_str(&id).unwrap())) {
-            state.delete_role_binding(&binding.role_name, binding.workspace.as_deref()).await?
-        } else {
-            false
-        }
-    } else {
-        state.delete_role_binding(&id, None).await?
+    let deleted = match uuid::Uuid::parse_str(&id) {
+        Ok(uuid) => state.delete_role_binding_by_id(uuid).await?,
+        Err(_) => state.delete_role_binding(&id, None).await?,
     };
     
     if !deleted {
@@ -146,4 +238,138 @@ pub async fn delete_role_binding(
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Delete a single binding by its id, even when other bindings share its role name
+pub async fn delete_role_binding_by_id(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> ApiResult<()> {
+    check_api_permission(&auth, &state, &permissions::ROLE_BINDING_DELETE, None)
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    if !state.delete_role_binding_by_id(id).await? {
+        return Err(ApiError::NotFound("Role binding not found".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn duplicate_bindings_conflict_in_workspaces_and_globally() {
+        let app = TestApp::new().await;
+        let token = app.admin_token();
+        let principal = unique("user");
+
+        for workspace in [Some("default"), None] {
+            let binding = json!({
+                "role_name": "admin", "principal_name": principal, "principal_type": "Subject", "workspace": workspace,
+            });
+            let response = app.request(Method::POST, "/api/v0/role-bindings", &token, Some(binding.clone())).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let response = app.request(Method::POST, "/api/v0/role-bindings", &token, Some(binding.clone())).await;
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+
+            // The bulk endpoint reports the existing binding instead
+            let response = app.request(Method::POST, "/api/v0/role-bindings/bulk", &token, Some(json!([binding]))).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(body_json(response).await[0]["created"], false);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn listings_cover_the_requested_scope() {
+        let app = TestApp::new().await;
+        let token = app.admin_token();
+        let principal = unique("user");
+        let (workspace, other) = (unique("ws"), unique("ws"));
+        for workspace in [None, Some(workspace.as_str()), Some(other.as_str())] {
+            let binding = json!({
+                "role_name": "admin", "principal_name": principal, "principal_type": "Subject", "workspace": workspace,
+            });
+            let response = app.request(Method::POST, "/api/v0/role-bindings", &token, Some(binding)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // Workspaces of the principal's bindings in a listing, global ones as None
+        let listed = |query: String| {
+            let app = &app;
+            let token = &token;
+            let principal = &principal;
+            async move {
+                let response = app.request(Method::GET, &format!("/api/v0/role-bindings?{}", query), token, None).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                let mut workspaces: Vec<Option<String>> = body_json(response)
+                    .await
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter(|binding| binding["principal_name"] == principal.as_str())
+                    .map(|binding| binding["workspace"].as_str().map(str::to_string))
+                    .collect();
+                workspaces.sort();
+                workspaces
+            }
+        };
+
+        assert_eq!(listed("scope=global".to_string()).await, [None]);
+        let mut both = vec![Some(workspace.clone()), Some(other.clone())];
+        both.sort();
+        assert_eq!(listed("scope=workspace".to_string()).await, both);
+        assert_eq!(listed(format!("scope=workspace&workspace={}", workspace)).await, [Some(workspace.clone())]);
+        assert_eq!(listed(format!("workspace={}", workspace)).await, [None, Some(workspace.clone())]);
+        assert_eq!(listed(String::new()).await.len(), 3);
+
+        let response = app.request(Method::GET, &format!("/api/v0/role-bindings?scope=global&workspace={}", workspace), &token, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn deleting_by_id_leaves_bindings_of_the_same_role() {
+        let app = TestApp::new().await;
+        let token = app.admin_token();
+        let principal = unique("user");
+
+        let mut ids = Vec::new();
+        for workspace in [unique("ws"), unique("ws")] {
+            let binding = json!({
+                "role_name": "admin", "principal_name": principal, "principal_type": "Subject", "workspace": workspace,
+            });
+            let response = app.request(Method::POST, "/api/v0/role-bindings", &token, Some(binding)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            ids.push(body_json(response).await["id"].as_str().unwrap().to_string());
+        }
+
+        let uri = format!("/api/v0/role-bindings/by-id/{}", ids[0]);
+        let response = app.request(Method::DELETE, &uri, &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::DELETE, &uri, &token, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app.request(Method::GET, "/api/v0/role-bindings?scope=workspace", &token, None).await;
+        let remaining: Vec<_> = body_json(response)
+            .await
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|binding| binding["principal_name"] == principal.as_str())
+            .map(|binding| binding["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(remaining, [ids[1].clone()]);
+    }
+}
@@ -0,0 +1,275 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::shared::models::secret::is_valid_secret_name;
+use crate::shared::models::{AppState, CreateSecretRequest, Secret, UpdateSecretRequest};
+use crate::shared::secrets::SecretsCipher;
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::workspaces::validate_workspace_name;
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::rbac_enforcement::{check_api_permission, get_user_workspace, permissions, PermissionRequirement};
+
+/// Unique constraint on a secret's name within its workspace
+const SECRET_NAME_CONSTRAINT: &str = "secrets_unique_name_workspace";
+
+/// Secret metadata; the value is write-only and never included
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SecretResponse {
+    pub id: String,
+    pub name: String,
+    pub workspace: String,
+    pub description: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<Secret> for SecretResponse {
+    fn from(secret: Secret) -> Self {
+        Self {
+            id: secret.id.to_string(),
+            name: secret.name,
+            workspace: secret.workspace,
+            description: secret.description,
+            created_by: secret.created_by,
+            created_at: secret.created_at.to_rfc3339(),
+            updated_at: secret.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecretWorkspaceQuery {
+    pub workspace: Option<String>,
+}
+
+fn cipher(state: &AppState) -> Result<&SecretsCipher, ApiError> {
+    state
+        .secrets
+        .as_deref()
+        .ok_or_else(|| ApiError::Internal(anyhow::anyhow!("Secrets store is not configured (RAWORC_SECRETS_KEY)")))
+}
+
+/// Workspace a request addresses, normalized the way secrets are stored under it
+fn target_workspace(auth: &AuthContext, requested: Option<String>) -> Result<String, ApiError> {
+    let workspace = requested
+        .or_else(|| get_user_workspace(auth))
+        .unwrap_or_else(|| "default".to_string());
+    validate_workspace_name(&workspace)
+}
+
+async fn require(
+    auth: &AuthContext,
+    state: &AppState,
+    requirement: &PermissionRequirement,
+    workspace: &str,
+) -> Result<(), ApiError> {
+    check_api_permission(auth, state, requirement, Some(workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })
+}
+
+pub async fn list_secrets(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SecretWorkspaceQuery>,
+) -> ApiResult<Json<Vec<SecretResponse>>> {
+    let workspace = target_workspace(&auth, query.workspace)?;
+    require(&auth, &state, &permissions::SECRET_LIST, &workspace).await?;
+
+    let secrets = Secret::find_all(&state.db, &workspace)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to list secrets: {}", e)))?;
+
+    Ok(Json(secrets.into_iter().map(Into::into).collect()))
+}
+
+pub async fn get_secret(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<SecretWorkspaceQuery>,
+) -> ApiResult<Json<SecretResponse>> {
+    let workspace = target_workspace(&auth, query.workspace)?;
+    require(&auth, &state, &permissions::SECRET_GET, &workspace).await?;
+
+    let secret = Secret::find_by_name(&state.db, &workspace, &name)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch secret: {}", e)))?
+        .ok_or(ApiError::NotFound("Secret not found".to_string()))?;
+
+    Ok(Json(secret.into()))
+}
+
+pub async fn create_secret(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(mut req): Json<CreateSecretRequest>,
+) -> ApiResult<Json<SecretResponse>> {
+    req.workspace = validate_workspace_name(&req.workspace)?;
+    require(&auth, &state, &permissions::SECRET_CREATE, &req.workspace).await?;
+
+    if !is_valid_secret_name(&req.name) {
+        return Err(ApiError::BadRequest(
+            "Secret names must be upper-case environment variable names (A-Z, 0-9, _)".to_string(),
+        ));
+    }
+
+    let (nonce, ciphertext) = cipher(&state)?
+        .encrypt(&req.workspace, &req.name, &req.value)
+        .map_err(ApiError::Internal)?;
+
+    let secret = Secret::create(
+        &state.db,
+        &req.workspace,
+        &req.name,
+        req.description.as_deref(),
+        &nonce,
+        &ciphertext,
+        auth.principal.name(),
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error().and_then(|db| db.constraint()) == Some(SECRET_NAME_CONSTRAINT) {
+            return ApiError::Conflict(format!("Secret '{}' already exists in workspace '{}'", req.name, req.workspace));
+        }
+        ApiError::Internal(anyhow::anyhow!("Failed to create secret: {}", e))
+    })?;
+
+    Ok(Json(secret.into()))
+}
+
+pub async fn update_secret(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<SecretWorkspaceQuery>,
+    Json(req): Json<UpdateSecretRequest>,
+) -> ApiResult<Json<SecretResponse>> {
+    let workspace = target_workspace(&auth, query.workspace)?;
+    require(&auth, &state, &permissions::SECRET_UPDATE, &workspace).await?;
+
+    let sealed = match &req.value {
+        Some(value) => Some(
+            cipher(&state)?
+                .encrypt(&workspace, &name, value)
+                .map_err(ApiError::Internal)?,
+        ),
+        None => None,
+    };
+
+    let secret = Secret::update(
+        &state.db,
+        &workspace,
+        &name,
+        req.description.as_deref(),
+        sealed.as_ref().map(|(nonce, ciphertext)| (nonce.as_slice(), ciphertext.as_slice())),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update secret: {}", e)))?
+    .ok_or(ApiError::NotFound("Secret not found".to_string()))?;
+
+    Ok(Json(secret.into()))
+}
+
+pub async fn delete_secret(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<SecretWorkspaceQuery>,
+) -> ApiResult<StatusCode> {
+    let workspace = target_workspace(&auth, query.workspace)?;
+    require(&auth, &state, &permissions::SECRET_DELETE, &workspace).await?;
+
+    let deleted = Secret::delete(&state.db, &workspace, &name)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to delete secret: {}", e)))?;
+
+    if !deleted {
+        return Err(ApiError::NotFound("Secret not found".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::server::rest::test_support::{unique, TestApp};
+
+    fn secret_name() -> String {
+        format!("KEY_{}", &Uuid::new_v4().simple().to_string()[..8]).to_uppercase()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn duplicate_secrets_conflict_and_deletes_return_no_content() {
+        let app = TestApp::new().await;
+        let name = secret_name();
+        let secret = json!({"name": name, "value": "s3cret"});
+
+        let response = app.request(Method::POST, "/api/v0/secrets", &app.admin_token(), Some(secret.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::POST, "/api/v0/secrets", &app.admin_token(), Some(secret)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let uri = format!("/api/v0/secrets/{}?workspace=default", name);
+        let response = app.request(Method::DELETE, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.request(Method::DELETE, &uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn sessions_only_name_secrets_their_creator_may_read() {
+        let app = TestApp::new().await;
+        let user = unique("user");
+        app.grant(&user, Some("default"), "sessions", &["create"]).await;
+        let name = secret_name();
+        let session = |name: &str| {
+            json!({"name": unique("session"), "starting_prompt": "hi", "metadata": {"secrets": [name]}})
+        };
+
+        let response = app.request(Method::POST, "/api/v0/sessions", &app.user_token(&user), Some(session(&name))).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        app.grant(&user, Some("default"), "secrets", &["get"]).await;
+        let response = app.request(Method::POST, "/api/v0/sessions", &app.user_token(&user), Some(session(&name))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn lookups_normalize_the_workspace_name() {
+        let app = TestApp::new().await;
+        let name = secret_name();
+        let secret = json!({"name": name, "value": "s3cret", "workspace": "Default"});
+        let response = app.request(Method::POST, "/api/v0/secrets", &app.admin_token(), Some(secret)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.request(Method::GET, &format!("/api/v0/secrets/{}?workspace=DEFAULT", name), &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::GET, &format!("/api/v0/secrets/{}?workspace=de_fault", name), &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // The database holds settings to the same rule
+        let rejected = sqlx::query("INSERT INTO workspace_settings (workspace) VALUES ('Upper_Case')")
+            .execute(&*app.state.db)
+            .await;
+        assert!(rejected.is_err());
+    }
+}
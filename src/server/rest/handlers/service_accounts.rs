@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Extension,
     Json,
 };
@@ -9,11 +9,37 @@ use std::sync::Arc;
 use utoipa::ToSchema;
 
 use crate::shared::models::AppState;
-use crate::server::rbac::ServiceAccount;
+use crate::server::rbac::{ServiceAccount, SubjectType};
 use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::role_bindings::RoleBindingResponse;
+use crate::server::rest::handlers::roles::{RoleResponse, RuleResponse};
 use crate::server::rest::middleware::AuthContext;
 use crate::server::rest::rbac_enforcement::{check_api_permission, permissions};
 
+#[derive(Debug, Deserialize)]
+pub struct ListServiceAccountsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Case-insensitive substring of the account name
+    pub name_contains: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServiceAccountRoleBindingsQuery {
+    /// Include each bound role's rules
+    #[serde(default)]
+    pub expand_rules: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServiceAccountRoleBindingResponse {
+    #[serde(flatten)]
+    pub binding: RoleBindingResponse,
+    /// Rules of the bound role, present when `expand_rules=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<RuleResponse>>,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateServiceAccountRequest {
     pub user: String,
@@ -68,6 +94,7 @@ impl From<ServiceAccount> for ServiceAccountResponse {
 pub async fn list_service_accounts(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ListServiceAccountsQuery>,
 ) -> ApiResult<Json<Vec<ServiceAccountResponse>>> {
     // Check permission
     check_api_permission(&auth, &state, &permissions::SERVICE_ACCOUNT_LIST, None)
@@ -77,7 +104,9 @@ pub async fn list_service_accounts(
             _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
         })?;
 
-    let accounts = state.get_all_service_accounts().await?;
+    let accounts = state
+        .find_service_accounts(query.name_contains.as_deref(), query.limit, query.offset)
+        .await?;
     let response: Vec<ServiceAccountResponse> = accounts.into_iter().map(Into::into).collect();
     Ok(Json(response))
 }
@@ -107,6 +136,54 @@ pub async fn get_service_account(
     Ok(Json(account.into()))
 }
 
+pub async fn get_service_account_role_bindings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ServiceAccountRoleBindingsQuery>,
+) -> ApiResult<Json<Vec<ServiceAccountRoleBindingResponse>>> {
+    // Check permission: reading the account and listing bindings
+    for requirement in [&permissions::SERVICE_ACCOUNT_GET, &permissions::ROLE_BINDING_LIST] {
+        check_api_permission(&auth, &state, requirement, None)
+            .await
+            .map_err(|e| match e {
+                axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+                _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+            })?;
+    }
+
+    let account = if let Ok(uuid) = uuid::Uuid::parse_str(&id) {
+        state.get_all_service_accounts().await?
+            .into_iter()
+            .find(|sa| sa.id == Some(uuid))
+    } else {
+        state.get_service_account(&id).await?
+    };
+    let account = account.ok_or(ApiError::NotFound("Service account not found".to_string()))?;
+
+    let bindings = state
+        .get_role_bindings_for_subject(&account.user, SubjectType::ServiceAccount, None)
+        .await?;
+
+    let mut response = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        let rules = if query.expand_rules {
+            state
+                .get_role(&binding.role_name)
+                .await?
+                .map(|role| RoleResponse::from(role).rules)
+        } else {
+            None
+        };
+        response.push(ServiceAccountRoleBindingResponse {
+            binding: binding.into(),
+            rules,
+        });
+    }
+
+    Ok(Json(response))
+}
+
 pub async fn create_service_account(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
@@ -258,4 +335,76 @@ pub async fn update_service_account(
         .ok_or(ApiError::NotFound("Service account not found".to_string()))?;
     
     Ok(Json(updated_account.into()))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn the_admin_account_lists_its_admin_binding() {
+        let app = TestApp::new().await;
+        let uri = "/api/v0/service-accounts/admin/role-bindings";
+
+        let response = app.request(Method::GET, uri, &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bindings = body_json(response).await;
+        let admin = bindings
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|binding| binding["role_name"] == "admin")
+            .expect("admin binding is listed");
+        assert_eq!(admin["principal_name"], "admin");
+        assert_eq!(admin["principal_type"], "ServiceAccount");
+        assert!(admin.get("rules").is_none());
+
+        let response = app.request(Method::GET, &format!("{}?expand_rules=true", uri), &app.admin_token(), None).await;
+        let bindings = body_json(response).await;
+        let admin = bindings.as_array().unwrap().iter().find(|binding| binding["role_name"] == "admin").unwrap();
+        assert!(!admin["rules"].as_array().unwrap().is_empty());
+
+        let response = app.request(Method::GET, uri, &app.user_token(&unique("user")), None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn service_accounts_are_paged_and_searched_by_name() {
+        let app = TestApp::new().await;
+        let prefix = unique("paged");
+        for n in 1..=3 {
+            app.state.create_service_account(&format!("{}-{}", prefix, n), None, "unused", None).await.unwrap();
+        }
+        let names = |query: String| {
+            let app = &app;
+            async move {
+                let uri = format!("/api/v0/service-accounts?{}", query);
+                let response = app.request(Method::GET, &uri, &app.admin_token(), None).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                body_json(response)
+                    .await
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|account| account["user"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Matching ignores case, and the newest account comes first
+        let all = names(format!("name_contains={}", prefix.to_uppercase())).await;
+        assert_eq!(all, vec![format!("{}-3", prefix), format!("{}-2", prefix), format!("{}-1", prefix)]);
+
+        let first_page = names(format!("name_contains={}&limit=2", prefix)).await;
+        let second_page = names(format!("name_contains={}&limit=2&offset=2", prefix)).await;
+        assert_eq!(first_page, all[..2]);
+        assert_eq!(second_page, all[2..]);
+
+        assert!(names(format!("name_contains={}-none", prefix)).await.is_empty());
+        // Without parameters every account is listed, the seeded admin included
+        assert!(names(String::new()).await.contains(&"admin".to_string()));
+    }
+}
@@ -0,0 +1,188 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    response::Response,
+    Extension,
+};
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::Message as NodeMessage;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::shared::models::{AppState, Session, SessionState};
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::node_client::{NodeClient, NodeShell};
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions};
+
+/// Open an interactive shell in a running session's container over a WebSocket.
+/// Text and binary frames from the client are written to the shell's stdin; terminal
+/// output comes back as binary frames. The socket closes when the shell exits.
+pub async fn session_shell(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Extension(auth): Extension<AuthContext>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let session_id = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::BadRequest("Invalid session ID format".to_string()))?;
+
+    let session = Session::find_by_id(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session: {}", e)))?
+        .ok_or(ApiError::NotFound("Session not found".to_string()))?;
+
+    // A shell reaches everything in the container, so owning the session isn't enough
+    check_api_permission(&auth, &state, &permissions::SESSION_EXEC_INTERACTIVE, Some(&session.workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    if !matches!(session.state, SessionState::Ready | SessionState::Busy) {
+        return Err(ApiError::Conflict("Only a READY or BUSY session has a shell".to_string()));
+    }
+
+    // The container lives on the node whose operator created it, which starts the shell
+    // before the client's socket is upgraded so failures are still reported as HTTP errors
+    let node_name = Session::container_node(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session node: {}", e)))?;
+    let shell = NodeClient::for_node(&state, node_name.as_deref())
+        .await?
+        .open_shell(session_id)
+        .await?;
+
+    if let Err(e) = state
+        .record_audit_event("exec-interactive", "session", Some(session_id), &auth.principal, serde_json::json!({}))
+        .await
+    {
+        warn!("Failed to record audit event for session {} shell: {}", session_id, e);
+    }
+
+    info!("Opened interactive shell in session {} for {}", session_id, auth.principal.name());
+
+    Ok(ws.on_upgrade(move |socket| relay_shell(socket, shell, session_id)))
+}
+
+/// Relay frames between the client and the operator's shell socket until either side closes
+async fn relay_shell(socket: WebSocket, shell: NodeShell, session_id: Uuid) {
+    let (mut client_tx, mut client_rx) = socket.split();
+    let (mut node_tx, mut node_rx) = shell.split();
+
+    loop {
+        tokio::select! {
+            message = node_rx.next() => {
+                let message = match message {
+                    Some(Ok(NodeMessage::Binary(bytes))) => Message::Binary(bytes),
+                    Some(Ok(NodeMessage::Text(text))) => Message::Text(text.as_str().into()),
+                    Some(Ok(NodeMessage::Ping(_) | NodeMessage::Pong(_) | NodeMessage::Frame(_))) => continue,
+                    Some(Ok(NodeMessage::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        warn!("Shell connection to the operator for session {} failed: {}", session_id, e);
+                        break;
+                    }
+                };
+                if client_tx.send(message).await.is_err() {
+                    break;
+                }
+            },
+            message = client_rx.next() => {
+                let message = match message {
+                    Some(Ok(Message::Text(text))) => NodeMessage::text(text.as_str()),
+                    Some(Ok(Message::Binary(bytes))) => NodeMessage::Binary(bytes),
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                };
+                if node_tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = node_tx.send(NodeMessage::Close(None)).await;
+    let _ = client_tx.send(Message::Close(None)).await;
+    info!("Closed interactive shell in session {}", session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use futures::{SinkExt, StreamExt};
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::{client::IntoClientRequest, handshake::client::Request, Error, Message};
+    use uuid::Uuid;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    fn shell_request(url: &str, token: &str) -> Request {
+        let mut request = url.into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn shell_needs_a_running_session() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session("admin").await;
+
+        // A new session is still in INIT
+        let url = format!("{}/api/v0/sessions/{}/shell", app.listen().await, session_id).replacen("http", "ws", 1);
+        match tokio_tungstenite::connect_async(shell_request(&url, &app.admin_token())).await {
+            Err(Error::Http(response)) => assert_eq!(response.status(), StatusCode::CONFLICT),
+            other => panic!("expected a 409, got {:?}", other.map(|(_, response)| response.status())),
+        }
+    }
+
+    /// Runs against a full deployment: its operator starts the session container and the shell
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, RAWORC_OPERATOR_URL and RAWORC_NODE_API_KEY of a running deployment with Docker and the host image"]
+    async fn shell_runs_commands_in_the_session_container() {
+        let app = TestApp::new().await;
+        let token = app.admin_token();
+        let request = json!({"name": unique("shell"), "starting_prompt": "test"});
+        let session = body_json(app.request(Method::POST, "/api/v0/sessions", &token, Some(request)).await).await;
+        let session_id = Uuid::parse_str(session["id"].as_str().unwrap()).unwrap();
+
+        let uri = format!("/api/v0/sessions/{}", session_id);
+        let mut ready = false;
+        for _ in 0..120 {
+            let session = body_json(app.request(Method::GET, &uri, &token, None).await).await;
+            if session["state"] == "READY" {
+                ready = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        assert!(ready, "the session's container never started");
+
+        let url = format!("{}{}/shell", app.listen().await, uri).replacen("http", "ws", 1);
+        let (mut shell, _) = tokio_tungstenite::connect_async(shell_request(&url, &token)).await.unwrap();
+
+        shell.send(Message::text("echo $((40 + 2))\n")).await.unwrap();
+        let mut output = String::new();
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while let Some(Ok(message)) = shell.next().await {
+                output.push_str(&String::from_utf8_lossy(&message.into_data()));
+                if output.contains("42") {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("the shell answered");
+        assert!(output.contains("42"));
+
+        shell.send(Message::text("exit\n")).await.unwrap();
+        app.request(Method::DELETE, &uri, &token, None).await;
+    }
+}
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::shared::models::{AppState, RecordUsageRequest, Session, SessionUsage};
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::handlers::sessions::principal_name;
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::rbac_enforcement::permissions;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionUsageResponse {
+    pub session_id: String,
+    /// Number of LLM turns reported
+    pub turns: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+    /// Estimated cost in US dollars, summed over the turns that reported one
+    pub cost_usd: Option<f64>,
+    pub last_recorded_at: Option<String>,
+}
+
+async fn usage_response(state: &AppState, session_id: Uuid) -> Result<SessionUsageResponse, ApiError> {
+    let totals = SessionUsage::for_session(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch usage: {}", e)))?;
+
+    Ok(SessionUsageResponse {
+        session_id: session_id.to_string(),
+        turns: totals.turns,
+        prompt_tokens: totals.prompt_tokens,
+        completion_tokens: totals.completion_tokens,
+        total_tokens: totals.prompt_tokens + totals.completion_tokens,
+        cost_usd: totals.cost_usd,
+        last_recorded_at: totals.last_recorded_at.map(|dt| dt.to_rfc3339()),
+    })
+}
+
+async fn find_session(state: &AppState, session_id: Uuid) -> Result<Session, ApiError> {
+    Session::find_by_id(&state.db, session_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch session: {}", e)))?
+        .ok_or(ApiError::NotFound("Session not found".to_string()))
+}
+
+/// Record one turn's token usage. Only the session's host agent, authenticated with the
+/// session's host token, reports usage.
+pub async fn record_usage(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+    Extension(auth): Extension<AuthContext>,
+    Json(req): Json<RecordUsageRequest>,
+) -> ApiResult<Json<SessionUsageResponse>> {
+    req.validate()?;
+
+    find_session(&state, session_id).await?;
+
+    if !auth.is_host_of(session_id) {
+        return Err(ApiError::Forbidden("Usage can only be recorded with the session's host token".to_string()));
+    }
+
+    let agent_id = req.agent_id.unwrap_or_default();
+    SessionUsage::record(&state.db, session_id, req)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|db| db.constraint()) {
+            Some("session_usage_agent_id_fkey") => ApiError::BadRequest(format!("Agent {} not found", agent_id)),
+            _ => ApiError::Internal(anyhow::anyhow!("Failed to record usage: {}", e)),
+        })?;
+
+    Ok(Json(usage_response(&state, session_id).await?))
+}
+
+pub async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+    Extension(auth): Extension<AuthContext>,
+) -> ApiResult<Json<SessionUsageResponse>> {
+    let session = find_session(&state, session_id).await?;
+
+    if session.created_by != principal_name(&auth) {
+        let is_admin = crate::server::auth::check_permission(
+            &auth.principal,
+            &state,
+            &permissions::SESSION_GET_ALL.context(),
+        )
+        .await
+        .unwrap_or(false);
+
+        if !is_admin {
+            return Err(ApiError::Forbidden("Cannot access other users' sessions".to_string()));
+        }
+    }
+
+    Ok(Json(usage_response(&state, session_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn only_the_host_records_usage() {
+        let app = TestApp::new().await;
+        let owner = unique("owner");
+        let session_id = app.create_session(&owner).await;
+        let uri = format!("/api/v0/sessions/{}/usage", session_id);
+        let turn = json!({"prompt_tokens": 100, "completion_tokens": 20, "cost_usd": 0.5});
+
+        let response = app.request(Method::POST, &uri, &app.user_token(&owner), Some(turn.clone())).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let host = app.host_token(session_id).await;
+        let response = app.request(Method::POST, &uri, &host, Some(turn.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::POST, &uri, &host, Some(turn)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let usage = body_json(app.request(Method::GET, &uri, &app.user_token(&owner), None).await).await;
+        assert_eq!(usage["turns"], 2);
+        assert_eq!(usage["prompt_tokens"], 200);
+        assert_eq!(usage["completion_tokens"], 40);
+        assert_eq!(usage["total_tokens"], 240);
+        assert_eq!(usage["cost_usd"], 1.0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn usage_for_an_unknown_agent_is_a_bad_request() {
+        let app = TestApp::new().await;
+        let session_id = app.create_session(&unique("owner")).await;
+        let host = app.host_token(session_id).await;
+
+        let response = app
+            .request(
+                Method::POST,
+                &format!("/api/v0/sessions/{}/usage", session_id),
+                &host,
+                Some(json!({"prompt_tokens": 1, "completion_tokens": 1, "agent_id": Uuid::new_v4()})),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, State},
+    Extension,
+    Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::shared::models::validation::is_valid_env_var_name;
+use crate::shared::models::{normalize_workspace_name, AppState, UpdateWorkspaceSettingsRequest, WorkspaceSettings, WorkspaceTier, DEFAULT_WAITING_TIMEOUT_SECONDS};
+use crate::server::rest::error::{ApiError, ApiResult};
+use crate::server::rest::middleware::AuthContext;
+use crate::server::rest::rbac_enforcement::{check_api_permission, permissions};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceSettingsResponse {
+    pub workspace: String,
+    /// Configured default, or null when the workspace uses the global default
+    pub default_waiting_timeout_seconds: Option<i32>,
+    /// Timeout new sessions in this workspace receive when they don't set one
+    pub effective_waiting_timeout_seconds: i32,
+    /// Variables session containers in this workspace may not receive, beyond the built-in and global lists
+    pub denied_env_vars: Vec<String>,
+    /// Tier picking the default container resources, or null for the global limits
+    pub tier: Option<WorkspaceTier>,
+    pub updated_at: Option<String>,
+}
+
+impl WorkspaceSettingsResponse {
+    fn new(workspace: &str, settings: Option<WorkspaceSettings>) -> Self {
+        let default_waiting_timeout_seconds = settings
+            .as_ref()
+            .and_then(|s| s.default_waiting_timeout_seconds);
+
+        Self {
+            workspace: workspace.to_string(),
+            default_waiting_timeout_seconds,
+            effective_waiting_timeout_seconds: default_waiting_timeout_seconds
+                .unwrap_or(DEFAULT_WAITING_TIMEOUT_SECONDS),
+            denied_env_vars: settings
+                .as_ref()
+                .map(|s| s.denied_env_vars.clone())
+                .unwrap_or_default(),
+            tier: settings.as_ref().and_then(|s| s.tier),
+            updated_at: settings.map(|s| s.updated_at.to_rfc3339()),
+        }
+    }
+}
+
+/// Validate a workspace name from a request and return its normalized (lower-case) form
+pub(crate) fn validate_workspace_name(workspace: &str) -> Result<String, ApiError> {
+    normalize_workspace_name(workspace).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "Invalid workspace name '{}': must match [a-z0-9][a-z0-9-]{{0,62}}",
+            workspace
+        ))
+    })
+}
+
+pub async fn get_workspace_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(workspace): Path<String>,
+) -> ApiResult<Json<WorkspaceSettingsResponse>> {
+    let workspace = validate_workspace_name(&workspace)?;
+
+    check_api_permission(&auth, &state, &permissions::WORKSPACE_GET, Some(&workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    let settings = WorkspaceSettings::find(&*state.db, &workspace)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to fetch workspace settings: {}", e)))?;
+
+    Ok(Json(WorkspaceSettingsResponse::new(&workspace, settings)))
+}
+
+pub async fn update_workspace_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(workspace): Path<String>,
+    Json(req): Json<UpdateWorkspaceSettingsRequest>,
+) -> ApiResult<Json<WorkspaceSettingsResponse>> {
+    let workspace = validate_workspace_name(&workspace)?;
+
+    check_api_permission(&auth, &state, &permissions::WORKSPACE_UPDATE, Some(&workspace))
+        .await
+        .map_err(|e| match e {
+            axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Insufficient permissions".to_string()),
+            _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+        })?;
+
+    if let Some(timeout) = req.default_waiting_timeout_seconds {
+        if timeout <= 0 {
+            return Err(ApiError::BadRequest("default_waiting_timeout_seconds must be positive".to_string()));
+        }
+    }
+
+    if let Some(name) = req.denied_env_vars.iter().flatten().find(|name| !is_valid_env_var_name(name)) {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid denied_env_vars entry '{}': must be an upper-case variable name",
+            name
+        )));
+    }
+
+    if let Some(tier) = req.tier {
+        let current = WorkspaceSettings::tier_for(&*state.db, &workspace)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to load workspace settings: {}", e)))?;
+        if current != Some(tier) {
+            check_api_permission(&auth, &state, &permissions::WORKSPACE_SET_TIER, None)
+                .await
+                .map_err(|e| match e {
+                    axum::http::StatusCode::FORBIDDEN => ApiError::Forbidden("Changing a workspace's tier requires the global workspaces/set-tier permission".to_string()),
+                    _ => ApiError::Internal(anyhow::anyhow!("Permission check failed")),
+                })?;
+        }
+    }
+
+    let settings = WorkspaceSettings::upsert(&state.db, &workspace, req)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to update workspace settings: {}", e)))?;
+
+    Ok(Json(WorkspaceSettingsResponse::new(&workspace, Some(settings))))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn changing_tier_needs_global_permission() {
+        let app = TestApp::new().await;
+        let workspace = unique("ws");
+        let owner = unique("owner");
+        app.grant(&owner, Some(&workspace), "workspaces", &["get", "update"]).await;
+        let uri = format!("/api/v0/workspaces/{}/settings", workspace);
+
+        let response = app.request(Method::PUT, &uri, &app.user_token(&owner), Some(json!({"tier": "enterprise"}))).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.request(Method::PUT, &uri, &app.admin_token(), Some(json!({"tier": "enterprise"}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Other settings stay editable, and resending the current tier isn't a change
+        let body = json!({"default_waiting_timeout_seconds": 60, "tier": "enterprise"});
+        let response = app.request(Method::PUT, &uri, &app.user_token(&owner), Some(body)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::PUT, &uri, &app.user_token(&owner), Some(json!({}))).await;
+        assert_eq!(body_json(response).await["tier"], "enterprise");
+    }
+}
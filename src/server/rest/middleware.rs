@@ -2,18 +2,83 @@ use axum::{
     extract::{Request, State},
     http::{header, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use crate::server::auth::decode_jwt;
-use crate::shared::models::AppState;
+use crate::server::rest::error::ApiError;
+use crate::server::rest::tls::ClientCertificate;
+use crate::shared::host_token;
+use crate::shared::models::{AppState, Session};
 use crate::server::rbac::{AuthPrincipal, RbacClaims, Subject, SubjectType};
 use std::sync::Arc;
 use tracing::info;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AuthContext {
     pub principal: AuthPrincipal,
     pub claims: RbacClaims,
+    /// Set when the request carries a session's host token; such requests act as the
+    /// session's creator but only reach that session's endpoints
+    pub host_session: Option<Uuid>,
+}
+
+impl AuthContext {
+    /// Whether the request comes from the host agent of `session_id`
+    pub fn is_host_of(&self, session_id: Uuid) -> bool {
+        self.host_session == Some(session_id)
+    }
+}
+
+/// Claims standing in for a token when a client certificate authenticates the request
+pub fn certificate_claims(cert: &ClientCertificate) -> RbacClaims {
+    RbacClaims {
+        sub: cert.common_name.clone(),
+        sub_type: SubjectType::Subject,
+        workspace: None,
+        exp: 0,
+        iat: 0,
+        iss: "tls-client-certificate".to_string(),
+        aud: None,
+    }
+}
+
+/// Claims standing in for a token when a session's host token authenticates the request
+fn host_claims(session: &Session) -> RbacClaims {
+    RbacClaims {
+        sub: session.created_by.clone(),
+        sub_type: SubjectType::Subject,
+        workspace: None,
+        exp: 0,
+        iat: 0,
+        iss: "raworc-host".to_string(),
+        aud: None,
+    }
+}
+
+/// Whether `path` is the session's own resource or one below it. The middleware runs inside
+/// the `/api/v0` nest, so the prefix is usually already stripped.
+fn is_session_path(path: &str, session_id: Uuid) -> bool {
+    let path = path.strip_prefix("/api/v0").unwrap_or(path);
+    path.strip_prefix("/sessions/")
+        .map(|rest| rest.split('/').next().unwrap_or_default())
+        .and_then(|id| Uuid::parse_str(id).ok())
+        == Some(session_id)
+}
+
+/// Check a host token against the hash stored for its session, returning the session
+async fn authenticate_host(state: &AppState, session_id: Uuid, token: &str) -> Result<Session, StatusCode> {
+    let stored = Session::host_token_hash(&state.db, session_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    if stored != host_token::hash(token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Session::find_by_id(&state.db, session_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)
 }
 
 pub async fn auth_middleware(
@@ -35,16 +100,37 @@ pub async fn auth_middleware(
     let auth_header = request
         .headers()
         .get(header::AUTHORIZATION)
-        .and_then(|h| h.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .and_then(|h| h.to_str().ok());
 
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let mut host_session = None;
+    let claims = match auth_header {
+        Some(auth_header) => {
+            let token = auth_header
+                .strip_prefix("Bearer ")
+                .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    // Decode and validate JWT
-    let claims = decode_jwt(token, &state.jwt_secret)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+            match host_token::session_of(token) {
+                Some(session_id) => {
+                    let session = authenticate_host(&state, session_id, token).await?;
+                    if !is_session_path(path, session_id) {
+                        return Ok(ApiError::Forbidden("Host tokens can only access their own session".to_string()).into_response());
+                    }
+                    host_session = Some(session_id);
+                    host_claims(&session)
+                }
+                // Decode and validate JWT
+                None => decode_jwt(token, &state.jwt_keys)
+                    .map_err(|_| StatusCode::UNAUTHORIZED)?,
+            }
+        }
+        // Without a token, a client certificate verified during the TLS handshake
+        // authenticates its common name as a subject
+        None => request
+            .extensions()
+            .get::<ClientCertificate>()
+            .map(certificate_claims)
+            .ok_or(StatusCode::UNAUTHORIZED)?,
+    };
 
     // Get principal from claims
     let principal = match claims.sub_type {
@@ -54,6 +140,11 @@ pub async fn auth_middleware(
                 .await
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
                 .ok_or(StatusCode::UNAUTHORIZED)?;
+
+            // The account is loaded on every request, so disabling it revokes outstanding tokens immediately
+            if !service_account.active {
+                return Ok(ApiError::Forbidden("Account disabled".to_string()).into_response());
+            }
             AuthPrincipal::ServiceAccount(service_account)
         }
         SubjectType::Subject => AuthPrincipal::Subject(Subject {
@@ -65,6 +156,7 @@ pub async fn auth_middleware(
     let auth_context = AuthContext {
         principal: principal.clone(),
         claims: claims.clone(),
+        host_session,
     };
     request.extensions_mut().insert(auth_context);
 
@@ -85,4 +177,49 @@ pub async fn auth_middleware(
     );
 
     Ok(next.run(request).await)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+    use axum::http::Method;
+    use serde_json::json;
+
+    #[test]
+    fn session_paths_match_the_session_and_its_subresources() {
+        let session_id = Uuid::new_v4();
+
+        assert!(is_session_path(&format!("/sessions/{}", session_id), session_id));
+        assert!(is_session_path(&format!("/sessions/{}/messages", session_id), session_id));
+        assert!(is_session_path(&format!("/api/v0/sessions/{}/usage", session_id), session_id));
+        assert!(!is_session_path(&format!("/sessions/{}", Uuid::new_v4()), session_id));
+        assert!(!is_session_path("/sessions", session_id));
+        assert!(!is_session_path(&format!("/agents/{}", session_id), session_id));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn disabling_an_account_rejects_its_existing_tokens() {
+        let app = TestApp::new().await;
+        let user = unique("account");
+        let account = json!({"user": user, "pass": "a-password-for-tests"});
+        let response = app.request(Method::POST, "/api/v0/service-accounts", &app.admin_token(), Some(account)).await;
+        assert!(response.status().is_success(), "{}", response.status());
+
+        let response = app
+            .request(Method::POST, "/api/v0/auth/internal", "", Some(json!({"user": user, "pass": "a-password-for-tests"})))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let token = body_json(response).await["token"].as_str().unwrap().to_string();
+        assert_eq!(app.request(Method::GET, "/api/v0/auth/me", &token, None).await.status(), StatusCode::OK);
+
+        let uri = format!("/api/v0/service-accounts/{}", user);
+        let response = app.request(Method::PUT, &uri, &app.admin_token(), Some(json!({"active": false}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The token is still unexpired and correctly signed
+        let response = app.request(Method::GET, "/api/v0/auth/me", &token, None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(body_json(response).await["error"]["message"], "Account disabled");
+    }
+}
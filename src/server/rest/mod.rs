@@ -1,11 +1,19 @@
 pub mod auth;
 pub mod error;
+pub mod etag;
 pub mod handlers;
 pub mod logging_middleware;
 pub mod middleware;
+pub mod node_client;
+pub mod read_only_middleware;
 pub mod openapi;
+pub mod rate_limit_middleware;
 pub mod rbac_enforcement;
 pub mod routes;
 pub mod server;
+pub mod tls;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod version_middleware;
 
 pub use routes::create_router;
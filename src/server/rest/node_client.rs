@@ -0,0 +1,240 @@
+//! Client for operators' node API. The server has no Docker access of its own; container and
+//! image administration, logs, shells and files go to the operator on the node that runs the container.
+
+use axum::body::{Body, Bytes};
+use futures::stream;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::client::IntoClientRequest, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+use crate::operator::{LocalImage, PullStarted, SessionContainer};
+use crate::server::rest::error::ApiError;
+use crate::shared::models::node::node_api_url;
+use crate::shared::models::AppState;
+
+/// Shell connection to the operator, relayed frame by frame to the client's WebSocket
+pub type NodeShell = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub struct NodeClient {
+    base_url: String,
+    api_key: String,
+    /// For error messages
+    node: String,
+}
+
+fn http() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+impl NodeClient {
+    /// Client for the operator on `node`, or the operator started without a node name when None
+    pub async fn for_node(state: &AppState, node: Option<&str>) -> Result<Self, ApiError> {
+        let api_key = state.config.node_api_key.clone().ok_or_else(|| {
+            ApiError::ServiceUnavailable("Docker access is disabled: RAWORC_NODE_API_KEY is not set".to_string())
+        })?;
+
+        let base_url = match node {
+            Some(node) => node_api_url(&state.db, node)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!("Failed to look up node {}: {}", node, e)))?
+                .ok_or_else(|| ApiError::ServiceUnavailable(format!("The operator on node '{}' has not registered a RAWORC_NODE_URL", node)))?,
+            None => state.config.server.operator_url.clone().ok_or_else(|| {
+                ApiError::ServiceUnavailable("RAWORC_OPERATOR_URL is not set, so the operator without a node name can't be reached".to_string())
+            })?,
+        };
+
+        Ok(Self {
+            base_url,
+            api_key,
+            node: node.unwrap_or("(unnamed)").to_string(),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        http()
+            .request(method, format!("{}/node{}", self.base_url, path))
+            .bearer_auth(&self.api_key)
+    }
+
+    /// Send a request, turning transport failures and error statuses into API errors.
+    /// The operator's 404s mean the container or image is missing and are passed on.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, ApiError> {
+        let response = request.send().await.map_err(|e| {
+            ApiError::ServiceUnavailable(format!("The operator on node {} is unreachable: {}", self.node, e))
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let message = response.text().await.unwrap_or_default();
+        Err(match status {
+            StatusCode::NOT_FOUND => ApiError::NotFound(message),
+            _ => ApiError::Internal(anyhow::anyhow!("Operator on node {} answered {}: {}", self.node, status, message)),
+        })
+    }
+
+    async fn json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiError> {
+        self.send(request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Unexpected answer from the operator on node {}: {}", self.node, e)))
+    }
+
+    pub async fn list_containers(&self, workspace: Option<&str>) -> Result<Vec<SessionContainer>, ApiError> {
+        let mut request = self.request(Method::GET, "/containers");
+        if let Some(workspace) = workspace {
+            request = request.query(&[("workspace", workspace)]);
+        }
+        self.json(request).await
+    }
+
+    pub async fn remove_container(&self, id: &str) -> Result<(), ApiError> {
+        self.send(self.request(Method::DELETE, &format!("/containers/{}", id))).await?;
+        Ok(())
+    }
+
+    pub async fn list_images(&self) -> Result<Vec<LocalImage>, ApiError> {
+        self.json(self.request(Method::GET, "/images")).await
+    }
+
+    /// Start pulling `image`, or the session image when None; returns the reference being pulled
+    pub async fn pull_image(&self, image: Option<&str>) -> Result<String, ApiError> {
+        let request = self.request(Method::POST, "/images/pull").json(&serde_json::json!({ "image": image }));
+        let started: PullStarted = self.json(request).await?;
+        Ok(started.image)
+    }
+
+    /// Output of the session's container, passed through chunk by chunk as the operator sends
+    /// it; NotFound when the container is gone
+    pub async fn container_logs(&self, session_id: Uuid) -> Result<Body, ApiError> {
+        let response = self.send(self.request(Method::GET, &format!("/sessions/{}/logs", session_id))).await?;
+        let chunks = stream::try_unfold(response, |mut response| async move {
+            Ok::<_, reqwest::Error>(response.chunk().await?.map(|chunk| (chunk, response)))
+        });
+        Ok(Body::from_stream(chunks))
+    }
+
+    /// Contents of the file at `path` in the session's container; NotFound when the container or
+    /// the file is missing
+    pub async fn read_file(&self, session_id: Uuid, path: &str) -> Result<Bytes, ApiError> {
+        let request = self.request(Method::GET, &format!("/sessions/{}/files", session_id)).query(&[("path", path)]);
+        self.send(request)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| ApiError::ServiceUnavailable(format!("Failed to read the file from node {}: {}", self.node, e)))
+    }
+
+    /// Create or replace the file at `path` in the session's container
+    pub async fn write_file(&self, session_id: Uuid, path: &str, contents: Bytes) -> Result<(), ApiError> {
+        let request = self
+            .request(Method::PUT, &format!("/sessions/{}/files", session_id))
+            .query(&[("path", path)])
+            .body(contents);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Open a shell in the session's container; the operator starts it before accepting the socket
+    pub async fn open_shell(&self, session_id: Uuid) -> Result<NodeShell, ApiError> {
+        let url = format!("{}/node/sessions/{}/shell", self.base_url, session_id)
+            .replacen("http", "ws", 1);
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Invalid node URL for node {}: {}", self.node, e)))?;
+        let authorization = format!("Bearer {}", self.api_key)
+            .parse()
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("RAWORC_NODE_API_KEY is not a valid header value")))?;
+        request.headers_mut().insert(reqwest::header::AUTHORIZATION, authorization);
+
+        match tokio_tungstenite::connect_async(request).await {
+            Ok((socket, _)) => Ok(socket),
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) if response.status() == StatusCode::NOT_FOUND => {
+                Err(ApiError::NotFound("Session has no container".to_string()))
+            }
+            Err(e) => Err(ApiError::ServiceUnavailable(format!("Failed to open a shell on node {}: {}", self.node, e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::HeaderMap, routing::{get, post}, Json, Router};
+    use axum::http::{Method, StatusCode};
+
+    use crate::server::rest::test_support::{body_json, serve, unique, TestApp};
+    use crate::shared::models::node::register_node;
+
+    const KEY: &str = "node-api-key-that-is-long-enough-for-tests";
+
+    /// Stand-in operator listing one image to callers presenting the key
+    async fn fake_operator() -> String {
+        let images = |headers: HeaderMap| async move {
+            if headers.get("authorization").and_then(|value| value.to_str().ok()) != Some(&format!("Bearer {}", KEY)) {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            Ok(Json(serde_json::json!([
+                {"id": "sha256:abc", "tags": ["raworc-host:latest"], "size": 1024, "created": 0, "session_image": true}
+            ])))
+        };
+        let pull = |Json(body): Json<serde_json::Value>| async move {
+            (StatusCode::ACCEPTED, Json(serde_json::json!({ "image": body["image"].as_str().unwrap_or("raworc-host:latest") })))
+        };
+        serve(
+            Router::new()
+                .route("/node/images", get(images))
+                .route("/node/images/pull", post(pull)),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn docker_endpoints_go_to_the_operator_of_the_node() {
+        let operator_url = fake_operator().await;
+        let app = TestApp::with_config(|config| {
+            config.node_api_key = Some(KEY.to_string());
+            config.server.operator_url = Some(operator_url.clone());
+        })
+        .await;
+        let token = app.admin_token();
+
+        let response = app.request(Method::GET, "/api/v0/admin/images", &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await[0]["tags"][0], "raworc-host:latest");
+
+        let node = unique("node");
+        register_node(&app.state.db, &node, Some(&operator_url)).await.unwrap();
+        let response = app.request(Method::GET, &format!("/api/v0/admin/images?node={}", node), &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Registered without a URL, so the server can't reach it
+        let unreachable = unique("node");
+        register_node(&app.state.db, &unreachable, None).await.unwrap();
+        let response = app.request(Method::GET, &format!("/api/v0/admin/images?node={}", unreachable), &token, None).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = app.request(Method::GET, "/api/v0/admin/images?node=Not%20A%20Node", &token, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Pulls are only started, so the server answers before the operator has the image
+        let response = app.request(Method::POST, "/api/v0/admin/images/pull", &token, Some(serde_json::json!({}))).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(body_json(response).await["image"], "raworc-host:latest");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn docker_endpoints_are_unavailable_without_the_node_api_key() {
+        let app = TestApp::with_config(|config| config.node_api_key = None).await;
+
+        let response = app.request(Method::GET, "/api/v0/admin/containers", &app.admin_token(), None).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
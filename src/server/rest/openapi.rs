@@ -4,17 +4,25 @@ use utoipa::{
 };
 
 use crate::server::rest::{
-    auth::{LoginRequest, LoginResponse, ExternalLoginRequest},
+    auth::{LoginRequest, LoginResponse, ExternalLoginRequest, MeResponse, MeRoleBinding},
     handlers::{
-        service_accounts::{CreateServiceAccountRequest, ServiceAccountResponse, UpdatePasswordRequest, UpdateServiceAccountRequest},
+        service_accounts::{CreateServiceAccountRequest, ServiceAccountResponse, ServiceAccountRoleBindingResponse, UpdatePasswordRequest, UpdateServiceAccountRequest},
         roles::{CreateRoleRequest, RoleResponse, RuleRequest, RuleResponse},
-        role_bindings::{CreateRoleBindingRequest, RoleBindingResponse},
-        agents::AgentResponse,
-        sessions::{SessionResponse, SessionAgentInfo},
+        role_bindings::{BulkRoleBindingResult, CreateRoleBindingRequest, RoleBindingResponse},
+        agents::{AgentResponse, AgentRevisionResponse, AgentTestResponse},
+        sessions::{SessionResponse, SessionAgentInfo, SessionTreeNode, SessionConfigResponse, MergedAgentConfig, SessionTimelineEntry, SessionStatusResponse, SessionTaskResponse, SessionExportLine, SessionExportHeader, ExportedMessage},
+        workspaces::WorkspaceSettingsResponse,
+        containers::{ContainerInfo, ContainerReport, GhostSession, ReconcileResponse},
+        images::{ImageInfo, PullImageRequest, PullImageResponse},
+        maintenance::{ReadOnlyResponse, SetReadOnlyRequest, ReapIdleResponse},
+        secrets::SecretResponse,
+        messages::{ClearMessagesResponse, MessageCountResponse},
+        usage::SessionUsageResponse,
     },
     error::ErrorResponse,
+    routes::VersionResponse,
 };
-use crate::shared::models::{CreateAgentRequest, UpdateAgentRequest, CreateSessionRequest, RemixSessionRequest, UpdateSessionStateRequest, UpdateSessionRequest, SessionState, MessageRole, CreateMessageRequest, MessageResponse};
+use crate::shared::models::{CreateAgentRequest, UpdateAgentRequest, TestAgentRequest, CreateSessionRequest, RemixSessionRequest, UpdateSessionStateRequest, UpdateSessionRequest, TransferSessionRequest, AttachSessionAgentRequest, SessionState, TerminationCause, MessageRole, MessageOrder, CreateMessageRequest, CreateSystemMessageRequest, MessageResponse, UpdateWorkspaceSettingsRequest, WorkspaceTier, CreateSecretRequest, UpdateSecretRequest, RecordUsageRequest};
 use crate::server::rbac::SubjectType;
 
 #[derive(OpenApi)]
@@ -27,6 +35,7 @@ use crate::server::rbac::SubjectType;
         crate::server::rest::openapi::me,
         crate::server::rest::openapi::list_service_accounts,
         crate::server::rest::openapi::get_service_account,
+        crate::server::rest::openapi::get_service_account_role_bindings,
         crate::server::rest::openapi::create_service_account,
         crate::server::rest::openapi::update_service_account,
         crate::server::rest::openapi::delete_service_account,
@@ -38,27 +47,80 @@ use crate::server::rbac::SubjectType;
         crate::server::rest::openapi::list_role_bindings,
         crate::server::rest::openapi::get_role_binding,
         crate::server::rest::openapi::create_role_binding,
+        crate::server::rest::openapi::create_role_bindings_bulk,
         crate::server::rest::openapi::delete_role_binding,
+        crate::server::rest::openapi::delete_role_binding_by_id,
         crate::server::rest::openapi::list_agents,
         crate::server::rest::openapi::get_agent,
+        crate::server::rest::openapi::head_agent,
         crate::server::rest::openapi::create_agent,
         crate::server::rest::openapi::update_agent,
         crate::server::rest::openapi::delete_agent,
+        crate::server::rest::openapi::list_agent_versions,
+        crate::server::rest::openapi::restore_agent_version,
+        crate::server::rest::openapi::test_agent,
+        crate::server::rest::openapi::list_secrets,
+        crate::server::rest::openapi::get_secret,
+        crate::server::rest::openapi::create_secret,
+        crate::server::rest::openapi::update_secret,
+        crate::server::rest::openapi::delete_secret,
+        crate::server::rest::openapi::get_workspace_settings,
+        crate::server::rest::openapi::update_workspace_settings,
+        crate::server::rest::openapi::list_containers,
+        crate::server::rest::openapi::reconcile_containers,
+        crate::server::rest::openapi::list_images,
+        crate::server::rest::openapi::pull_image,
+        crate::server::rest::openapi::get_read_only,
+        crate::server::rest::openapi::set_read_only,
+        crate::server::rest::openapi::reap_idle_sessions,
         crate::server::rest::openapi::list_sessions,
+        crate::server::rest::openapi::list_my_sessions,
         crate::server::rest::openapi::get_session,
+        crate::server::rest::openapi::head_session,
+        crate::server::rest::openapi::get_session_status,
+        crate::server::rest::openapi::get_session_tree,
         crate::server::rest::openapi::create_session,
         crate::server::rest::openapi::update_session,
         crate::server::rest::openapi::update_session_state,
+        crate::server::rest::openapi::heartbeat_session,
+        crate::server::rest::openapi::cancel_session,
+        crate::server::rest::openapi::pause_session,
+        crate::server::rest::openapi::resume_session,
+        crate::server::rest::openapi::list_session_agents,
+        crate::server::rest::openapi::attach_session_agent,
+        crate::server::rest::openapi::detach_session_agent,
+        crate::server::rest::openapi::get_session_config,
+        crate::server::rest::openapi::get_session_timeline,
+        crate::server::rest::openapi::list_session_tasks,
+        crate::server::rest::openapi::export_session,
+        crate::server::rest::openapi::get_session_logs,
+        crate::server::rest::openapi::import_session,
         crate::server::rest::openapi::remix_session,
+        crate::server::rest::openapi::session_shell,
+        crate::server::rest::openapi::read_session_file,
+        crate::server::rest::openapi::write_session_file,
+        crate::server::rest::openapi::transfer_session,
         crate::server::rest::openapi::delete_session,
+        crate::server::rest::openapi::list_messages,
+        crate::server::rest::openapi::create_message,
+        crate::server::rest::openapi::create_messages_batch,
+        crate::server::rest::openapi::create_system_message,
+        crate::server::rest::openapi::get_latest_message,
+        crate::server::rest::openapi::get_message_count,
+        crate::server::rest::openapi::clear_messages,
+        crate::server::rest::openapi::get_usage,
+        crate::server::rest::openapi::record_usage,
     ),
     components(
         schemas(
             LoginRequest,
             LoginResponse,
             ExternalLoginRequest,
+            MeResponse,
+            MeRoleBinding,
             CreateServiceAccountRequest,
             ServiceAccountResponse,
+            ServiceAccountRoleBindingResponse,
             UpdatePasswordRequest,
             UpdateServiceAccountRequest,
             CreateRoleRequest,
@@ -67,22 +129,61 @@ use crate::server::rbac::SubjectType;
             RuleResponse,
             CreateRoleBindingRequest,
             RoleBindingResponse,
+            BulkRoleBindingResult,
             SubjectType,
             ErrorResponse,
             crate::server::rest::error::ErrorDetails,
+            VersionResponse,
             AgentResponse,
+            AgentRevisionResponse,
+            AgentTestResponse,
             CreateAgentRequest,
             UpdateAgentRequest,
+            TestAgentRequest,
+            SecretResponse,
+            CreateSecretRequest,
+            UpdateSecretRequest,
+            WorkspaceSettingsResponse,
+            UpdateWorkspaceSettingsRequest,
+            WorkspaceTier,
+            ContainerInfo,
+            ContainerReport,
+            GhostSession,
+            ReconcileResponse,
+            ImageInfo,
+            PullImageRequest,
+            PullImageResponse,
+            ReadOnlyResponse,
+            SetReadOnlyRequest,
+            ReapIdleResponse,
             SessionResponse,
             SessionAgentInfo,
+            SessionTreeNode,
+            SessionConfigResponse,
+            MergedAgentConfig,
+            SessionTimelineEntry,
+            SessionTaskResponse,
+            SessionExportLine,
+            SessionExportHeader,
+            ExportedMessage,
+            SessionStatusResponse,
             CreateSessionRequest,
             RemixSessionRequest,
             UpdateSessionStateRequest,
             UpdateSessionRequest,
+            TransferSessionRequest,
+            AttachSessionAgentRequest,
             SessionState,
+            TerminationCause,
             MessageRole,
+            MessageOrder,
             CreateMessageRequest,
+            CreateSystemMessageRequest,
             MessageResponse,
+            MessageCountResponse,
+            ClearMessagesResponse,
+            SessionUsageResponse,
+            RecordUsageRequest,
         )
     ),
     modifiers(&SecurityAddon),
@@ -93,12 +194,15 @@ use crate::server::rbac::SubjectType;
         (name = "Roles", description = "Role management"),
         (name = "Role Bindings", description = "Role binding management"),
         (name = "Agents", description = "Agent management"),
+        (name = "Secrets", description = "Workspace secrets injected into session containers"),
+        (name = "Workspaces", description = "Workspace settings"),
+        (name = "Admin", description = "Operational endpoints for administrators"),
         (name = "Sessions", description = "Session management"),
         (name = "Messages", description = "Session message history"),
+        (name = "Usage", description = "Session token usage and estimated cost"),
     ),
     info(
         title = "Raworc REST API",
-        version = "1.0.0",
         description = "Remote Agent Work Orchestration REST API with RBAC",
         license(name = "MIT"),
     ),
@@ -108,6 +212,18 @@ use crate::server::rbac::SubjectType;
 )]
 pub struct ApiDoc;
 
+/// The OpenAPI document served to clients: stamped with the crate version and,
+/// when `RAWORC_PUBLIC_URL` is set, the externally reachable server URL.
+pub fn api_spec(public_url: Option<&str>) -> utoipa::openapi::OpenApi {
+    let mut spec = ApiDoc::openapi();
+    spec.info.version = env!("CARGO_PKG_VERSION").to_string();
+
+    if let Some(url) = public_url {
+        spec.servers = Some(vec![utoipa::openapi::Server::new(url.trim_end_matches('/'))]);
+    }
+    spec
+}
+
 struct SecurityAddon;
 
 impl Modify for SecurityAddon {
@@ -138,7 +254,7 @@ pub async fn health() {}
     path = "/api/v0/version",
     tag = "Health",
     responses(
-        (status = 200, description = "API version", body = String),
+        (status = 200, description = "API version and build metadata", body = VersionResponse),
     ),
 )]
 #[allow(dead_code)]
@@ -183,7 +299,7 @@ pub async fn external_login() {}
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 200, description = "Current user info", body = serde_json::Value),
+        (status = 200, description = "Current principal with its role bindings and effective permissions", body = MeResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
     ),
 )]
@@ -198,6 +314,11 @@ pub async fn me() {}
     security(
         ("bearer_auth" = [])
     ),
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum results (1-1000); all when omitted"),
+        ("offset" = Option<i64>, Query, description = "Results to skip"),
+        ("name_contains" = Option<String>, Query, description = "Case-insensitive substring of the account name"),
+    ),
     responses(
         (status = 200, description = "List of service accounts", body = Vec<ServiceAccountResponse>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
@@ -227,6 +348,27 @@ pub async fn list_service_accounts() {}
 #[allow(dead_code)]
 pub async fn get_service_account() {}
 
+#[utoipa::path(
+    get,
+    path = "/api/v0/service-accounts/{id}/role-bindings",
+    tag = "Service Accounts",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Service account ID or username"),
+        ("expand_rules" = Option<bool>, Query, description = "Include the rules of each bound role"),
+    ),
+    responses(
+        (status = 200, description = "Role bindings of the service account", body = Vec<ServiceAccountRoleBindingResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Service account not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_service_account_role_bindings() {}
+
 #[utoipa::path(
     post,
     path = "/api/v0/service-accounts",
@@ -318,6 +460,11 @@ pub async fn update_service_account_password() {}
     security(
         ("bearer_auth" = [])
     ),
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum results (1-1000); all when omitted"),
+        ("offset" = Option<i64>, Query, description = "Results to skip"),
+        ("name_contains" = Option<String>, Query, description = "Case-insensitive substring of the role name"),
+    ),
     responses(
         (status = 200, description = "List of roles", body = Vec<RoleResponse>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
@@ -357,7 +504,7 @@ pub async fn get_role() {}
     ),
     responses(
         (status = 200, description = "Role created", body = RoleResponse),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 400, description = "Invalid request, or a rule names an unknown api group, resource or verb while RAWORC_VALIDATE_ROLE_RULES is on", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
         (status = 409, description = "Role already exists", body = ErrorResponse),
@@ -394,8 +541,13 @@ pub async fn delete_role() {}
     security(
         ("bearer_auth" = [])
     ),
+    params(
+        ("scope" = Option<String>, Query, description = "`global` (no workspace), `workspace` (workspace-scoped) or `all` (default)"),
+        ("workspace" = Option<String>, Query, description = "Only workspace-scoped bindings for this workspace; global bindings are still included unless scope=workspace"),
+    ),
     responses(
         (status = 200, description = "List of role bindings", body = Vec<RoleBindingResponse>),
+        (status = 400, description = "Invalid workspace, or workspace combined with scope=global", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
     ),
@@ -442,6 +594,24 @@ pub async fn get_role_binding() {}
 #[allow(dead_code)]
 pub async fn create_role_binding() {}
 
+#[utoipa::path(
+    post,
+    path = "/api/v0/role-bindings/bulk",
+    tag = "Role Bindings",
+    request_body = Vec<CreateRoleBindingRequest>,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Per-item results; existing bindings are returned with created = false", body = Vec<BulkRoleBindingResult>),
+        (status = 400, description = "Invalid item or unknown role; nothing was created", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions for an item", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn create_role_bindings_bulk() {}
+
 #[utoipa::path(
     delete,
     path = "/api/v0/role-bindings/{id}",
@@ -462,6 +632,27 @@ pub async fn create_role_binding() {}
 #[allow(dead_code)]
 pub async fn delete_role_binding() {}
 
+#[utoipa::path(
+    delete,
+    path = "/api/v0/role-bindings/by-id/{id}",
+    tag = "Role Bindings",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Role binding UUID"),
+    ),
+    responses(
+        (status = 204, description = "Role binding deleted"),
+        (status = 400, description = "Invalid role binding ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Role binding not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn delete_role_binding_by_id() {}
+
 // Agent endpoints
 #[utoipa::path(
     get,
@@ -470,8 +661,14 @@ pub async fn delete_role_binding() {}
     security(
         ("bearer_auth" = [])
     ),
+    params(
+        ("workspace" = Option<String>, Query, description = "Workspace to list (defaults to the caller's)"),
+        ("created_after" = Option<String>, Query, description = "Only agents created at or after this RFC 3339 timestamp"),
+        ("created_before" = Option<String>, Query, description = "Only agents created before this RFC 3339 timestamp"),
+    ),
     responses(
         (status = 200, description = "List of agents", body = Vec<AgentResponse>),
+        (status = 400, description = "Invalid or inverted created_after/created_before", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
     ),
@@ -488,9 +685,11 @@ pub async fn list_agents() {}
     ),
     params(
         ("id" = String, Path, description = "Agent ID or name"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response"),
     ),
     responses(
-        (status = 200, description = "Agent details", body = AgentResponse),
+        (status = 200, description = "Agent details", body = AgentResponse, headers(("ETag" = String, description = "Weak ETag of the representation"))),
+        (status = 304, description = "Agent unchanged since the given ETag"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
         (status = 404, description = "Agent not found", body = ErrorResponse),
@@ -499,6 +698,28 @@ pub async fn list_agents() {}
 #[allow(dead_code)]
 pub async fn get_agent() {}
 
+#[utoipa::path(
+    head,
+    path = "/api/v0/agents/{id}",
+    tag = "Agents",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Agent ID or name"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response"),
+    ),
+    responses(
+        (status = 200, description = "Agent exists; same headers as GET, without a body", headers(("ETag" = String, description = "Weak ETag of the representation"), ("Content-Length" = u64, description = "Size of the GET body"))),
+        (status = 304, description = "Agent unchanged since the given ETag"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Agent not found"),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn head_agent() {}
+
 #[utoipa::path(
     post,
     path = "/api/v0/agents",
@@ -513,6 +734,7 @@ pub async fn get_agent() {}
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
         (status = 409, description = "Agent already exists", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
@@ -536,6 +758,7 @@ pub async fn create_agent() {}
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
         (status = 404, description = "Agent not found", body = ErrorResponse),
         (status = 409, description = "Agent name conflict", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
@@ -561,147 +784,1207 @@ pub async fn update_agent() {}
 #[allow(dead_code)]
 pub async fn delete_agent() {}
 
-// Session endpoints
 #[utoipa::path(
     get,
-    path = "/api/v0/sessions",
-    tag = "Sessions",
+    path = "/api/v0/agents/{id}/versions",
+    tag = "Agents",
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("created_by" = Option<String>, Query, description = "Filter by creator (admin only)"),
-        ("lifecycle_state" = Option<String>, Query, description = "Filter by lifecycle state"),
+        ("id" = String, Path, description = "Agent ID"),
     ),
     responses(
-        (status = 200, description = "List of sessions", body = Vec<SessionResponse>),
+        (status = 200, description = "Saved revisions, newest first; recorded while RAWORC_AGENT_REVISIONS is enabled", body = Vec<AgentRevisionResponse>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Agent not found", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn list_sessions() {}
+pub async fn list_agent_versions() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/agents/{id}/versions/{revision}/restore",
+    tag = "Agents",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Agent ID"),
+        ("revision" = i32, Path, description = "Revision to put back"),
+    ),
+    responses(
+        (status = 200, description = "Agent restored to the revision; the replaced definition is saved as a new revision", body = AgentResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Agent or revision not found", body = ErrorResponse),
+        (status = 409, description = "The revision's name is taken by another agent", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn restore_agent_version() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/agents/{id}/test",
+    tag = "Agents",
+    request_body = TestAgentRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Agent ID"),
+    ),
+    responses(
+        (status = 200, description = "The agent's reply from a throwaway session, which is already removed; no reply when a guardrail blocked it", body = AgentTestResponse),
+        (status = 400, description = "Invalid agent ID, the agent is inactive, or a requested secret names a denied variable", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions, or a requested secret the caller may not read", body = ErrorResponse),
+        (status = 404, description = "Agent not found", body = ErrorResponse),
+        (status = 422, description = "Blank prompt, or one longer than RAWORC_MAX_MESSAGE_LENGTH", body = ErrorResponse),
+        (status = 429, description = "RAWORC_MAX_CONCURRENT_AGENT_TESTS tests are already running, or no container capacity for the test session; retry after the Retry-After seconds", body = ErrorResponse),
+        (status = 503, description = "The test session failed or the agent didn't answer within RAWORC_AGENT_TEST_TIMEOUT_SECONDS", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn test_agent() {}
 
 #[utoipa::path(
     get,
-    path = "/api/v0/sessions/{id}",
-    tag = "Sessions",
+    path = "/api/v0/secrets",
+    tag = "Secrets",
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("id" = String, Path, description = "Session ID"),
+        ("workspace" = Option<String>, Query, description = "Workspace (defaults to the caller's)"),
     ),
     responses(
-        (status = 200, description = "Session details", body = SessionResponse),
+        (status = 200, description = "Secret metadata; values are never returned", body = Vec<SecretResponse>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
-        (status = 404, description = "Session not found", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn get_session() {}
+pub async fn list_secrets() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/secrets/{name}",
+    tag = "Secrets",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("name" = String, Path, description = "Secret name"),
+        ("workspace" = Option<String>, Query, description = "Workspace (defaults to the caller's)"),
+    ),
+    responses(
+        (status = 200, description = "Secret metadata; the value is never returned", body = SecretResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Secret not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_secret() {}
 
 #[utoipa::path(
     post,
-    path = "/api/v0/sessions",
-    tag = "Sessions",
-    request_body = CreateSessionRequest,
+    path = "/api/v0/secrets",
+    tag = "Secrets",
+    request_body = CreateSecretRequest,
     security(
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 200, description = "Session created", body = SessionResponse),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 200, description = "Secret stored", body = SecretResponse),
+        (status = 400, description = "Invalid secret or workspace name", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 409, description = "Secret already exists", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn create_session() {}
+pub async fn create_secret() {}
 
 #[utoipa::path(
     put,
-    path = "/api/v0/sessions/{id}",
-    tag = "Sessions",
-    request_body = UpdateSessionRequest,
+    path = "/api/v0/secrets/{name}",
+    tag = "Secrets",
+    request_body = UpdateSecretRequest,
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("id" = String, Path, description = "Session ID"),
+        ("name" = String, Path, description = "Secret name"),
+        ("workspace" = Option<String>, Query, description = "Workspace (defaults to the caller's)"),
     ),
     responses(
-        (status = 200, description = "Session updated", body = SessionResponse),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 200, description = "Secret updated", body = SecretResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
-        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 404, description = "Secret not found", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn update_session() {}
+pub async fn update_secret() {}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v0/secrets/{name}",
+    tag = "Secrets",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("name" = String, Path, description = "Secret name"),
+        ("workspace" = Option<String>, Query, description = "Workspace (defaults to the caller's)"),
+    ),
+    responses(
+        (status = 204, description = "Secret deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Secret not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn delete_secret() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/workspaces/{workspace}/settings",
+    tag = "Workspaces",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("workspace" = String, Path, description = "Workspace name"),
+    ),
+    responses(
+        (status = 200, description = "Workspace settings", body = WorkspaceSettingsResponse),
+        (status = 400, description = "Invalid workspace or node name", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_workspace_settings() {}
 
 #[utoipa::path(
     put,
-    path = "/api/v0/sessions/{id}/state",
-    tag = "Sessions",
-    request_body = UpdateSessionStateRequest,
+    path = "/api/v0/workspaces/{workspace}/settings",
+    tag = "Workspaces",
+    request_body = UpdateWorkspaceSettingsRequest,
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("id" = String, Path, description = "Session ID"),
+        ("workspace" = String, Path, description = "Workspace name"),
     ),
     responses(
-        (status = 200, description = "Session state updated", body = SessionResponse),
-        (status = 400, description = "Invalid state transition", body = ErrorResponse),
+        (status = 200, description = "Workspace settings updated", body = WorkspaceSettingsResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions, or a tier change without the global workspaces/set-tier permission", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn update_workspace_settings() {}
+
+// Admin endpoints
+#[utoipa::path(
+    get,
+    path = "/api/v0/admin/containers",
+    tag = "Admin",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("workspace" = Option<String>, Query, description = "Only list containers labelled with this workspace"),
+        ("node" = Option<String>, Query, description = "Node whose operator to ask; defaults to the operator without a node name"),
+    ),
+    responses(
+        (status = 200, description = "Managed containers with orphans and ghost sessions flagged", body = ContainerReport),
+        (status = 400, description = "Invalid workspace or node name", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
-        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Docker is not available", body = ErrorResponse),
+        (status = 503, description = "RAWORC_NODE_API_KEY is not set, or the node's operator can't be reached", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn update_session_state() {}
+pub async fn list_containers() {}
 
 #[utoipa::path(
     post,
-    path = "/api/v0/sessions/{id}/remix",
-    tag = "Sessions",
-    request_body = RemixSessionRequest,
+    path = "/api/v0/admin/containers/reconcile",
+    tag = "Admin",
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("id" = String, Path, description = "Parent session ID"),
+        ("workspace" = Option<String>, Query, description = "Only reconcile containers labelled with this workspace"),
+        ("node" = Option<String>, Query, description = "Node whose operator to ask; defaults to the operator without a node name"),
     ),
     responses(
-        (status = 200, description = "New session created from parent", body = SessionResponse),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 200, description = "Orphaned containers removed", body = ReconcileResponse),
+        (status = 400, description = "Invalid workspace or node name", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
-        (status = 404, description = "Parent session not found", body = ErrorResponse),
+        (status = 500, description = "Docker is not available", body = ErrorResponse),
+        (status = 503, description = "RAWORC_NODE_API_KEY is not set, or the node's operator can't be reached", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn remix_session() {}
+pub async fn reconcile_containers() {}
 
 #[utoipa::path(
-    delete,
-    path = "/api/v0/sessions/{id}",
-    tag = "Sessions",
+    get,
+    path = "/api/v0/admin/images",
+    tag = "Admin",
     security(
         ("bearer_auth" = [])
     ),
     params(
-        ("id" = String, Path, description = "Session ID"),
+        ("node" = Option<String>, Query, description = "Node whose operator to ask; defaults to the operator without a node name"),
     ),
     responses(
-        (status = 204, description = "Session deleted"),
+        (status = 200, description = "Tagged images available locally, the session image first", body = Vec<ImageInfo>),
+        (status = 400, description = "Invalid node name", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 500, description = "Docker is not available", body = ErrorResponse),
+        (status = 503, description = "RAWORC_NODE_API_KEY is not set, or the node's operator can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_images() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/admin/images/pull",
+    tag = "Admin",
+    request_body = PullImageRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("node" = Option<String>, Query, description = "Node whose operator to ask; defaults to the operator without a node name"),
+    ),
+    responses(
+        (status = 202, description = "Pull started in the background; the image is listed once it is there", body = PullImageResponse),
+        (status = 400, description = "Invalid node name", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 422, description = "Invalid image reference", body = ErrorResponse),
+        (status = 500, description = "The operator failed to start the pull", body = ErrorResponse),
+        (status = 503, description = "RAWORC_NODE_API_KEY is not set, or the node's operator can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn pull_image() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/admin/read-only",
+    tag = "Admin",
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Whether the server is in read-only maintenance mode", body = ReadOnlyResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_read_only() {}
+
+#[utoipa::path(
+    put,
+    path = "/api/v0/admin/read-only",
+    tag = "Admin",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = SetReadOnlyRequest,
+    responses(
+        (status = 200, description = "Read-only mode updated for this server process", body = ReadOnlyResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Insufficient permissions", body = ErrorResponse),
-        (status = 404, description = "Session not found", body = ErrorResponse),
     ),
 )]
 #[allow(dead_code)]
-pub async fn delete_session() {}
\ No newline at end of file
+pub async fn set_read_only() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/admin/sessions/reap-idle",
+    tag = "Admin",
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "READY sessions past their waiting timeout moved to IDLE; their containers are being stopped", body = ReapIdleResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn reap_idle_sessions() {}
+
+// Session endpoints
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("created_by" = Option<String>, Query, description = "Filter by creator (admin only)"),
+        ("lifecycle_state" = Option<String>, Query, description = "Filter by lifecycle state"),
+        ("name" = Option<String>, Query, description = "Filter by exact session name"),
+        ("parent_id" = Option<String>, Query, description = "Only direct remixes of this session"),
+        ("created_after" = Option<String>, Query, description = "Only sessions created at or after this RFC 3339 timestamp"),
+        ("created_before" = Option<String>, Query, description = "Only sessions created before this RFC 3339 timestamp"),
+        ("include_deleted" = Option<bool>, Query, description = "Include soft-deleted sessions with their deleted_at, listing every user's sessions unless created_by is given (admin only)"),
+    ),
+    responses(
+        (status = 200, description = "List of sessions", body = Vec<SessionResponse>),
+        (status = 400, description = "Invalid or inverted created_after/created_before", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_sessions() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/me/sessions",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("workspace" = Option<String>, Query, description = "Filter by workspace"),
+        ("state" = Option<String>, Query, description = "Filter by session state"),
+        ("name" = Option<String>, Query, description = "Filter by exact session name"),
+        ("parent_id" = Option<String>, Query, description = "Only direct remixes of this session"),
+        ("created_after" = Option<String>, Query, description = "Only sessions created at or after this RFC 3339 timestamp"),
+        ("created_before" = Option<String>, Query, description = "Only sessions created before this RFC 3339 timestamp"),
+    ),
+    responses(
+        (status = 200, description = "Sessions created by the caller", body = Vec<SessionResponse>),
+        (status = 400, description = "Invalid or inverted created_after/created_before", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_my_sessions() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response"),
+    ),
+    responses(
+        (status = 200, description = "Session details", body = SessionResponse, headers(("ETag" = String, description = "Weak ETag of the representation"))),
+        (status = 304, description = "Session unchanged since the given ETag"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session() {}
+
+#[utoipa::path(
+    head,
+    path = "/api/v0/sessions/{id}",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response"),
+    ),
+    responses(
+        (status = 200, description = "Session exists; same headers as GET, without a body", headers(("ETag" = String, description = "Weak ETag of the representation"), ("Content-Length" = u64, description = "Size of the GET body"))),
+        (status = 304, description = "Session unchanged since the given ETag"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Session not found"),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn head_session() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/status",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response"),
+    ),
+    responses(
+        (status = 200, description = "Session state for polling, without agents", body = SessionStatusResponse, headers(("ETag" = String, description = "Weak ETag of the representation"))),
+        (status = 304, description = "Status unchanged since the given ETag"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session_status() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/tree",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Root session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session with its remixes, recursively", body = SessionTreeNode),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session_tree() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions",
+    tag = "Sessions",
+    request_body = CreateSessionRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Session created", body = SessionResponse),
+        (status = 400, description = "Invalid request, agent outside the session's workspace, or node_selector names a node no operator runs on", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions, or metadata.secrets names a secret the caller may not read", body = ErrorResponse),
+        (status = 409, description = "Session name already in use", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
+        (status = 429, description = "No container capacity and the start queue is full; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn create_session() {}
+
+#[utoipa::path(
+    put,
+    path = "/api/v0/sessions/{id}",
+    tag = "Sessions",
+    request_body = UpdateSessionRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session updated", body = SessionResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions, or metadata.secrets names a secret the caller may not read", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "Session name already in use", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn update_session() {}
+
+#[utoipa::path(
+    put,
+    path = "/api/v0/sessions/{id}/state",
+    tag = "Sessions",
+    request_body = UpdateSessionStateRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session state updated", body = SessionResponse),
+        (status = 400, description = "Invalid state transition", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 429, description = "Waking an IDLE session with no container capacity while the start queue is full; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn update_session_state() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/heartbeat",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 204, description = "Session activity recorded"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Neither the session's host token nor its owner", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn heartbeat_session() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/cancel",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Current operation cancelled; the session is READY", body = SessionResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "Session is not BUSY", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn cancel_session() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/pause",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session is IDLE and its container is being stopped", body = SessionResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "Session is not READY or BUSY", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn pause_session() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/resume",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session is INIT until its container has been restarted, then READY", body = SessionResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "Session is not IDLE", body = ErrorResponse),
+        (status = 429, description = "No container capacity and the start queue is full; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn resume_session() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/agents",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Agents attached to the session", body = Vec<SessionAgentInfo>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_session_agents() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/agents",
+    tag = "Sessions",
+    request_body = AttachSessionAgentRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Agent attached; returns the session's agents", body = Vec<SessionAgentInfo>),
+        (status = 400, description = "Agent not found, inactive, or in a workspace other than the session's or the global one, or a configuration that is not an object", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn attach_session_agent() {}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v0/sessions/{id}/agents/{agent_id}",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("agent_id" = String, Path, description = "Agent ID"),
+    ),
+    responses(
+        (status = 204, description = "Agent detached"),
+        (status = 400, description = "Invalid ID format", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found or agent not attached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn detach_session_agent() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/config",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "The session's agents with their config and a merged view", body = SessionConfigResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session_config() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/timeline",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "The session's state transitions, oldest first", body = Vec<SessionTimelineEntry>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session_timeline() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/tasks",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("status" = Option<String>, Query, description = "Only tasks in this status: pending, processing, completed or failed"),
+        ("task_type" = Option<String>, Query, description = "Only tasks of this type, e.g. create_session"),
+    ),
+    responses(
+        (status = 200, description = "The session's operator tasks, oldest first", body = Vec<SessionTaskResponse>),
+        (status = 400, description = "Invalid task status", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_session_tasks() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/export",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "JSON Lines: a `session` header line, then one `message` line per message, oldest first", body = SessionExportLine, content_type = "application/x-ndjson"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn export_session() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/logs",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("download" = Option<bool>, Query, description = "Send the logs as a `session-<id>.log` attachment"),
+    ),
+    responses(
+        (status = 200, description = "Container output, stdout and stderr interleaved", body = String, content_type = "text/plain"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Cannot access other users' sessions", body = ErrorResponse),
+        (status = 404, description = "Session not found, or its container was destroyed", body = ErrorResponse),
+        (status = 503, description = "The operator on the session's node can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_session_logs() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/import",
+    tag = "Sessions",
+    request_body(content = String, description = "A session export as produced by `GET /api/v0/sessions/{id}/export`", content_type = "application/x-ndjson"),
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("name" = Option<String>, Query, description = "Name for the new session instead of the exported one"),
+    ),
+    responses(
+        (status = 200, description = "New session created from the export, with its messages, which are marked `imported` so the host does not answer them again", body = SessionResponse),
+        (status = 400, description = "Malformed export, or an agent outside the workspace", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "A SYSTEM message without the sessions:post-system-message permission, or metadata.secrets names a secret the caller may not read", body = ErrorResponse),
+        (status = 409, description = "Session name already in use", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
+        (status = 429, description = "No container capacity and the start queue is full; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn import_session() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/remix",
+    tag = "Sessions",
+    request_body = RemixSessionRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Parent session ID"),
+    ),
+    responses(
+        (status = 200, description = "New session created from parent", body = SessionResponse),
+        (status = 400, description = "Invalid request or agent outside the parent's workspace", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions, or metadata.secrets names a secret the caller may not read", body = ErrorResponse),
+        (status = 404, description = "Parent session not found", body = ErrorResponse),
+        (status = 409, description = "Session name already in use, or the parent is already at the maximum remix depth", body = ErrorResponse),
+        (status = 422, description = "Field validation failed; details map each field to its error", body = ErrorResponse),
+        (status = 429, description = "No container capacity and the start queue is full; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn remix_session() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/shell",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 101, description = "Switched to a WebSocket carrying the shell's stdin (client frames) and terminal output (binary frames)"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Missing sessions:exec-interactive permission", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "Session is not READY or BUSY", body = ErrorResponse),
+        (status = 503, description = "The operator on the session's node can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn session_shell() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/files",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("path" = String, Query, description = "Absolute path of the file in the session's container"),
+    ),
+    responses(
+        (status = 200, description = "The file's contents, copied out through the Docker archive API", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 400, description = "path is not an absolute path to a file", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Missing sessions:read-files permission", body = ErrorResponse),
+        (status = 404, description = "Session not found, or its container or the file is missing", body = ErrorResponse),
+        (status = 503, description = "The operator on the session's node can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn read_session_file() {}
+
+#[utoipa::path(
+    put,
+    path = "/api/v0/sessions/{id}/files",
+    tag = "Sessions",
+    request_body(content = Vec<u8>, description = "The file's new contents", content_type = "application/octet-stream"),
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("path" = String, Query, description = "Absolute path of the file in the session's container; its directory must exist"),
+    ),
+    responses(
+        (status = 204, description = "File created or replaced"),
+        (status = 400, description = "path is not an absolute path to a file", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Missing sessions:write-files permission", body = ErrorResponse),
+        (status = 404, description = "Session not found, or its container or the file's directory is missing", body = ErrorResponse),
+        (status = 503, description = "The operator on the session's node can't be reached", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn write_session_file() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/transfer",
+    tag = "Sessions",
+    request_body = TransferSessionRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Session ownership transferred", body = SessionResponse),
+        (status = 400, description = "Invalid request or unknown principal", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 409, description = "New owner already has a session with this name", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn transfer_session() {}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v0/sessions/{id}",
+    tag = "Sessions",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 204, description = "Session deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient permissions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn delete_session() {}
+
+// Message endpoints
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/messages",
+    tag = "Messages",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("limit" = Option<i64>, Query, description = "Maximum messages to return (default 100, max 1000)"),
+        ("offset" = Option<i64>, Query, description = "Messages to skip"),
+        ("role" = Option<MessageRole>, Query, description = "Only messages with this role"),
+        ("since" = Option<String>, Query, description = "Only messages created after this RFC 3339 timestamp"),
+        ("agent_id" = Option<String>, Query, description = "Only messages from this agent"),
+        ("order" = Option<MessageOrder>, Query, description = "asc (default) for oldest first, desc for newest first"),
+    ),
+    responses(
+        (status = 200, description = "Messages in the session, in the requested order", body = Vec<MessageResponse>),
+        (status = 400, description = "Invalid agent ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn list_messages() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/messages",
+    tag = "Messages",
+    request_body = CreateMessageRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key return the original message"),
+    ),
+    responses(
+        (status = 200, description = "Message created", body = MessageResponse, headers(("X-Backlog-Limit" = u32, description = "RAWORC_MAX_PENDING_MESSAGES, when set and the request has user messages"), ("X-Backlog-Remaining" = u32, description = "User messages the session accepts before it returns 429"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "A SYSTEM message without the sessions:post-system-message permission", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 422, description = "content longer than RAWORC_MAX_MESSAGE_LENGTH, or an AGENT message without agent_id; details map each field to its error", body = ErrorResponse),
+        (status = 429, description = "The session's backlog of unanswered user messages is full, or the session is IDLE and there is no container capacity to wake it; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn create_message() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/messages/batch",
+    tag = "Messages",
+    request_body = Vec<CreateMessageRequest>,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key return the originally stored batch"),
+    ),
+    responses(
+        (status = 200, description = "Messages created, in request order", body = Vec<MessageResponse>, headers(("X-Backlog-Limit" = u32, description = "RAWORC_MAX_PENDING_MESSAGES, when set and the request has user messages"), ("X-Backlog-Remaining" = u32, description = "User messages the session accepts before it returns 429"))),
+        (status = 400, description = "Invalid request, or the batch has more user messages than a session's backlog holds", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "A SYSTEM message without the sessions:post-system-message permission", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 422, description = "A message failed validation; details are keyed by the message, e.g. messages[2].content", body = ErrorResponse),
+        (status = 409, description = "The Idempotency-Key was already used for a batch of a different size", body = ErrorResponse),
+        (status = 429, description = "The batch's user messages would overflow the session's backlog, or the session is IDLE and there is no container capacity to wake it; retry after the Retry-After seconds", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn create_messages_batch() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/messages/system",
+    tag = "Messages",
+    request_body = CreateSystemMessageRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key return the original message"),
+    ),
+    responses(
+        (status = 200, description = "SYSTEM message created", body = MessageResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Missing sessions:post-system-message permission", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 422, description = "content longer than RAWORC_MAX_MESSAGE_LENGTH", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn create_system_message() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/messages/latest",
+    tag = "Messages",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "The session's most recent message", body = MessageResponse),
+        (status = 204, description = "The session has no messages"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Cannot access other users' sessions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_latest_message() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/messages/count",
+    tag = "Messages",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Number of messages in the session", body = MessageCountResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_message_count() {}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v0/sessions/{id}/messages",
+    tag = "Messages",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Messages deleted", body = ClearMessagesResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn clear_messages() {}
+
+// Usage endpoints
+#[utoipa::path(
+    get,
+    path = "/api/v0/sessions/{id}/usage",
+    tag = "Usage",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Token usage totals for the session", body = SessionUsageResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Cannot access other users' sessions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn get_usage() {}
+
+#[utoipa::path(
+    post,
+    path = "/api/v0/sessions/{id}/usage",
+    tag = "Usage",
+    request_body = RecordUsageRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Session ID"),
+    ),
+    responses(
+        (status = 200, description = "Usage recorded; returns the updated totals", body = SessionUsageResponse),
+        (status = 400, description = "The agent_id names no agent", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not authenticated with the session's host token", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 422, description = "Validation failed", body = ErrorResponse),
+    ),
+)]
+#[allow(dead_code)]
+pub async fn record_usage() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_endpoints_are_in_the_spec() {
+        let spec = serde_json::to_value(api_spec(None)).unwrap();
+        let paths = &spec["paths"];
+
+        for (path, method) in [
+            ("/api/v0/sessions/{id}/messages", "get"),
+            ("/api/v0/sessions/{id}/messages", "post"),
+            ("/api/v0/sessions/{id}/messages", "delete"),
+            ("/api/v0/sessions/{id}/messages/count", "get"),
+        ] {
+            let operation = &paths[path][method];
+            assert!(operation.is_object(), "{} {} is missing", method, path);
+            assert_eq!(operation["tags"], serde_json::json!(["Messages"]), "{} {}", method, path);
+        }
+
+        // Request and response bodies name their schemas
+        let create = &paths["/api/v0/sessions/{id}/messages"]["post"];
+        assert!(create["requestBody"]["content"]["application/json"]["schema"]["$ref"].is_string());
+        for schema in ["MessageResponse", "CreateMessageRequest", "MessageCountResponse", "ClearMessagesResponse"] {
+            assert!(spec["components"]["schemas"][schema].is_object(), "{} schema is missing", schema);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::server::rbac::AuthPrincipal;
+use crate::server::rest::error::ApiError;
+use crate::server::rest::middleware::AuthContext;
+use crate::shared::models::AppState;
+use crate::shared::rate_limit::RateLimitDecision;
+
+/// Service accounts and external subjects are separate namespaces, so they get separate buckets.
+/// Requests without a principal, e.g. logins, share a bucket per client address.
+fn bucket_key(request: &Request) -> String {
+    if let Some(auth) = request.extensions().get::<AuthContext>() {
+        return match &auth.principal {
+            AuthPrincipal::Subject(s) => format!("subject:{}", s.name),
+            AuthPrincipal::ServiceAccount(sa) => format!("service-account:{}", sa.user),
+        };
+    }
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(address)) => format!("address:{}", address.ip()),
+        None => "anonymous".to_string(),
+    }
+}
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert("x-ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert("x-ratelimit-reset", HeaderValue::from(decision.reset_secs));
+}
+
+/// Limit each principal's request rate, answering 429 with `Retry-After` once its bucket is
+/// empty. Applied to public routes as well as protected ones, except health checks.
+pub async fn rate_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_deref() else {
+        return next.run(request).await;
+    };
+    let decision = limiter.check(&bucket_key(&request), Instant::now());
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        ApiError::RateLimited { retry_after_secs: decision.retry_after_secs }.into_response()
+    };
+    set_rate_limit_headers(response.headers_mut(), &decision);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use crate::server::rest::test_support::TestApp;
+    use crate::shared::config::RateLimitConfig;
+
+    async fn limited_app() -> TestApp {
+        TestApp::with_config(|config| {
+            config.server.rate_limit = Some(RateLimitConfig { requests_per_minute: 1, burst: 2 })
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn exceeding_the_burst_returns_429_with_headers() {
+        let app = limited_app().await;
+        let token = app.admin_token();
+
+        for remaining in ["1", "0"] {
+            let response = app.request(Method::GET, "/api/v0/auth/me", &token, None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["x-ratelimit-limit"], "2");
+            assert_eq!(response.headers()["x-ratelimit-remaining"], remaining);
+        }
+
+        let response = app.request(Method::GET, "/api/v0/auth/me", &token, None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "60");
+        assert_eq!(response.headers()["x-ratelimit-remaining"], "0");
+        assert_eq!(response.headers()["x-ratelimit-reset"], "120");
+
+        // Other principals have their own bucket
+        let response = app.request(Method::GET, "/api/v0/auth/me", &app.user_token("someone"), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn public_routes_carry_headers_and_health_is_exempt() {
+        let app = limited_app().await;
+
+        let response = app.request(Method::GET, "/api/v0/version", "", None).await;
+        assert_eq!(response.headers()["x-ratelimit-limit"], "2");
+
+        for _ in 0..3 {
+            let response = app.request(Method::GET, "/api/v0/health", "", None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(response.headers().get("x-ratelimit-limit").is_none());
+        }
+    }
+}
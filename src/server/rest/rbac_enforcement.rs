@@ -1,7 +1,7 @@
 use axum::http::StatusCode;
 use crate::shared::models::AppState;
 use crate::server::rest::middleware::AuthContext;
-use crate::server::rbac::PermissionContext;
+use crate::server::rbac::{PermissionContext, Rule};
 use crate::server::auth::check_permission;
 
 /// Permission requirements for each API endpoint
@@ -22,6 +22,11 @@ impl PermissionRequirement {
             workspace_scoped,
         }
     }
+
+    /// Unscoped context for checks made outside `check_api_permission`
+    pub fn context(&self) -> PermissionContext {
+        PermissionContext::new(self.api_group, self.resource, self.verb)
+    }
 }
 
 /// Check if user has permission for the requested action
@@ -103,6 +108,8 @@ pub mod permissions {
         PermissionRequirement::new("api", "agents", "update", true);
     pub const AGENT_DELETE: PermissionRequirement = 
         PermissionRequirement::new("api", "agents", "delete", true);
+    pub const AGENT_TEST: PermissionRequirement = 
+        PermissionRequirement::new("api", "agents", "test", true);
 
     // Session permissions (workspace-scoped)
     #[allow(dead_code)]
@@ -118,9 +125,101 @@ pub mod permissions {
         PermissionRequirement::new("api", "sessions", "update", true);
     pub const SESSION_DELETE: PermissionRequirement = 
         PermissionRequirement::new("api", "sessions", "delete", true);
-    #[allow(dead_code)]
+    pub const SESSION_TRANSFER: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "transfer", true);
+    pub const SESSION_EXEC_INTERACTIVE: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "exec-interactive", true);
+    pub const SESSION_FILES_READ: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "read-files", true);
+    pub const SESSION_FILES_WRITE: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "write-files", true);
+    pub const SESSION_MESSAGE_SYSTEM: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "post-system-message", true);
+    pub const SESSION_REAP_IDLE: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "reap-idle", false);
     pub const SESSION_LIST_ALL: PermissionRequirement = 
         PermissionRequirement::new("api", "sessions", "list-all", false);
+    pub const SESSION_GET_ALL: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "get-all", false);
+    pub const SESSION_REMIX_ALL: PermissionRequirement = 
+        PermissionRequirement::new("api", "sessions", "remix-all", false);
+
+    // Secret permissions (workspace-scoped)
+    pub const SECRET_LIST: PermissionRequirement = 
+        PermissionRequirement::new("api", "secrets", "list", true);
+    pub const SECRET_GET: PermissionRequirement = 
+        PermissionRequirement::new("api", "secrets", "get", true);
+    pub const SECRET_CREATE: PermissionRequirement = 
+        PermissionRequirement::new("api", "secrets", "create", true);
+    pub const SECRET_UPDATE: PermissionRequirement = 
+        PermissionRequirement::new("api", "secrets", "update", true);
+    pub const SECRET_DELETE: PermissionRequirement = 
+        PermissionRequirement::new("api", "secrets", "delete", true);
+
+    // Workspace settings permissions (workspace-scoped)
+    pub const WORKSPACE_GET: PermissionRequirement = 
+        PermissionRequirement::new("api", "workspaces", "get", true);
+    pub const WORKSPACE_UPDATE: PermissionRequirement = 
+        PermissionRequirement::new("api", "workspaces", "update", true);
+    /// Global: the tier decides a workspace's resources, so its own admins can't raise it
+    pub const WORKSPACE_SET_TIER: PermissionRequirement = 
+        PermissionRequirement::new("api", "workspaces", "set-tier", false);
+
+    // Container administration permissions (global)
+    pub const CONTAINER_LIST: PermissionRequirement = 
+        PermissionRequirement::new("api", "containers", "list", false);
+    pub const CONTAINER_RECONCILE: PermissionRequirement = 
+        PermissionRequirement::new("api", "containers", "reconcile", false);
+    pub const IMAGE_LIST: PermissionRequirement = 
+        PermissionRequirement::new("api", "images", "list", false);
+    pub const IMAGE_PULL: PermissionRequirement = 
+        PermissionRequirement::new("api", "images", "pull", false);
+
+    // Maintenance mode permissions (global)
+    pub const MAINTENANCE_GET: PermissionRequirement = 
+        PermissionRequirement::new("api", "maintenance", "get", false);
+    pub const MAINTENANCE_UPDATE: PermissionRequirement = 
+        PermissionRequirement::new("api", "maintenance", "update", false);
+
+    /// Every permission an endpoint checks; role rules are validated against this list
+    pub const ALL: &[PermissionRequirement] = &[
+        SERVICE_ACCOUNT_LIST, SERVICE_ACCOUNT_GET, SERVICE_ACCOUNT_CREATE, SERVICE_ACCOUNT_UPDATE, SERVICE_ACCOUNT_DELETE,
+        ROLE_LIST, ROLE_GET, ROLE_CREATE, ROLE_UPDATE, ROLE_DELETE,
+        ROLE_BINDING_LIST, ROLE_BINDING_GET, ROLE_BINDING_CREATE, ROLE_BINDING_UPDATE, ROLE_BINDING_DELETE,
+        AGENT_LIST, AGENT_GET, AGENT_CREATE, AGENT_UPDATE, AGENT_DELETE, AGENT_TEST,
+        SESSION_LIST, SESSION_GET, SESSION_CREATE, SESSION_UPDATE, SESSION_DELETE, SESSION_TRANSFER,
+        SESSION_EXEC_INTERACTIVE, SESSION_FILES_READ, SESSION_FILES_WRITE, SESSION_MESSAGE_SYSTEM, SESSION_REAP_IDLE, SESSION_LIST_ALL,
+        SESSION_GET_ALL, SESSION_REMIX_ALL,
+        SECRET_LIST, SECRET_GET, SECRET_CREATE, SECRET_UPDATE, SECRET_DELETE,
+        WORKSPACE_GET, WORKSPACE_UPDATE, WORKSPACE_SET_TIER,
+        CONTAINER_LIST, CONTAINER_RECONCILE, IMAGE_LIST, IMAGE_PULL,
+        MAINTENANCE_GET, MAINTENANCE_UPDATE,
+    ];
+}
+
+/// Tokens in `rule` that no endpoint ever checks, e.g. `verb 'lst'`; `*` always matches.
+/// Resources are looked up within the rule's api groups and verbs within its resources,
+/// so a verb that exists elsewhere but not on the named resources is reported too.
+pub fn unknown_rule_tokens(rule: &Rule) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut known: Vec<&PermissionRequirement> = permissions::ALL.iter().collect();
+
+    let field = |p: &PermissionRequirement, level: usize| [p.api_group, p.resource, p.verb][level];
+    let levels = [("api group", &rule.api_groups), ("resource", &rule.resources), ("verb", &rule.verbs)];
+    for (level, (kind, tokens)) in levels.into_iter().enumerate() {
+        for token in tokens.iter().filter(|t| *t != "*") {
+            if !known.iter().any(|p| field(p, level) == token) {
+                unknown.push(format!("{} '{}'", kind, token));
+            }
+        }
+        // Narrow to what the rule names, unless it names nothing known at this level
+        let wildcard = tokens.iter().any(|t| t == "*");
+        let narrowed: Vec<_> = known.iter().copied().filter(|p| tokens.iter().any(|t| field(p, level) == t)).collect();
+        if !wildcard && !narrowed.is_empty() {
+            known = narrowed;
+        }
+    }
+    unknown
 }
 
 /// Extract workspace from JWT claims
@@ -166,4 +265,67 @@ pub async fn check_workspace_access(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(has_workspace_access)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(api_groups: &[&str], resources: &[&str], verbs: &[&str]) -> Rule {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        Rule {
+            api_groups: owned(api_groups),
+            resources: owned(resources),
+            verbs: owned(verbs),
+            resource_names: None,
+        }
+    }
+
+    #[test]
+    fn accepts_known_rule() {
+        assert!(unknown_rule_tokens(&rule(&["api"], &["sessions"], &["get", "get-all"])).is_empty());
+    }
+
+    #[test]
+    fn reports_typo_in_verb() {
+        assert_eq!(unknown_rule_tokens(&rule(&["api"], &["sessions"], &["lst"])), vec!["verb 'lst'"]);
+    }
+
+    #[test]
+    fn reports_verb_missing_on_named_resource() {
+        // `reconcile` exists, but only on containers
+        assert_eq!(unknown_rule_tokens(&rule(&["api"], &["secrets"], &["reconcile"])), vec!["verb 'reconcile'"]);
+    }
+
+    #[test]
+    fn wildcards_match_everything() {
+        assert!(unknown_rule_tokens(&rule(&["*"], &["*"], &["*"])).is_empty());
+        assert!(unknown_rule_tokens(&rule(&["api"], &["*"], &["reconcile"])).is_empty());
+        assert_eq!(unknown_rule_tokens(&rule(&["*"], &["sessoins"], &["*"])), vec!["resource 'sessoins'"]);
+    }
+
+    #[test]
+    fn every_permission_constant_is_in_all() {
+        let source = include_str!("rbac_enforcement.rs");
+        let module = &source[source.find("pub mod permissions").unwrap()..source.find("pub const ALL").unwrap()];
+        let declared: Vec<(&str, &str, &str)> = module
+            .split("PermissionRequirement::new(")
+            .skip(1)
+            .map(|call| {
+                let args: Vec<&str> = call.split('"').collect();
+                (args[1], args[3], args[5])
+            })
+            .collect();
+
+        assert!(declared.len() > 40);
+        for (api_group, resource, verb) in declared {
+            assert!(
+                permissions::ALL.iter().any(|p| (p.api_group, p.resource, p.verb) == (api_group, resource, verb)),
+                "{}/{}/{} is missing from permissions::ALL",
+                api_group,
+                resource,
+                verb
+            );
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::server::rest::error::ApiError;
+use crate::shared::models::AppState;
+
+/// Toggle endpoint, exempt so an admin can always lift read-only mode
+pub const READ_ONLY_TOGGLE_PATH: &str = "/admin/read-only";
+
+/// Whether a request may proceed while writes are blocked: reads always may,
+/// as may logins and the toggle itself. Paths are relative to the API prefix.
+fn allowed_while_read_only(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || path.starts_with("/auth/")
+        || path == READ_ONLY_TOGGLE_PATH
+}
+
+/// Reject mutating requests with 503 while the server is in read-only mode
+pub async fn read_only_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.read_only.load(Ordering::Relaxed)
+        && !allowed_while_read_only(request.method(), request.uri().path())
+    {
+        return ApiError::ServiceUnavailable(
+            "The server is in read-only maintenance mode; writes are temporarily disabled".to_string(),
+        )
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::server::rest::test_support::{body_json, unique, TestApp};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn read_only_mode_blocks_writes_but_not_reads_or_auth() {
+        let app = TestApp::with_config(|config| config.server.read_only = true).await;
+        let token = app.admin_token();
+        let agent = || json!({"name": unique("agent"), "instructions": "Answer briefly", "model": "claude-3-5-sonnet-latest"});
+
+        let response = app.request(Method::GET, "/api/v0/agents", &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.request(Method::POST, "/api/v0/agents", &token, Some(agent())).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body_json(response).await["error"]["message"].as_str().unwrap().contains("read-only"));
+
+        // Logins are checked as usual rather than refused outright
+        let login = json!({"user": unique("nobody"), "pass": "a-password-for-tests"});
+        let response = app.request(Method::POST, "/api/v0/auth/internal", "", Some(login)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.request(Method::GET, "/api/v0/auth/me", &token, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The toggle stays reachable, and lifting it lets writes through again
+        let response = app.request(Method::PUT, "/api/v0/admin/read-only", &token, Some(json!({"read_only": false}))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.request(Method::POST, "/api/v0/agents", &token, Some(agent())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
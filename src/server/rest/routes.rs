@@ -1,4 +1,5 @@
 use axum::{
+    extract::State,
     http::StatusCode,
     middleware,
     routing::{delete, get, post, put},
@@ -6,23 +7,29 @@ use axum::{
 };
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
-use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::shared::models::AppState;
-use crate::server::rest::{auth, handlers, middleware::auth_middleware, logging_middleware::request_logging_middleware, openapi::ApiDoc};
+use crate::server::rest::{auth, handlers, middleware::auth_middleware, logging_middleware::request_logging_middleware, openapi::api_spec};
+use crate::server::rest::rate_limit_middleware::rate_limit_middleware;
+use crate::server::rest::read_only_middleware::read_only_middleware;
+use crate::server::rest::version_middleware::{api_version_middleware, API_VERSION};
 
 pub fn create_router(state: Arc<AppState>) -> Router {
-    // Public routes
+    // Public routes; health checks are not rate limited
     let public_routes = Router::new()
-        .route("/health", get(health))
         .route("/version", get(version))
+        .route("/openapi.json", get(openapi_json))
         .route("/auth/internal", post(auth::login))
-        .route("/auth/external", post(auth::external_login));
+        .route("/auth/external", post(auth::external_login))
+        .layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
+        .route("/health", get(health));
     
-    // Protected routes
+    // Protected routes. Every GET route also answers HEAD with the same status and
+    // headers (ETag included) and no body, so there are no separate HEAD routes.
     let protected_routes = Router::new()
         .route("/auth/me", get(auth::me))
+        .route("/me/sessions", get(handlers::sessions::list_my_sessions))
         // Service account endpoints
         .route("/service-accounts", get(handlers::service_accounts::list_service_accounts))
         .route("/service-accounts", post(handlers::service_accounts::create_service_account))
@@ -30,6 +37,7 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/service-accounts/{id}", put(handlers::service_accounts::update_service_account))
         .route("/service-accounts/{id}", delete(handlers::service_accounts::delete_service_account))
         .route("/service-accounts/{id}/password", put(handlers::service_accounts::update_service_account_password))
+        .route("/service-accounts/{id}/role-bindings", get(handlers::service_accounts::get_service_account_role_bindings))
         // Role endpoints
         .route("/roles", get(handlers::roles::list_roles))
         .route("/roles", post(handlers::roles::create_role))
@@ -38,34 +46,88 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // Role binding endpoints
         .route("/role-bindings", get(handlers::role_bindings::list_role_bindings))
         .route("/role-bindings", post(handlers::role_bindings::create_role_binding))
+        .route("/role-bindings/bulk", post(handlers::role_bindings::create_role_bindings_bulk))
         .route("/role-bindings/{id}", get(handlers::role_bindings::get_role_binding))
         .route("/role-bindings/{id}", delete(handlers::role_bindings::delete_role_binding))
+        .route("/role-bindings/by-id/{id}", delete(handlers::role_bindings::delete_role_binding_by_id))
         // Agent endpoints
         .route("/agents", get(handlers::agents::list_agents))
         .route("/agents", post(handlers::agents::create_agent))
         .route("/agents/{id}", get(handlers::agents::get_agent))
         .route("/agents/{id}", put(handlers::agents::update_agent))
         .route("/agents/{id}", delete(handlers::agents::delete_agent))
+        .route("/agents/{id}/versions", get(handlers::agents::list_agent_versions))
+        .route("/agents/{id}/test", post(handlers::agents::test_agent))
+        .route("/agents/{id}/versions/{revision}/restore", post(handlers::agents::restore_agent_version))
+        // Secret endpoints
+        .route("/secrets", get(handlers::secrets::list_secrets))
+        .route("/secrets", post(handlers::secrets::create_secret))
+        .route("/secrets/{name}", get(handlers::secrets::get_secret))
+        .route("/secrets/{name}", put(handlers::secrets::update_secret))
+        .route("/secrets/{name}", delete(handlers::secrets::delete_secret))
+        // Workspace endpoints
+        .route("/workspaces/{workspace}/settings", get(handlers::workspaces::get_workspace_settings))
+        .route("/workspaces/{workspace}/settings", put(handlers::workspaces::update_workspace_settings))
+        // Admin endpoints
+        .route("/admin/containers", get(handlers::containers::list_containers))
+        .route("/admin/containers/reconcile", post(handlers::containers::reconcile_containers))
+        .route("/admin/images", get(handlers::images::list_images))
+        .route("/admin/images/pull", post(handlers::images::pull_image))
+        .route("/admin/read-only", get(handlers::maintenance::get_read_only))
+        .route("/admin/read-only", put(handlers::maintenance::set_read_only))
+        .route("/admin/sessions/reap-idle", post(handlers::maintenance::reap_idle_sessions))
         // Session endpoints
         .route("/sessions", get(handlers::sessions::list_sessions))
         .route("/sessions", post(handlers::sessions::create_session))
+        .route("/sessions/import", post(handlers::sessions::import_session))
         .route("/sessions/{id}", get(handlers::sessions::get_session))
         .route("/sessions/{id}", put(handlers::sessions::update_session))
         .route("/sessions/{id}/state", put(handlers::sessions::update_session_state))
+        .route("/sessions/{id}/heartbeat", post(handlers::sessions::heartbeat_session))
+        .route("/sessions/{id}/cancel", post(handlers::sessions::cancel_session))
+        .route("/sessions/{id}/pause", post(handlers::sessions::pause_session))
+        .route("/sessions/{id}/resume", post(handlers::sessions::resume_session))
+        .route("/sessions/{id}/agents", get(handlers::sessions::list_session_agents))
+        .route("/sessions/{id}/agents", post(handlers::sessions::attach_session_agent))
+        .route("/sessions/{id}/agents/{agent_id}", delete(handlers::sessions::detach_session_agent))
+        .route("/sessions/{id}/config", get(handlers::sessions::get_session_config))
+        .route("/sessions/{id}/timeline", get(handlers::sessions::get_session_timeline))
+        .route("/sessions/{id}/tasks", get(handlers::sessions::list_session_tasks))
+        .route("/sessions/{id}/export", get(handlers::sessions::export_session))
+        .route("/sessions/{id}/logs", get(handlers::sessions::get_session_logs))
         .route("/sessions/{id}/remix", post(handlers::sessions::remix_session))
+        .route("/sessions/{id}/status", get(handlers::sessions::get_session_status))
+        .route("/sessions/{id}/tree", get(handlers::sessions::get_session_tree))
+        .route("/sessions/{id}/shell", get(handlers::shell::session_shell))
+        .route("/sessions/{id}/files", get(handlers::files::read_session_file))
+        .route("/sessions/{id}/files", put(handlers::files::write_session_file))
+        .route("/sessions/{id}/transfer", post(handlers::sessions::transfer_session))
         .route("/sessions/{id}", delete(handlers::sessions::delete_session))
         // Message endpoints
         .route("/sessions/{id}/messages", get(handlers::messages::list_messages))
         .route("/sessions/{id}/messages", post(handlers::messages::create_message))
+        .route("/sessions/{id}/messages/batch", post(handlers::messages::create_messages_batch))
+        .route("/sessions/{id}/messages/system", post(handlers::messages::create_system_message))
+        .route("/sessions/{id}/messages/latest", get(handlers::messages::get_latest_message))
         .route("/sessions/{id}/messages/count", get(handlers::messages::get_message_count))
         .route("/sessions/{id}/messages", delete(handlers::messages::clear_messages))
+        // Usage endpoints
+        .route("/sessions/{id}/usage", get(handlers::usage::get_usage))
+        .route("/sessions/{id}/usage", post(handlers::usage::record_usage))
+        // Runs after authentication, which it needs to identify the principal
+        .layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
         .layer(middleware::from_fn_with_state(state.clone(), auth_middleware));
 
-    let api_routes = public_routes.merge(protected_routes).with_state(state.clone());
+    let spec = api_spec(state.config.server.public_url.as_deref());
+    let api_routes = public_routes
+        .merge(protected_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), read_only_middleware))
+        .with_state(state.clone());
 
     Router::new()
         .nest("/api/v0", api_routes)
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", spec))
+        .layer(middleware::from_fn(api_version_middleware))
         .layer(middleware::from_fn(request_logging_middleware))
         .layer(TraceLayer::new_for_http())
 }
@@ -74,9 +136,66 @@ async fn health() -> StatusCode {
     StatusCode::OK
 }
 
-async fn version() -> axum::Json<serde_json::Value> {
-    axum::Json(serde_json::json!({
-        "version": "0.1.0",
-        "api": "v0"
-    }))
-}
\ No newline at end of file
+async fn openapi_json(State(state): State<Arc<AppState>>) -> axum::Json<utoipa::openapi::OpenApi> {
+    axum::Json(api_spec(state.config.server.public_url.as_deref()))
+}
+
+/// Build metadata for the running binary, captured by build.rs
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct VersionResponse {
+    pub version: String,
+    pub api: String,
+    pub git_sha: String,
+    pub build_time: String,
+    pub rustc_version: String,
+}
+
+async fn version() -> axum::Json<VersionResponse> {
+    let build_time = env!("RAWORC_BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    axum::Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        api: API_VERSION.to_string(),
+        git_sha: env!("RAWORC_GIT_SHA").to_string(),
+        build_time,
+        rustc_version: env!("RAWORC_RUSTC_VERSION").to_string(),
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::rest::test_support::{body_json, TestApp};
+    use axum::http::Method;
+
+    #[tokio::test]
+    async fn version_reports_the_build() {
+        let axum::Json(response) = version().await;
+
+        assert_eq!(response.version, env!("CARGO_PKG_VERSION"));
+        assert!(!response.version.is_empty());
+        assert_eq!(response.api, API_VERSION);
+        assert!(!response.git_sha.is_empty());
+        assert!(!response.rustc_version.is_empty());
+        assert!(response.build_time == "unknown" || chrono::DateTime::parse_from_rfc3339(&response.build_time).is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn the_spec_is_served_with_the_crate_version_and_server_url() {
+        let app = TestApp::with_config(|config| config.server.public_url = Some("https://raworc.example.com/".to_string())).await;
+
+        // No token needed
+        let response = app.request(Method::GET, "/api/v0/openapi.json", "", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let spec = body_json(response).await;
+        assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(spec["servers"][0]["url"], "https://raworc.example.com");
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        assert!(spec["paths"]["/api/v0/sessions"].is_object());
+    }
+}
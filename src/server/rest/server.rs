@@ -1,11 +1,14 @@
 use anyhow::Result;
 use std::fs;
+use std::net::SocketAddr;
 use std::process;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
-use crate::shared::{init_database, seed_rbac_system};
+use crate::server::auth::JwtKeySet;
+use crate::shared::{bootstrap_admin, init_database, seed_rbac_system, Config, Service};
 use crate::server::rest::create_router;
+use crate::server::rest::tls::{load_server_config, serve_tls};
 
 pub async fn run_rest_server() -> Result<()> {
     // Load .env file if it exists
@@ -42,20 +45,23 @@ PID: {}
         pid
     );
 
+    let config = Config::from_env(Service::Server).map_err(|e| {
+        error!("{}", e);
+        anyhow::anyhow!(e)
+    })?;
+    config.log_effective(Service::Server);
+
+    let jwt_keys = JwtKeySet::new(config.server.jwt_secret.clone(), config.server.jwt_previous_secrets.clone())
+        .with_claims(config.server.jwt_issuer.clone(), config.server.jwt_audience.clone());
+    let host = config.server.host.clone();
+    let port = config.server.port;
+    let tls = config.server.tls.as_ref().map(load_server_config).transpose()?;
+    let scheme = if tls.is_some() { "https" } else { "http" };
+
     // Initialize database connection and app state
     info!("Connecting to PostgreSQL database...");
     
-    // Get environment variables or use defaults
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "postgresql://postgres@localhost/raworc".to_string());
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "super-secret-key".to_string());
-    let host = std::env::var("RAWORC_HOST")
-        .unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = std::env::var("RAWORC_PORT")
-        .unwrap_or_else(|_| "9000".to_string());
-    
-    let app_state = match init_database(&database_url, jwt_secret).await {
+    let app_state = match init_database(Arc::new(config), jwt_keys).await {
         Ok(state) => {
             info!("Connected to database successfully!");
             Arc::new(state)
@@ -85,16 +91,71 @@ PID: {}
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
 
     info!("Server started successfully!");
-    info!("REST API Endpoint: http://{}:{}/api/v0", host, port);
-    info!("Swagger UI: http://{}:{}/swagger-ui/", host, port);
-    info!("OpenAPI JSON: http://{}:{}/api-docs/openapi.json", host, port);
+    info!("REST API Endpoint: {}://{}:{}/api/v0", scheme, host, port);
+    info!("Swagger UI: {}://{}:{}/swagger-ui/", scheme, host, port);
+    info!("OpenAPI JSON: {}://{}:{}/api/v0/openapi.json", scheme, host, port);
     info!("Ready to accept requests...");
 
-    let result = axum::serve(listener, app).await;
+    let result = match tls {
+        Some(tls) => serve_tls(listener, app, tls, shutdown_signal()).await,
+        None => axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown_signal()).await,
+    };
 
     // Clean up PID file on exit
     let _ = fs::remove_file(pid_file);
 
     result?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Completes on Ctrl-C or SIGTERM, e.g. from `docker stop`
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutdown signal received");
+}
+
+/// Create the initial admin account for `raworc bootstrap-admin`
+pub async fn run_bootstrap_admin(user: &str, password: &str, force: bool) -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let config = Config::from_env(Service::Admin).map_err(|e| {
+        error!("{}", e);
+        anyhow::anyhow!(e)
+    })?;
+    config.log_effective(Service::Admin);
+
+    let app_state = init_database(Arc::new(config), JwtKeySet::new(String::new(), Vec::new()))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+
+    bootstrap_admin(&app_state, user, password, force)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    println!("Admin account '{}' is ready", user);
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! Router tests against a real database. They need `DATABASE_URL` to point at a Postgres the
+//! server may migrate, so they're `#[ignore]`d by default: run them with
+//! `DATABASE_URL=... cargo test -- --ignored`.
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, Response},
+    Router,
+};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use std::sync::{Arc, Once};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::server::auth::{create_service_account_jwt, create_subject_jwt, JwtKeySet};
+use crate::server::rbac::{Role, RoleBinding, Rule, SubjectType};
+use crate::server::rest::create_router;
+use crate::shared::host_token;
+use crate::shared::models::Session;
+use crate::shared::{init_database, seed_rbac_system, AppState, Config, Service};
+
+pub struct TestApp {
+    pub state: Arc<AppState>,
+    router: Router,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    /// App whose configuration `configure` adjusts after it is read from the environment
+    pub async fn with_config(configure: impl FnOnce(&mut Config)) -> Self {
+        static ENV: Once = Once::new();
+        ENV.call_once(|| {
+            if std::env::var_os("RAWORC_JWT_SECRET").is_none() && std::env::var_os("JWT_SECRET").is_none() {
+                std::env::set_var("RAWORC_JWT_SECRET", "test-secret-that-is-long-enough-for-hs256");
+            }
+            if std::env::var_os("RAWORC_SECRETS_KEY").is_none() {
+                // 32 zero bytes, base64
+                std::env::set_var("RAWORC_SECRETS_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+            }
+        });
+
+        let mut config = Config::from_env(Service::Server).expect("test configuration");
+        configure(&mut config);
+        let jwt_keys = JwtKeySet::new(config.server.jwt_secret.clone(), Vec::new())
+            .with_claims(config.server.jwt_issuer.clone(), config.server.jwt_audience.clone());
+        let state = Arc::new(init_database(Arc::new(config), jwt_keys).await.expect("test database"));
+        seed_rbac_system(&state).await.expect("seeded admin");
+
+        Self {
+            router: create_router(state.clone()),
+            state,
+        }
+    }
+
+    /// Token for the seeded `admin` service account, bound to the admin role
+    pub fn admin_token(&self) -> String {
+        let admin = crate::server::rbac::ServiceAccount {
+            id: None,
+            user: "admin".to_string(),
+            pass_hash: String::new(),
+            description: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            last_login_at: None,
+            active: true,
+        };
+        create_service_account_jwt(&admin, &self.state.jwt_keys, 1).unwrap().token
+    }
+
+    /// Token for a subject with no role bindings
+    pub fn user_token(&self, name: &str) -> String {
+        create_subject_jwt(name, &self.state.jwt_keys, 1).unwrap().token
+    }
+
+    pub async fn request(&self, method: Method, uri: &str, token: &str, body: Option<serde_json::Value>) -> Response<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token));
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        self.send(request.unwrap()).await
+    }
+
+    /// POST a non-JSON body, e.g. a session export
+    pub async fn post_text(&self, uri: &str, token: &str, content_type: &str, body: String) -> Response<Body> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap();
+        self.send(request).await
+    }
+
+    /// Serve the app on a local port for clients that need a real connection, e.g. WebSockets
+    pub async fn listen(&self) -> String {
+        serve(self.router.clone()).await
+    }
+
+    /// Send a request built by the test, e.g. one with extra headers
+    pub async fn send(&self, request: Request<Body>) -> Response<Body> {
+        self.router.clone().oneshot(request).await.unwrap()
+    }
+
+    /// Bind a fresh role allowing `verbs` on the `api` group's `resource` to the subject `name`,
+    /// in `workspace` or globally
+    pub async fn grant(&self, name: &str, workspace: Option<&str>, resource: &str, verbs: &[&str]) {
+        let role = Role {
+            id: None,
+            name: unique("role"),
+            rules: vec![Rule {
+                api_groups: vec!["api".to_string()],
+                resources: vec![resource.to_string()],
+                verbs: verbs.iter().map(|verb| verb.to_string()).collect(),
+                resource_names: None,
+            }],
+            description: None,
+            created_at: String::new(),
+        };
+        self.state.create_role(&role).await.unwrap();
+        let binding = RoleBinding {
+            id: None,
+            role_name: role.name,
+            principal_name: name.to_string(),
+            principal_type: SubjectType::Subject,
+            workspace: workspace.map(str::to_string),
+            created_at: String::new(),
+        };
+        self.state.create_role_binding(&binding).await.unwrap();
+    }
+
+    /// Insert a session in the default workspace owned by `created_by`
+    pub async fn create_session(&self, created_by: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO sessions (name, workspace, starting_prompt, created_by) VALUES ($1, 'default', 'test', $2) RETURNING id",
+        )
+        .bind(format!("test-{}", Uuid::new_v4()))
+        .bind(created_by)
+        .fetch_one(&*self.state.db)
+        .await
+        .unwrap()
+    }
+
+    /// Issue a host token for `session_id`, as the operator does when it starts the container
+    pub async fn host_token(&self, session_id: Uuid) -> String {
+        let token = host_token::generate(session_id).unwrap();
+        Session::set_host_token_hash(&self.state.db, session_id, &host_token::hash(&token))
+            .await
+            .unwrap();
+        token
+    }
+
+    /// Create an empty database on the test database's server, for tests that need a clean slate
+    pub async fn scratch_database(&self) -> ScratchDatabase {
+        let name = format!("raworc_scratch_{}", Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE DATABASE {}", name)).execute(&*self.state.db).await.unwrap();
+        let options = self.state.config.database_url.parse::<PgConnectOptions>().unwrap().database(&name);
+        let pool = PgPoolOptions::new().max_connections(2).connect_with(options).await.unwrap();
+        ScratchDatabase {
+            pool,
+            name,
+            server: self.state.db.clone(),
+        }
+    }
+
+    /// Insert a message with the given role, e.g. `USER`
+    pub async fn add_message(&self, session_id: Uuid, role: &str, content: &str) {
+        sqlx::query("INSERT INTO session_messages (session_id, role, content) VALUES ($1, $2::message_role, $3)")
+            .bind(session_id)
+            .bind(role)
+            .bind(content)
+            .execute(&*self.state.db)
+            .await
+            .unwrap();
+    }
+}
+
+/// Database made by [`TestApp::scratch_database`]; call `remove` once done with it
+pub struct ScratchDatabase {
+    pub pool: PgPool,
+    name: String,
+    server: Arc<PgPool>,
+}
+
+impl ScratchDatabase {
+    pub async fn remove(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP DATABASE {}", self.name)).execute(&*self.server).await.unwrap();
+    }
+}
+
+pub async fn body_bytes(response: Response<Body>) -> Vec<u8> {
+    to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+}
+
+pub async fn body_json(response: Response<Body>) -> serde_json::Value {
+    serde_json::from_slice(&body_bytes(response).await).unwrap()
+}
+
+/// Serve `router` on a local port, e.g. in place of an operator's node API, returning its URL
+pub async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    url
+}
+
+/// Name unique to one test run, for fixtures that must not collide
+pub fn unique(prefix: &str) -> String {
+    format!("{}-{}", prefix, &Uuid::new_v4().simple().to_string()[..8])
+}
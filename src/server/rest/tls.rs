@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, Request},
+    Router,
+};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+use crate::shared::config::TlsConfig;
+
+/// How long a client may take to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client certificate verified during the TLS handshake, attached to each request on the connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// The subject's common name, which becomes the request's principal
+    pub common_name: String,
+}
+
+impl ClientCertificate {
+    /// Read the subject CN from a DER certificate; None when it has none or can't be parsed
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+        let common_name = cert.subject().iter_common_name().next()?.as_str().ok()?.trim();
+        (!common_name.is_empty()).then(|| Self {
+            common_name: common_name.to_string(),
+        })
+    }
+}
+
+/// Build the rustls config from the configured PEM files. With a client CA, the handshake
+/// fails for clients that don't present a certificate chaining to it.
+pub fn load_server_config(config: &TlsConfig) -> Result<rustls::ServerConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let certs = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read TLS certificate {}", config.cert_path))?;
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .with_context(|| format!("Failed to read TLS key {}", config.key_path))?;
+
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    let builder = match &config.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca_path)
+                .with_context(|| format!("Failed to read client CA {}", ca_path))?
+            {
+                roots.add(cert.with_context(|| format!("Failed to read client CA {}", ca_path))?)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder.with_single_cert(certs, key)?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(server_config)
+}
+
+/// Serve `app` over TLS, like `axum::serve` does over plain TCP. Once `shutdown` completes,
+/// no new connections are accepted and open ones finish their in-flight requests.
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    config: rustls::ServerConfig,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    // Connections hold a receiver; the sender sees them all gone once they have closed
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let mut shutdown_rx = shutdown_rx.clone();
+
+        tokio::spawn(async move {
+            // Clients that stall the handshake would otherwise hold the task forever
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+                Err(_) => {
+                    debug!("TLS handshake with {} timed out", peer);
+                    return;
+                }
+            };
+
+            let client_certificate = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(|cert| ClientCertificate::from_der(cert));
+
+            let service = app.map_request(move |mut request: Request<_>| {
+                if let Some(cert) = &client_certificate {
+                    request.extensions_mut().insert(cert.clone());
+                }
+                request.extensions_mut().insert(ConnectInfo(peer));
+                request
+            });
+
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(service));
+            tokio::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = shutdown_rx.changed() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(e) = result {
+                debug!("Connection from {} ended with an error: {}", peer, e);
+            }
+        });
+    }
+
+    info!("Shutting down; waiting for open connections to finish");
+    drop(shutdown_rx);
+    let _ = shutdown_tx.send(true);
+    shutdown_tx.closed().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::pki_types::{pem::PemObject, CertificateDer};
+
+    use super::ClientCertificate;
+
+    /// Self-signed, subject `O=Raworc Test, CN=ci-deployer`
+    const WITH_CN: &str = "-----BEGIN CERTIFICATE-----
+MIIBrzCCAVWgAwIBAgIURdb6jDvVbnS7kHsvbIAi2yWR+FkwCgYIKoZIzj0EAwIw
+LDEUMBIGA1UECgwLUmF3b3JjIFRlc3QxFDASBgNVBAMMC2NpLWRlcGxveWVyMCAX
+DTI2MTAxNjE0MDQyOFoYDzIxMjYwOTIyMTQwNDI4WjAsMRQwEgYDVQQKDAtSYXdv
+cmMgVGVzdDEUMBIGA1UEAwwLY2ktZGVwbG95ZXIwWTATBgcqhkjOPQIBBggqhkjO
+PQMBBwNCAARY3iLcFdj+qQ/MvSLmu8EVzSd1bfVpaj9udeh8kCHvsJA6jQsDfxZC
+G861SqNoOt14wTNQeLYSiZhrPEE2jR0So1MwUTAdBgNVHQ4EFgQULA0o3hljH8AI
+9lz4HVRVKuBMuj8wHwYDVR0jBBgwFoAULA0o3hljH8AI9lz4HVRVKuBMuj8wDwYD
+VR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiAurTVZWSvKTNeFN1ehQpN5
++H+UPyLt9wqkCb0+ANQiJwIhAK5Rc4KEOjCAw8cfkN28RdeMMSVQgcEYOA4FdxDJ
+qdQh
+-----END CERTIFICATE-----";
+
+    /// Self-signed, subject `O=Raworc Test` only
+    const WITHOUT_CN: &str = "-----BEGIN CERTIFICATE-----
+MIIBgzCCASmgAwIBAgIUSb8fuNV+PlczZwGjzf1pSEx1oiYwCgYIKoZIzj0EAwIw
+FjEUMBIGA1UECgwLUmF3b3JjIFRlc3QwIBcNMjYxMDE2MTQwNDI4WhgPMjEyNjA5
+MjIxNDA0MjhaMBYxFDASBgNVBAoMC1Jhd29yYyBUZXN0MFkwEwYHKoZIzj0CAQYI
+KoZIzj0DAQcDQgAEULlnBdnfSzIhAJVKBH4HcuKRqlb8bGHLE46IOScTdMfWC28G
+anzubYpvfL8nzoVa4md3+mc7Rss2zNJ1SuYtDqNTMFEwHQYDVR0OBBYEFCSMNRca
+Zd9yu/EKkln7apk+0aNvMB8GA1UdIwQYMBaAFCSMNRcaZd9yu/EKkln7apk+0aNv
+MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIgAe9VFO6a0iV6V8F6
+3H+Jg00aXhbk1Hg6kXmPtkWKiZ8CIQCa5p+kqAyxor9d4wzunL3D9d+3If2L9HZa
+nFy2MdHjvQ==
+-----END CERTIFICATE-----";
+
+    fn der(pem: &str) -> Vec<u8> {
+        CertificateDer::from_pem_slice(pem.as_bytes()).unwrap().to_vec()
+    }
+
+    #[test]
+    fn client_certificate_common_name_is_the_principal() {
+        assert_eq!(
+            ClientCertificate::from_der(&der(WITH_CN)),
+            Some(ClientCertificate {
+                common_name: "ci-deployer".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn client_certificate_without_common_name_is_ignored() {
+        assert_eq!(ClientCertificate::from_der(&der(WITHOUT_CN)), None);
+        assert_eq!(ClientCertificate::from_der(b"not a certificate"), None);
+    }
+}
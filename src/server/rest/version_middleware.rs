@@ -0,0 +1,93 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::server::rest::error::ApiError;
+
+/// Version of the REST API served by this build
+pub const API_VERSION: &str = "v0";
+
+/// Versions a client may request through `Accept-Version`
+pub const SUPPORTED_API_VERSIONS: &[&str] = &[API_VERSION];
+
+pub static API_VERSION_HEADER: HeaderName = HeaderName::from_static("x-api-version");
+pub static ACCEPT_VERSION_HEADER: HeaderName = HeaderName::from_static("accept-version");
+
+/// Whether an `Accept-Version` value names a version this server can serve.
+/// Accepts `v0` or `0`, case-insensitively.
+fn is_supported_version(requested: &str) -> bool {
+    let requested = requested.trim().to_ascii_lowercase();
+    let requested = requested.strip_prefix('v').unwrap_or(&requested);
+    SUPPORTED_API_VERSIONS
+        .iter()
+        .any(|supported| supported.trim_start_matches('v') == requested)
+}
+
+/// Stamp every response with `X-Api-Version`, and reject requests whose
+/// `Accept-Version` names a version this server does not support with 406.
+pub async fn api_version_middleware(request: Request, next: Next) -> Response {
+    let requested = request
+        .headers()
+        .get(&ACCEPT_VERSION_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().to_string());
+
+    let mut response = match requested {
+        Some(version) if !is_supported_version(&version) => ApiError::NotAcceptable(format!(
+            "API version '{}' is not supported; supported versions: {}",
+            version,
+            SUPPORTED_API_VERSIONS.join(", ")
+        ))
+        .into_response(),
+        _ => next.run(request).await,
+    };
+
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER.clone(), HeaderValue::from_static(API_VERSION));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    async fn get_with(accept_version: Option<&str>) -> Response {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(middleware::from_fn(api_version_middleware));
+        let mut request = Request::builder().uri("/");
+        if let Some(version) = accept_version {
+            request = request.header(&ACCEPT_VERSION_HEADER, version);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn every_response_names_the_api_version() {
+        for accept_version in [None, Some("v0"), Some("0"), Some(" V0 ")] {
+            let response = get_with(accept_version).await;
+            assert_eq!(response.status(), StatusCode::OK, "{:?}", accept_version);
+            assert_eq!(response.headers()[&API_VERSION_HEADER], API_VERSION);
+        }
+    }
+
+    #[tokio::test]
+    async fn unsupported_versions_are_not_acceptable() {
+        let response = get_with(Some("v1")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        // Even the rejection says which version answered
+        assert_eq!(response.headers()[&API_VERSION_HEADER], API_VERSION);
+    }
+}
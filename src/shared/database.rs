@@ -1,8 +1,15 @@
+use crate::shared::config::{Config, DatabaseConfig};
 use crate::shared::models::{AppState, DatabaseError};
-use crate::server::rbac::{Role, RoleBinding, ServiceAccount, SubjectType};
+use crate::shared::secrets::SecretsCipher;
+use crate::server::auth::JwtKeySet;
+use crate::shared::rate_limit::RateLimiter;
+use crate::server::rbac::{AuthPrincipal, BindingScope, Role, RoleBinding, ServiceAccount, SubjectType};
 use chrono::Utc;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use sqlx::{query, Row};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use sqlx::{postgres::PgPoolOptions, query, Pool, Postgres, Row};
 use uuid::Uuid;
 use tracing::{info, warn};
 
@@ -96,6 +103,45 @@ impl AppState {
         }).collect())
     }
 
+    /// Page through service accounts, optionally keeping only names containing `name_contains`
+    /// (case-insensitive). No limit returns every match, as `get_all_service_accounts` does.
+    pub async fn find_service_accounts(
+        &self,
+        name_contains: Option<&str>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<ServiceAccount>, DatabaseError> {
+        let limit = limit.map(|limit| limit.clamp(1, 1000));
+        let offset = offset.unwrap_or(0).max(0);
+
+        let rows = query(
+            r#"
+            SELECT id, name, password_hash, description, created_at, updated_at, active, last_login_at
+            FROM service_accounts
+            WHERE $1::text IS NULL OR strpos(lower(name), lower($1)) > 0
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(name_contains)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&*self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| ServiceAccount {
+            id: Some(r.get("id")),
+            user: r.get("name"),
+            pass_hash: r.get("password_hash"),
+            description: r.get("description"),
+            created_at: r.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+            updated_at: r.get::<chrono::DateTime<chrono::Utc>, _>("updated_at").to_rfc3339(),
+            active: r.get("active"),
+            last_login_at: r.get::<Option<chrono::DateTime<chrono::Utc>>, _>("last_login_at")
+                .map(|dt| dt.to_rfc3339()),
+        }).collect())
+    }
+
     pub async fn delete_service_account(
         &self,
         user: &str,
@@ -364,6 +410,40 @@ impl AppState {
         }).collect())
     }
 
+    /// Page through roles, optionally keeping only names containing `name_contains` (case-insensitive)
+    pub async fn find_roles(
+        &self,
+        name_contains: Option<&str>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Role>, DatabaseError> {
+        let limit = limit.map(|limit| limit.clamp(1, 1000));
+        let offset = offset.unwrap_or(0).max(0);
+
+        let rows = query(
+            r#"
+            SELECT id, name, rules, description, created_at
+            FROM roles
+            WHERE $1::text IS NULL OR strpos(lower(name), lower($1)) > 0
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(name_contains)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&*self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| Role {
+            id: Some(r.get("id")),
+            name: r.get("name"),
+            rules: serde_json::from_value(r.get("rules")).unwrap_or_default(),
+            description: r.get("description"),
+            created_at: r.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+        }).collect())
+    }
+
     pub async fn delete_role(
         &self,
         name: &str,
@@ -382,36 +462,56 @@ impl AppState {
     }
 
     // Role Binding operations
+    /// Create a binding; None when an identical one already exists
     pub async fn create_role_binding(
         &self,
         role_binding: &RoleBinding,
-    ) -> Result<RoleBinding, DatabaseError> {
-        let id = Uuid::new_v4();
-        
-        // Convert SubjectType enum to string for database
-        let principal_type_str = match role_binding.principal_type {
-            SubjectType::ServiceAccount => "ServiceAccount",
-            SubjectType::Subject => "User",
-        };
-        
-        query(
-            r#"
-            INSERT INTO role_bindings (id, role_name, principal_name, principal_type, workspace)
-            VALUES ($1, $2, $3, $4, $5)
-            "#
-        )
-        .bind(id)
-        .bind(&role_binding.role_name)
-        .bind(&role_binding.principal_name)
-        .bind(principal_type_str)
-        .bind(&role_binding.workspace)
-        .execute(&*self.db)
-        .await?;
+    ) -> Result<Option<RoleBinding>, DatabaseError> {
+        Ok(insert_role_binding(&*self.db, role_binding).await?)
+    }
 
-        Ok(RoleBinding {
-            id: Some(id),
-            ..role_binding.clone()
-        })
+    /// Create several bindings atomically. Bindings that already exist are left alone and
+    /// returned with `false`; new ones are returned with `true`.
+    pub async fn create_role_bindings(
+        &self,
+        role_bindings: &[RoleBinding],
+    ) -> Result<Vec<(RoleBinding, bool)>, DatabaseError> {
+        let mut tx = self.db.begin().await?;
+        let mut results = Vec::with_capacity(role_bindings.len());
+
+        for role_binding in role_bindings {
+            if let Some(created) = insert_role_binding(&mut *tx, role_binding).await? {
+                results.push((created, true));
+                continue;
+            }
+
+            let existing = query(
+                r#"
+                SELECT id, created_at
+                FROM role_bindings
+                WHERE role_name = $1 AND principal_name = $2 AND principal_type = $3
+                  AND workspace IS NOT DISTINCT FROM $4
+                LIMIT 1
+                "#
+            )
+            .bind(&role_binding.role_name)
+            .bind(&role_binding.principal_name)
+            .bind(principal_type_column(&role_binding.principal_type))
+            .bind(&role_binding.workspace)
+            .fetch_one(&mut *tx)
+            .await?;
+            results.push((
+                RoleBinding {
+                    id: Some(existing.get("id")),
+                    created_at: existing.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+                    ..role_binding.clone()
+                },
+                false,
+            ));
+        }
+
+        tx.commit().await?;
+        Ok(results)
     }
 
     pub async fn get_role_binding(
@@ -479,7 +579,49 @@ impl AppState {
         }).collect())
     }
 
-    #[allow(dead_code)]
+    /// Role bindings in `scope`, newest first. A workspace narrows the workspace-scoped
+    /// bindings to that workspace; global bindings are unaffected by it.
+    pub async fn get_role_bindings_in_scope(
+        &self,
+        scope: BindingScope,
+        workspace: Option<&str>,
+    ) -> Result<Vec<RoleBinding>, DatabaseError> {
+        let include_global = matches!(scope, BindingScope::Global | BindingScope::All);
+        let include_workspace = matches!(scope, BindingScope::Workspace | BindingScope::All);
+
+        let rows = query(
+            r#"
+            SELECT id, role_name, principal_name, principal_type, workspace, created_at
+            FROM role_bindings
+            WHERE (workspace IS NULL AND $1)
+               OR (workspace IS NOT NULL AND $2 AND ($3::text IS NULL OR workspace = $3))
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(include_global)
+        .bind(include_workspace)
+        .bind(workspace)
+        .fetch_all(&*self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| {
+            let principal_type_str: String = r.get("principal_type");
+            let principal_type = match principal_type_str.as_str() {
+                "ServiceAccount" => SubjectType::ServiceAccount,
+                _ => SubjectType::Subject,
+            };
+            
+            RoleBinding {
+                id: Some(r.get("id")),
+                role_name: r.get("role_name"),
+                principal_name: r.get("principal_name"),
+                principal_type,
+                workspace: r.get("workspace"),
+                created_at: r.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+            }
+        }).collect())
+    }
+
     pub async fn get_role_bindings_for_subject(
         &self,
         subject_name: &str,
@@ -557,24 +699,154 @@ impl AppState {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// Delete exactly one binding, leaving others with the same role name untouched
+    pub async fn delete_role_binding_by_id(&self, id: Uuid) -> Result<bool, DatabaseError> {
+        let result = query("DELETE FROM role_bindings WHERE id = $1")
+            .bind(id)
+            .execute(&*self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Principal lookup
+    /// Whether a principal is known: an active service account, or a user with at least
+    /// one role binding (users have no table of their own)
+    pub async fn principal_exists(&self, name: &str) -> Result<bool, DatabaseError> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM service_accounts WHERE name = $1 AND active = true)
+                OR EXISTS (SELECT 1 FROM role_bindings WHERE principal_name = $1)
+            "#
+        )
+        .bind(name)
+        .fetch_one(&*self.db)
+        .await?;
+
+        Ok(exists)
+    }
+
+    // Audit log
+    pub async fn record_audit_event(
+        &self,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<Uuid>,
+        actor: &AuthPrincipal,
+        details: serde_json::Value,
+    ) -> Result<(), DatabaseError> {
+        let actor_type = match actor.subject_type() {
+            SubjectType::Subject => "User",
+            SubjectType::ServiceAccount => "ServiceAccount",
+        };
+
+        query(
+            r#"
+            INSERT INTO audit_log (action, entity_type, entity_id, actor, actor_type, details)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(action)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(actor.name())
+        .bind(actor_type)
+        .bind(&details)
+        .execute(&*self.db)
+        .await?;
+
+        Ok(())
+    }
 }
 
 // Database connection utilities
+
+/// Build connection pool options from the database settings
+pub fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    let mut options = PgPoolOptions::new().max_connections(config.max_connections);
+
+    if let Some(min_connections) = config.min_connections {
+        options = options.min_connections(min_connections);
+    }
+
+    if let Some(timeout) = config.acquire_timeout {
+        options = options.acquire_timeout(timeout);
+    }
+
+    if let Some(timeout) = config.idle_timeout {
+        options = options.idle_timeout(timeout);
+    }
+
+    options
+}
+
+/// Connect to the database, retrying with exponential backoff so startup tolerates
+/// Postgres coming up after us. Gives up after `max_attempts`.
+pub async fn connect_with_retry(options: PgPoolOptions, database_url: &str, max_attempts: u64) -> anyhow::Result<Pool<Postgres>> {
+    retry_with_backoff(max_attempts, Duration::from_secs(1), || options.clone().connect(database_url)).await
+}
+
+/// Call `connect` until it succeeds, doubling the delay between attempts from `first_delay`
+/// up to 30 seconds
+async fn retry_with_backoff<T, E, F, Fut>(max_attempts: u64, first_delay: Duration, mut connect: F) -> anyhow::Result<T>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    let max_delay = Duration::from_secs(30);
+    let mut delay = first_delay;
+    let mut attempt = 1;
+
+    loop {
+        match connect().await {
+            Ok(pool) => {
+                if attempt > 1 {
+                    info!("Connected to database after {} attempts", attempt);
+                }
+                return Ok(pool);
+            }
+            Err(e) if attempt < max_attempts => {
+                warn!(
+                    "Database connection attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt, max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(max_delay);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to connect to database after {} attempts: {}",
+                    attempt, e
+                ));
+            }
+        }
+    }
+}
+
+/// The deployment id stamped on managed containers: the configured one if set, otherwise
+/// the id generated by the migrations
+pub async fn resolve_instance_id(pool: &Pool<Postgres>, configured: Option<&str>) -> Result<String, sqlx::Error> {
+    if let Some(id) = configured {
+        return Ok(id.to_string());
+    }
+    sqlx::query_scalar("SELECT instance_id FROM raworc_instance")
+        .fetch_one(pool)
+        .await
+}
+
 pub async fn init_database(
-    database_url: &str,
-    jwt_secret: String,
+    config: Arc<Config>,
+    jwt_keys: JwtKeySet,
 ) -> Result<AppState, Box<dyn std::error::Error>> {
-    use sqlx::postgres::PgPoolOptions;
-    
     let db = Arc::new(
-        PgPoolOptions::new()
-            .max_connections(10)
-            .connect(database_url)
-            .await?,
+        connect_with_retry(pool_options(&config.database), &config.database_url, config.database.connect_attempts).await?,
     );
 
     // Run migrations (skip if SKIP_MIGRATIONS is set or if migrations fail)
-    if std::env::var("SKIP_MIGRATIONS").is_err() {
+    if !config.database.skip_migrations {
         info!("Running database migrations...");
         match sqlx::migrate!("./db").run(&*db).await {
             Ok(_) => info!("Database migrations completed successfully"),
@@ -598,12 +870,62 @@ pub async fn init_database(
         info!("Skipping migrations (SKIP_MIGRATIONS set)");
     }
 
+    let secrets = match &config.secrets_key {
+        Some(key) => Some(Arc::new(SecretsCipher::new(key)?)),
+        None => None,
+    };
+
+    let read_only = Arc::new(AtomicBool::new(config.server.read_only));
+    let rate_limiter = config.server.rate_limit.as_ref().map(|limit| Arc::new(RateLimiter::new(limit)));
+    let agent_tests = Arc::new(Semaphore::new(config.server.max_concurrent_agent_tests as usize));
+
     Ok(AppState {
         db,
-        jwt_secret,
+        jwt_keys,
+        secrets,
+        config,
+        read_only,
+        rate_limiter,
+        agent_tests,
     })
 }
 
+/// `principal_type` as stored in role_bindings
+fn principal_type_column(principal_type: &SubjectType) -> &'static str {
+    match principal_type {
+        SubjectType::ServiceAccount => "ServiceAccount",
+        SubjectType::Subject => "User",
+    }
+}
+
+/// Insert `role_binding` unless an identical binding exists, global ones included, returning
+/// it with its id; None when it already existed, including when a concurrent request won
+async fn insert_role_binding<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    role_binding: &RoleBinding,
+) -> Result<Option<RoleBinding>, sqlx::Error> {
+    let row = query(
+        r#"
+        INSERT INTO role_bindings (role_name, principal_name, principal_type, workspace)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        RETURNING id, created_at
+        "#
+    )
+    .bind(&role_binding.role_name)
+    .bind(&role_binding.principal_name)
+    .bind(principal_type_column(&role_binding.principal_type))
+    .bind(&role_binding.workspace)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(|row| RoleBinding {
+        id: Some(row.get("id")),
+        created_at: row.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+        ..role_binding.clone()
+    }))
+}
+
 // Database seeding for RBAC - only seeds if service_accounts table is empty
 pub async fn seed_rbac_system(app_state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
     use crate::server::rbac::{get_admin_role, RoleBinding, SubjectType};
@@ -646,8 +968,194 @@ pub async fn seed_rbac_system(app_state: &AppState) -> Result<(), Box<dyn std::e
         created_at: Utc::now().to_rfc3339(),
     };
 
-    let _created_binding = app_state.create_role_binding(&admin_role_binding).await?;
+    app_state.create_role_binding(&admin_role_binding).await?;
     info!("Admin role binding created");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Shortest password `bootstrap_admin` accepts
+pub const MIN_ADMIN_PASSWORD_LEN: usize = 8;
+
+/// Create the initial admin service account with the given password, the admin role and a
+/// global binding between them. Refuses when any account is already bound to the admin role
+/// unless `force` is set, in which case an existing account gets the new password and any
+/// missing role or binding is created.
+pub async fn bootstrap_admin(
+    app_state: &AppState,
+    user: &str,
+    password: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::server::rbac::get_admin_role;
+    use bcrypt::{hash, DEFAULT_COST};
+
+    if user.trim().is_empty() {
+        return Err("Admin user name cannot be empty".into());
+    }
+    if password.len() < MIN_ADMIN_PASSWORD_LEN {
+        return Err(format!("Admin password must be at least {} characters", MIN_ADMIN_PASSWORD_LEN).into());
+    }
+
+    let admin_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM role_bindings WHERE role_name = 'admin')")
+        .fetch_one(&*app_state.db)
+        .await?;
+    if admin_exists && !force {
+        return Err("An admin account already exists; pass --force to bootstrap anyway".into());
+    }
+
+    let pass_hash = hash(password, DEFAULT_COST)?;
+    if app_state.get_service_account(user).await?.is_some() {
+        app_state.update_service_account_password(user, &pass_hash).await?;
+        info!("Reset password of existing service account '{}'", user);
+    } else {
+        app_state
+            .create_service_account(user, None, &pass_hash, Some("Bootstrapped admin service account".to_string()))
+            .await?;
+        info!("Admin service account '{}' created", user);
+    }
+
+    let admin_role = get_admin_role();
+    if app_state.get_role(&admin_role.name).await?.is_none() {
+        app_state.create_role(&admin_role).await?;
+        info!("Admin role created");
+    }
+
+    let binding = RoleBinding {
+        id: None,
+        role_name: admin_role.name.clone(),
+        principal_name: user.to_string(),
+        principal_type: SubjectType::ServiceAccount,
+        workspace: None, // Global access
+        created_at: Utc::now().to_rfc3339(),
+    };
+    let created = app_state.create_role_bindings(&[binding]).await?;
+    if created.iter().any(|(_, created)| *created) {
+        info!("Admin role bound to '{}'", user);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn connecting_retries_until_the_database_is_reachable() {
+        let attempts = AtomicU32::new(0);
+
+        let connected = retry_with_backoff(5, Duration::from_millis(1), || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) + 1 {
+                attempt if attempt < 3 => Err(format!("attempt {} refused", attempt)),
+                attempt => Ok(attempt),
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(connected, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn connecting_gives_up_after_the_last_attempt() {
+        let attempts = AtomicU32::new(0);
+
+        let result: anyhow::Result<()> = retry_with_backoff(2, Duration::from_millis(1), || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err("refused")
+        })
+        .await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("after 2 attempts: refused"), "{}", error);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn bootstrapping_creates_the_admin_once() {
+        let app = crate::server::rest::test_support::TestApp::new().await;
+        let scratch = app.scratch_database().await;
+        sqlx::migrate!("./db").run(&scratch.pool).await.unwrap();
+        let state = AppState {
+            db: Arc::new(scratch.pool.clone()),
+            ..(*app.state).clone()
+        };
+
+        assert!(bootstrap_admin(&state, "root", "short", false).await.is_err());
+        bootstrap_admin(&state, "root", "first-password", false).await.unwrap();
+
+        let account = state.get_service_account("root").await.unwrap().expect("account is created");
+        assert!(account.active);
+        assert!(bcrypt::verify("first-password", &account.pass_hash).unwrap());
+        assert!(state.get_role("admin").await.unwrap().is_some());
+        let bindings = state.get_role_bindings_for_subject("root", SubjectType::ServiceAccount, None).await.unwrap();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].role_name, "admin");
+        assert_eq!(bindings[0].workspace, None);
+
+        // With an admin in place a second run is refused and changes nothing
+        let error = bootstrap_admin(&state, "other", "second-password", false).await.unwrap_err();
+        assert!(error.to_string().contains("--force"), "{}", error);
+        assert!(state.get_service_account("other").await.unwrap().is_none());
+
+        // Forcing resets the password without duplicating the binding
+        bootstrap_admin(&state, "root", "second-password", true).await.unwrap();
+        let account = state.get_service_account("root").await.unwrap().unwrap();
+        assert!(bcrypt::verify("second-password", &account.pass_hash).unwrap());
+        let bindings = state.get_role_bindings_for_subject("root", SubjectType::ServiceAccount, None).await.unwrap();
+        assert_eq!(bindings.len(), 1);
+
+        scratch.remove().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn a_clean_migration_creates_the_task_tables() {
+        let app = crate::server::rest::test_support::TestApp::new().await;
+        let scratch = app.scratch_database().await;
+        sqlx::migrate!("./db").run(&scratch.pool).await.unwrap();
+
+        let columns: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT table_name::text, column_name::text, is_nullable::text FROM information_schema.columns
+             WHERE table_schema = 'public' AND table_name IN ('session_tasks', 'command_results')",
+        )
+        .fetch_all(&scratch.pool)
+        .await
+        .unwrap();
+        let nullable = |table: &str, column: &str| {
+            columns
+                .iter()
+                .find(|(t, c, _)| t == table && c == column)
+                .map(|(_, _, nullable)| nullable == "YES")
+                .unwrap_or_else(|| panic!("{}.{} is missing", table, column))
+        };
+        for column in ["id", "task_type", "session_id", "payload", "status", "created_at", "updated_at"] {
+            assert!(!nullable("session_tasks", column), "session_tasks.{} is nullable", column);
+        }
+        assert!(nullable("session_tasks", "completed_at"));
+        for column in ["id", "session_id", "command", "created_at"] {
+            assert!(!nullable("command_results", column), "command_results.{} is nullable", column);
+        }
+
+        let indexes: Vec<String> = sqlx::query_scalar(
+            "SELECT indexname::text FROM pg_indexes WHERE tablename IN ('session_tasks', 'command_results')",
+        )
+        .fetch_all(&scratch.pool)
+        .await
+        .unwrap();
+        for index in [
+            "idx_session_tasks_type_status_created_at",
+            "idx_session_tasks_finished_completed_at",
+            "idx_command_results_session_created_at",
+        ] {
+            assert!(indexes.iter().any(|name| name == index), "{} is missing from {:?}", index, indexes);
+        }
+        assert!(!indexes.iter().any(|name| name == "idx_command_results_session_id"));
+
+        scratch.remove().await;
+    }
+}
@@ -0,0 +1,55 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use uuid::Uuid;
+
+/// Prefix telling host tokens apart from JWTs
+pub const HOST_TOKEN_PREFIX: &str = "rwh_";
+
+/// Token the host agent in a session's container authenticates with, of the form
+/// `rwh_<session id>_<secret>`. Only its hash is stored, so a database leak doesn't expose it.
+pub fn generate(session_id: Uuid) -> anyhow::Result<String> {
+    let mut secret = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| anyhow::anyhow!("Failed to generate host token"))?;
+    Ok(format!("{}{}_{}", HOST_TOKEN_PREFIX, session_id.simple(), URL_SAFE_NO_PAD.encode(secret)))
+}
+
+/// Stored form of a token
+pub fn hash(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest(&SHA256, token.as_bytes()))
+}
+
+/// Session a host token was issued for, or None if `token` isn't shaped like a host token
+pub fn session_of(token: &str) -> Option<Uuid> {
+    let (session_id, secret) = token.strip_prefix(HOST_TOKEN_PREFIX)?.split_once('_')?;
+    if secret.is_empty() {
+        return None;
+    }
+    Uuid::try_parse(session_id).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_name_their_session_and_differ() {
+        let session_id = Uuid::new_v4();
+        let first = generate(session_id).unwrap();
+        let second = generate(session_id).unwrap();
+
+        assert_eq!(session_of(&first), Some(session_id));
+        assert_ne!(first, second);
+        assert_ne!(hash(&first), hash(&second));
+        assert_eq!(hash(&first), hash(&first));
+    }
+
+    #[test]
+    fn other_tokens_are_not_host_tokens() {
+        assert_eq!(session_of("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+        assert_eq!(session_of("rwh_not-a-session_secret"), None);
+        assert_eq!(session_of(&format!("rwh_{}_", Uuid::new_v4().simple())), None);
+    }
+}
@@ -1,9 +1,53 @@
 use std::path::Path;
-use tracing::info;
+use tracing::{info, Subscriber};
 use tracing_appender::{non_blocking, rolling};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+use crate::shared::config::{LogFormat, LoggingConfig};
+
+/// Level filter from the configured directives, `info` when there are none or they don't parse
+fn env_filter(filter: Option<&str>) -> EnvFilter {
+    filter
+        .and_then(|directives| EnvFilter::try_new(directives).ok())
+        .unwrap_or_else(|| EnvFilter::new("info"))
+}
+
+/// Layer writing events to `writer` in `format`. `detailed` adds thread ids and line numbers
+/// and leaves out colours, for log files.
+fn format_layer<S, W>(format: LogFormat, writer: W, detailed: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => fmt::layer()
+            .with_writer(writer)
+            .with_ansi(!detailed)
+            .with_target(detailed)
+            .with_thread_ids(detailed)
+            .with_line_number(detailed)
+            .boxed(),
+        // Structured lines always name their target so log shippers can filter on it
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_writer(writer)
+            .with_target(true)
+            .with_thread_ids(detailed)
+            .with_line_number(detailed)
+            .boxed(),
+    }
+}
+
+/// Initialize logging to stdout and a daily-rotated file. `config.dir` overrides `log_dir`.
+pub fn init_logging(config: &LoggingConfig, log_dir: &str, service_name: &str) -> Result<(), anyhow::Error> {
+    let log_dir = config.dir.as_deref().unwrap_or(log_dir);
 
-pub fn init_logging(log_dir: &str, service_name: &str) -> Result<(), anyhow::Error> {
     // Rotate logs on startup
     let _ = rotate_logs_on_startup(log_dir, service_name);
     // Create log directory if it doesn't exist
@@ -16,27 +60,13 @@ pub fn init_logging(log_dir: &str, service_name: &str) -> Result<(), anyhow::Err
     // Set up console output
     let (non_blocking_stdout, _guard2) = non_blocking(std::io::stdout());
 
-    // Create layers
-    let file_layer = fmt::layer()
-        .with_writer(non_blocking_file)
-        .with_ansi(false) // No colors in file logs
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_line_number(true);
-
-    let console_layer = fmt::layer()
-        .with_writer(non_blocking_stdout)
-        .with_ansi(true) // Colors for console
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_line_number(false);
-
-    // Set up environment filter (can be controlled via RUST_LOG env var)
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    // Files get the detailed format, the console the compact one
+    let file_layer = format_layer(config.format, non_blocking_file, true);
+    let console_layer = format_layer(config.format, non_blocking_stdout, false);
 
     // Initialize tracing subscriber
     tracing_subscriber::registry()
-        .with(env_filter)
+        .with(env_filter(config.filter.as_deref()))
         .with(file_layer)
         .with(console_layer)
         .init();
@@ -65,3 +95,61 @@ pub fn rotate_logs_on_startup(log_dir: &str, service_name: &str) -> Result<(), a
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer collecting everything written through it
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logs_are_one_object_per_line() {
+        let captured = Captured::default();
+        let writer = {
+            let captured = captured.clone();
+            move || captured.clone()
+        };
+        let subscriber = tracing_subscriber::registry().with(format_layer(LogFormat::Json, writer, true));
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(session = "abc", "Session started");
+            tracing::warn!("Session idle");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", e, line)))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["target"], module_path!());
+        assert_eq!(lines[0]["fields"]["message"], "Session started");
+        assert_eq!(lines[0]["fields"]["session"], "abc");
+        assert_eq!(lines[1]["level"], "WARN");
+    }
+
+    #[test]
+    fn unparseable_level_directives_fall_back_to_info() {
+        assert_eq!(env_filter(Some("debug")).to_string(), "debug");
+        assert_eq!(env_filter(Some("raworc=trace,warn")).to_string(), "raworc=trace,warn");
+        assert_eq!(env_filter(Some("raworc=[")).to_string(), "info");
+        assert_eq!(env_filter(None).to_string(), "info");
+    }
+}
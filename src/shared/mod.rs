@@ -1,6 +1,11 @@
+pub mod config;
 pub mod database;
+pub mod host_token;
 pub mod models;
 pub mod logging;
+pub mod rate_limit;
+pub mod secrets;
 
 pub use models::AppState;
-pub use database::{init_database, seed_rbac_system};
\ No newline at end of file
+pub use config::{Config, Service};
+pub use database::{bootstrap_admin, connect_with_retry, init_database, pool_options, resolve_instance_id, seed_rbac_system};
\ No newline at end of file
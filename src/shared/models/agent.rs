@@ -3,6 +3,14 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use utoipa::ToSchema;
+use validator::Validate;
+
+use super::created_range::CreatedRange;
+use super::patch::nullable;
+use super::validation::{model_name, not_blank};
+
+/// Workspace of agents shared with every workspace, whose sessions may all attach them
+pub const GLOBAL_AGENT_WORKSPACE: &str = "global";
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Agent {
@@ -22,13 +30,34 @@ pub struct Agent {
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+/// An agent's definition as it was before an update replaced it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AgentRevision {
+    pub agent_id: Uuid,
+    /// 1 for the agent's first recorded definition, counting up
+    pub revision: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub instructions: String,
+    pub model: String,
+    pub tools: serde_json::Value,
+    pub routes: serde_json::Value,
+    pub guardrails: serde_json::Value,
+    pub knowledge_bases: serde_json::Value,
+    /// Principal whose update replaced this definition
+    pub replaced_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateAgentRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
     pub name: String,
     #[serde(default = "default_workspace")]
     pub workspace: String, // Organization for this agent
     pub description: Option<String>,
     pub instructions: String,
+    #[validate(custom(function = "model_name"))]
     pub model: String,
     #[serde(default = "default_json_array")]
     pub tools: serde_json::Value,
@@ -40,11 +69,16 @@ pub struct CreateAgentRequest {
     pub knowledge_bases: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateAgentRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
     pub name: Option<String>,
-    pub description: Option<String>,
+    /// Omit to keep the current description; null clears it
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub description: Option<Option<String>>,
     pub instructions: Option<String>,
+    #[validate(custom(function = "model_name"))]
     pub model: Option<String>,
     pub tools: Option<serde_json::Value>,
     pub routes: Option<serde_json::Value>,
@@ -53,6 +87,17 @@ pub struct UpdateAgentRequest {
     pub active: Option<bool>,
 }
 
+/// A single prompt to run against an agent without keeping a session around
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TestAgentRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub prompt: String,
+    /// Workspace secrets to inject into the test container, like a session's `metadata.secrets`;
+    /// the host needs `ANTHROPIC_API_KEY`
+    #[serde(default)]
+    pub secrets: Vec<String>,
+}
+
 fn default_json_array() -> serde_json::Value {
     serde_json::json!([])
 }
@@ -61,35 +106,48 @@ fn default_workspace() -> String {
     "default".to_string()
 }
 
+impl Agent {
+    /// Whether sessions in `workspace` may attach this agent: it is in the same workspace or global
+    pub fn attachable_in(&self, workspace: &str) -> bool {
+        self.workspace == workspace || self.workspace == GLOBAL_AGENT_WORKSPACE
+    }
+}
+
 // Database queries
 impl Agent {
-    pub async fn find_all(pool: &sqlx::PgPool, workspace: Option<&str>) -> Result<Vec<Agent>, sqlx::Error> {
-        let query = if let Some(ns) = workspace {
-            sqlx::query_as::<_, Agent>(
-                r#"
-                SELECT id, name, workspace, description, instructions, model, 
-                       tools, routes, guardrails, knowledge_bases,
-                       active, created_at, updated_at
-                FROM agents
-                WHERE active = true AND workspace = $1
-                ORDER BY name ASC
-                "#
-            )
-            .bind(ns)
-        } else {
-            sqlx::query_as::<_, Agent>(
-                r#"
-                SELECT id, name, workspace, description, instructions, model, 
-                       tools, routes, guardrails, knowledge_bases,
-                       active, created_at, updated_at
-                FROM agents
-                WHERE active = true
-                ORDER BY name ASC
-                "#
-            )
-        };
-        
-        query.fetch_all(pool).await
+    pub async fn find_all(
+        pool: &sqlx::PgPool,
+        workspace: Option<&str>,
+        created: CreatedRange,
+    ) -> Result<Vec<Agent>, sqlx::Error> {
+        let mut sql = String::from(
+            r#"
+            SELECT id, name, workspace, description, instructions, model, 
+                   tools, routes, guardrails, knowledge_bases,
+                   active, created_at, updated_at, deleted_at
+            FROM agents
+            WHERE active = true
+            "#
+        );
+
+        let mut param_count = 0;
+
+        if workspace.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND workspace = ${}", param_count));
+        }
+
+        created.push_sql(&mut sql, "created_at", &mut param_count);
+
+        sql.push_str(" ORDER BY name ASC");
+
+        let mut query = sqlx::query_as::<_, Agent>(&sql);
+
+        if let Some(ns) = workspace {
+            query = query.bind(ns);
+        }
+
+        created.bind(query).fetch_all(pool).await
     }
 
     pub async fn find_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<Option<Agent>, sqlx::Error> {
@@ -97,7 +155,7 @@ impl Agent {
             r#"
             SELECT id, name, workspace, description, instructions, model,
                    tools, routes, guardrails, knowledge_bases,
-                   active, created_at, updated_at
+                   active, created_at, updated_at, deleted_at
             FROM agents
             WHERE id = $1
             "#
@@ -112,7 +170,7 @@ impl Agent {
             r#"
             SELECT id, name, workspace, description, instructions, model,
                    tools, routes, guardrails, knowledge_bases,
-                   active, created_at, updated_at
+                   active, created_at, updated_at, deleted_at
             FROM agents
             WHERE name = $1 AND workspace = $2
             "#
@@ -130,7 +188,7 @@ impl Agent {
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING id, name, workspace, description, instructions, model,
                       tools, routes, guardrails, knowledge_bases,
-                      active, created_at, updated_at
+                      active, created_at, updated_at, deleted_at
             "#
         )
         .bind(req.name)
@@ -146,13 +204,28 @@ impl Agent {
         .await
     }
 
-    pub async fn update(pool: &sqlx::PgPool, id: Uuid, req: UpdateAgentRequest) -> Result<Option<Agent>, sqlx::Error> {
+    /// Apply `req`. With `revised_by`, the agent's current definition is first saved as a
+    /// revision credited to that principal, in the same transaction.
+    pub async fn update(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        req: UpdateAgentRequest,
+        revised_by: Option<&str>,
+    ) -> Result<Option<Agent>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        if let Some(revised_by) = revised_by {
+            if !AgentRevision::record(&mut tx, id, revised_by).await? {
+                return Ok(None);
+            }
+        }
+
         // Build dynamic update query based on provided fields
         let result = sqlx::query_as::<_, Agent>(
             r#"
             UPDATE agents
             SET name = COALESCE($2, name),
-                description = COALESCE($3, description),
+                description = CASE WHEN $11 THEN $3 ELSE description END,
                 instructions = COALESCE($4, instructions),
                 model = COALESCE($5, model),
                 tools = COALESCE($6, tools),
@@ -163,12 +236,12 @@ impl Agent {
             WHERE id = $1
             RETURNING id, name, workspace, description, instructions, model,
                       tools, routes, guardrails, knowledge_bases,
-                      active, created_at, updated_at
+                      active, created_at, updated_at, deleted_at
             "#
         )
         .bind(id)
         .bind(req.name)
-        .bind(req.description)
+        .bind(req.description.clone().flatten())
         .bind(req.instructions)
         .bind(req.model)
         .bind(req.tools)
@@ -176,9 +249,57 @@ impl Agent {
         .bind(req.guardrails)
         .bind(req.knowledge_bases)
         .bind(req.active)
-        .fetch_optional(pool)
+        .bind(req.description.is_some())
+        .fetch_optional(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Put back the definition saved as `revision`. With `revised_by`, the definition being
+    /// replaced is saved as a new revision first, so the restore can be undone in turn.
+    /// None when the agent or the revision doesn't exist.
+    pub async fn restore_revision(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        revision: i32,
+        revised_by: Option<&str>,
+    ) -> Result<Option<Agent>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        if let Some(revised_by) = revised_by {
+            if !AgentRevision::record(&mut tx, id, revised_by).await? {
+                return Ok(None);
+            }
+        }
+
+        let result = sqlx::query_as::<_, Agent>(
+            r#"
+            UPDATE agents a
+            SET name = r.name,
+                description = r.description,
+                instructions = r.instructions,
+                model = r.model,
+                tools = r.tools,
+                routes = r.routes,
+                guardrails = r.guardrails,
+                knowledge_bases = r.knowledge_bases
+            FROM agent_revisions r
+            WHERE a.id = $1 AND r.agent_id = a.id AND r.revision = $2
+            RETURNING a.id, a.name, a.workspace, a.description, a.instructions, a.model,
+                      a.tools, a.routes, a.guardrails, a.knowledge_bases,
+                      a.active, a.created_at, a.updated_at, a.deleted_at
+            "#
+        )
+        .bind(id)
+        .bind(revision)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if result.is_some() {
+            tx.commit().await?;
+        }
         Ok(result)
     }
 
@@ -196,4 +317,55 @@ impl Agent {
 
         Ok(result.rows_affected() > 0)
     }
-}
\ No newline at end of file
+}
+
+impl AgentRevision {
+    /// Save the agent's current definition as its next revision, locking the agent row
+    /// so concurrent updates number their revisions in order. False when the agent doesn't exist.
+    async fn record(conn: &mut sqlx::PgConnection, agent_id: Uuid, replaced_by: &str) -> Result<bool, sqlx::Error> {
+        let locked = sqlx::query("SELECT 1 FROM agents WHERE id = $1 FOR UPDATE")
+            .bind(agent_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if locked.is_none() {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO agent_revisions (agent_id, revision, name, description, instructions, model,
+                                         tools, routes, guardrails, knowledge_bases, replaced_by)
+            SELECT id,
+                   COALESCE((SELECT MAX(revision) FROM agent_revisions WHERE agent_id = $1), 0) + 1,
+                   name, description, instructions, model,
+                   COALESCE(tools, '[]'), COALESCE(routes, '[]'),
+                   COALESCE(guardrails, '[]'), COALESCE(knowledge_bases, '[]'),
+                   $2
+            FROM agents
+            WHERE id = $1
+            "#
+        )
+        .bind(agent_id)
+        .bind(replaced_by)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(true)
+    }
+
+    /// An agent's saved revisions, newest first
+    pub async fn find_by_agent(pool: &sqlx::PgPool, agent_id: Uuid) -> Result<Vec<AgentRevision>, sqlx::Error> {
+        sqlx::query_as::<_, AgentRevision>(
+            r#"
+            SELECT agent_id, revision, name, description, instructions, model,
+                   tools, routes, guardrails, knowledge_bases, replaced_by, created_at
+            FROM agent_revisions
+            WHERE agent_id = $1
+            ORDER BY revision DESC
+            "#
+        )
+        .bind(agent_id)
+        .fetch_all(pool)
+        .await
+    }
+}
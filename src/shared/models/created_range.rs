@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgArguments, Postgres};
+use sqlx::query::QueryAs;
+
+/// A `created_at` window from the `created_after`/`created_before` list parameters.
+/// `after` is inclusive and `before` exclusive, so consecutive ranges never overlap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreatedRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl CreatedRange {
+    /// Parse RFC 3339 bounds; the error is a message for the caller
+    pub fn parse(after: Option<&str>, before: Option<&str>) -> Result<Self, String> {
+        let parse = |name: &str, value: Option<&str>| {
+            value
+                .map(|v| {
+                    DateTime::parse_from_rfc3339(v)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|_| format!("{} must be an RFC 3339 timestamp", name))
+                })
+                .transpose()
+        };
+
+        let range = Self {
+            after: parse("created_after", after)?,
+            before: parse("created_before", before)?,
+        };
+        if let (Some(after), Some(before)) = (range.after, range.before) {
+            if before < after {
+                return Err("created_before must not be earlier than created_after".to_string());
+            }
+        }
+        Ok(range)
+    }
+
+    /// Append the range's conditions on `column`, numbering parameters after `param_count`
+    pub fn push_sql(&self, sql: &mut String, column: &str, param_count: &mut usize) {
+        if self.after.is_some() {
+            *param_count += 1;
+            sql.push_str(&format!(" AND {} >= ${}", column, param_count));
+        }
+        if self.before.is_some() {
+            *param_count += 1;
+            sql.push_str(&format!(" AND {} < ${}", column, param_count));
+        }
+    }
+
+    /// Bind the parameters added by `push_sql`, in the same order
+    pub fn bind<'q, O>(&self, mut query: QueryAs<'q, Postgres, O, PgArguments>) -> QueryAs<'q, Postgres, O, PgArguments> {
+        if let Some(after) = self.after {
+            query = query.bind(after);
+        }
+        if let Some(before) = self.before {
+            query = query.bind(before);
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CreatedRange;
+
+    #[test]
+    fn bounds_are_rfc3339_in_any_offset() {
+        let range = CreatedRange::parse(Some("2025-01-01T00:00:00Z"), Some("2025-01-01T02:00:00+01:00")).unwrap();
+        assert_eq!(range.after.unwrap().to_rfc3339(), "2025-01-01T00:00:00+00:00");
+        assert_eq!(range.before.unwrap().to_rfc3339(), "2025-01-01T01:00:00+00:00");
+
+        assert_eq!(CreatedRange::parse(None, None).unwrap(), CreatedRange::default());
+        assert_eq!(
+            CreatedRange::parse(Some("yesterday"), None).unwrap_err(),
+            "created_after must be an RFC 3339 timestamp"
+        );
+    }
+
+    #[test]
+    fn an_empty_range_is_allowed_but_a_reversed_one_is_not() {
+        assert!(CreatedRange::parse(Some("2025-01-01T00:00:00Z"), Some("2025-01-01T00:00:00Z")).is_ok());
+        assert_eq!(
+            CreatedRange::parse(Some("2025-01-02T00:00:00Z"), Some("2025-01-01T00:00:00Z")).unwrap_err(),
+            "created_before must not be earlier than created_after"
+        );
+    }
+
+    #[test]
+    fn sql_parameters_continue_the_numbering() {
+        let range = CreatedRange::parse(Some("2025-01-01T00:00:00Z"), Some("2025-02-01T00:00:00Z")).unwrap();
+        let mut sql = String::from("SELECT * FROM sessions WHERE workspace = $1");
+        let mut param_count = 1;
+        range.push_sql(&mut sql, "created_at", &mut param_count);
+
+        assert_eq!(sql, "SELECT * FROM sessions WHERE workspace = $1 AND created_at >= $2 AND created_at < $3");
+        assert_eq!(param_count, 3);
+    }
+}
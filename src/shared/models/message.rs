@@ -33,6 +33,15 @@ pub struct CreateMessageRequest {
     pub metadata: serde_json::Value,
 }
 
+/// Body of the operator endpoint that injects a SYSTEM message; the role is always SYSTEM
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateSystemMessageRequest {
+    pub content: String,
+    /// JSON object; `posted_by` is set by the server
+    #[serde(default = "default_metadata")]
+    pub metadata: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MessageResponse {
     pub id: String,
@@ -49,30 +58,122 @@ pub struct MessageResponse {
 pub struct ListMessagesQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
-    #[allow(dead_code)]
     pub role: Option<MessageRole>,
-    #[allow(dead_code)]
     pub since: Option<DateTime<Utc>>,
+    /// Only messages from this agent; parsed by the handler so a bad UUID is a 400
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub order: MessageOrder,
+}
+
+/// Direction messages are listed in by creation time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageOrder {
+    /// Oldest first
+    #[default]
+    Asc,
+    /// Newest first
+    Desc,
+}
+
+impl MessageOrder {
+    fn sql(self) -> &'static str {
+        match self {
+            MessageOrder::Asc => "ASC",
+            MessageOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Conditions a listed message must meet; unset fields match every message
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+    pub role: Option<MessageRole>,
+    pub since: Option<DateTime<Utc>>,
+    pub agent_id: Option<Uuid>,
 }
 
+/// A message joined with its agent's name
+#[derive(Debug, FromRow)]
+struct MessageWithAgentRow {
+    id: Uuid,
+    session_id: Uuid,
+    role: MessageRole,
+    content: String,
+    agent_id: Option<Uuid>,
+    metadata: Option<serde_json::Value>,
+    created_at: DateTime<Utc>,
+    agent_name: Option<String>,
+}
+
+/// `metadata.type` of the SYSTEM message asking a session's host to abort its current operation
+pub const CANCEL_REQUEST_TYPE: &str = "cancel_request";
+
+/// Metadata key the server sets to `true` on messages posted with the session's host token.
+/// Clients can't set it, so readers can trust host reports such as guardrail events.
+pub const FROM_HOST_KEY: &str = "from_host";
+
+/// Metadata key the server sets to `true` on messages recreated by a session import. Their
+/// USER messages were answered in the original session, so hosts don't process them again.
+pub const IMPORTED_KEY: &str = "imported";
+
 fn default_metadata() -> serde_json::Value {
     serde_json::json!({})
 }
 
+/// Idempotency key stored for item `index` of a batch sent with `key`
+pub fn batch_item_key(key: &str, index: usize) -> String {
+    format!("{}/{}", key, index)
+}
+
 // Database operations
 impl SessionMessage {
     pub async fn create(
         pool: &sqlx::PgPool,
         session_id: Uuid,
         req: CreateMessageRequest,
+        idempotency_key: Option<&str>,
     ) -> Result<SessionMessage, sqlx::Error> {
-        // Note: Database constraint ensures agent_id is set when role is AGENT
-        sqlx::query_as::<_, SessionMessage>(
+        let mut conn = pool.acquire().await?;
+        Self::insert(&mut conn, session_id, req, idempotency_key).await
+    }
+
+    /// Insert messages in order in one transaction, so either all are stored or none are.
+    /// With an idempotency key, item `i` is stored under `{key}/{i}` and a retried batch
+    /// returns the messages stored the first time.
+    pub async fn create_batch(
+        pool: &sqlx::PgPool,
+        session_id: Uuid,
+        reqs: Vec<CreateMessageRequest>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Vec<SessionMessage>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut messages = Vec::with_capacity(reqs.len());
+        for (index, req) in reqs.into_iter().enumerate() {
+            let key = idempotency_key.map(|key| batch_item_key(key, index));
+            messages.push(Self::insert(&mut tx, session_id, req, key.as_deref()).await?);
+        }
+        tx.commit().await?;
+        Ok(messages)
+    }
+
+    pub(crate) async fn insert(
+        conn: &mut sqlx::PgConnection,
+        session_id: Uuid,
+        req: CreateMessageRequest,
+        idempotency_key: Option<&str>,
+    ) -> Result<SessionMessage, sqlx::Error> {
+        // Note: Database constraints ensure AGENT messages name an agent assigned to the session.
+        // clock_timestamp() keeps messages inserted in one transaction in insertion order.
+        let created = sqlx::query_as::<_, SessionMessage>(
             r#"
             INSERT INTO session_messages (
-                session_id, role, content, agent_id, metadata
+                session_id, role, content, agent_id, metadata, idempotency_key, created_at
             )
-            VALUES ($1, $2, $3, $4, $5)
+            VALUES ($1, $2, $3, $4, $5, $6, clock_timestamp())
+            ON CONFLICT (session_id, idempotency_key) WHERE idempotency_key IS NOT NULL
+            DO NOTHING
             RETURNING id, session_id, role, content, agent_id, 
                       metadata, created_at
             "#
@@ -82,7 +183,76 @@ impl SessionMessage {
         .bind(&req.content)
         .bind(req.agent_id)
         .bind(&req.metadata)
-        .fetch_one(pool)
+        .bind(idempotency_key)
+        .fetch_optional(&mut *conn)
+        .await?;
+        
+        match (created, idempotency_key) {
+            (Some(message), _) => Ok(message),
+            // A concurrent request with the same key won the insert
+            (None, Some(key)) => Self::find_by_idempotency_key(&mut *conn, session_id, key)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound),
+            (None, None) => Err(sqlx::Error::RowNotFound),
+        }
+    }
+
+    pub async fn find_by_idempotency_key<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        session_id: Uuid,
+        idempotency_key: &str,
+    ) -> Result<Option<SessionMessage>, sqlx::Error> {
+        sqlx::query_as::<_, SessionMessage>(
+            r#"
+            SELECT id, session_id, role, content, agent_id,
+                   metadata, created_at
+            FROM session_messages
+            WHERE session_id = $1 AND idempotency_key = $2
+            "#
+        )
+        .bind(session_id)
+        .bind(idempotency_key)
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Messages stored under any of `idempotency_keys`, in the order of the keys
+    pub async fn find_by_idempotency_keys<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        session_id: Uuid,
+        idempotency_keys: &[String],
+    ) -> Result<Vec<SessionMessage>, sqlx::Error> {
+        sqlx::query_as::<_, SessionMessage>(
+            r#"
+            SELECT id, session_id, role, content, agent_id,
+                   metadata, created_at
+            FROM session_messages
+            WHERE session_id = $1 AND idempotency_key = ANY($2)
+            ORDER BY array_position($2, idempotency_key)
+            "#
+        )
+        .bind(session_id)
+        .bind(idempotency_keys)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Every message of a session, oldest first
+    pub async fn find_all_by_session(
+        pool: &sqlx::PgPool,
+        session_id: Uuid,
+    ) -> Result<Vec<SessionMessage>, sqlx::Error> {
+        sqlx::query_as::<_, SessionMessage>(
+            r#"
+            SELECT id, session_id, role, content, agent_id,
+                   metadata, created_at
+            FROM session_messages
+            WHERE session_id = $1
+            ORDER BY created_at ASC, id ASC
+            "#
+        )
+        .bind(session_id)
+        .fetch_all(pool)
         .await
     }
 
@@ -181,6 +351,28 @@ impl SessionMessage {
         Ok(result)
     }
 
+    /// USER messages the host hasn't answered yet, i.e. those newer than the session's latest
+    /// AGENT message and not imported
+    pub async fn count_pending(
+        pool: &sqlx::PgPool,
+        session_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM session_messages
+            WHERE session_id = $1 AND role = 'USER'
+              AND metadata->'imported' IS DISTINCT FROM 'true'::jsonb
+              AND created_at > COALESCE(
+                  (SELECT MAX(created_at) FROM session_messages WHERE session_id = $1 AND role = 'AGENT'),
+                  '-infinity'::timestamptz
+              )
+            "#
+        )
+        .bind(session_id)
+        .fetch_one(pool)
+        .await
+    }
+
     pub async fn delete_by_session(
         pool: &sqlx::PgPool,
         session_id: Uuid,
@@ -198,31 +390,71 @@ impl SessionMessage {
     pub async fn get_with_agent_info(
         pool: &sqlx::PgPool,
         session_id: Uuid,
+        filter: &MessageFilter,
+        order: MessageOrder,
         limit: Option<i64>,
         offset: Option<i64>,
     ) -> Result<Vec<MessageResponse>, sqlx::Error> {
         let limit = limit.unwrap_or(100).min(1000);
         let offset = offset.unwrap_or(0);
         
-        let messages = sqlx::query!(
+        let mut sql = String::from(
             r#"
             SELECT 
-                m.id, m.session_id, m.role as "role: MessageRole", 
+                m.id, m.session_id, m.role, 
                 m.content, m.agent_id, 
                 m.metadata, m.created_at,
-                a.name as "agent_name?"
+                a.name as agent_name
             FROM session_messages m
             LEFT JOIN agents a ON m.agent_id = a.id
             WHERE m.session_id = $1
-            ORDER BY m.created_at ASC
-            LIMIT $2 OFFSET $3
-            "#,
-            session_id,
-            limit,
-            offset
-        )
-        .fetch_all(pool)
-        .await?;
+            "#
+        );
+        
+        let mut param_count = 1;
+        
+        if filter.role.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND m.role = ${}", param_count));
+        }
+        
+        if filter.since.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND m.created_at > ${}", param_count));
+        }
+        
+        if filter.agent_id.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND m.agent_id = ${}", param_count));
+        }
+        
+        sql.push_str(&format!(
+            " ORDER BY m.created_at {0}, m.id {0} LIMIT ${1} OFFSET ${2}",
+            order.sql(),
+            param_count + 1,
+            param_count + 2
+        ));
+        
+        let mut query_builder = sqlx::query_as::<_, MessageWithAgentRow>(&sql)
+            .bind(session_id);
+        
+        if let Some(role) = filter.role {
+            query_builder = query_builder.bind(role);
+        }
+        
+        if let Some(since) = filter.since {
+            query_builder = query_builder.bind(since);
+        }
+        
+        if let Some(agent_id) = filter.agent_id {
+            query_builder = query_builder.bind(agent_id);
+        }
+        
+        let messages = query_builder
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await?;
         
         Ok(messages.into_iter().map(|m| MessageResponse {
             id: m.id.to_string(),
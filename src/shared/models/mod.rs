@@ -4,10 +4,23 @@ use sqlx::{Pool, Postgres};
 pub mod agent;
 pub mod session;
 pub mod message;
+pub mod workspace;
+pub mod secret;
+pub mod usage;
+pub mod task;
+pub mod validation;
+pub mod patch;
+pub mod created_range;
+pub mod node;
 
-pub use agent::{Agent, CreateAgentRequest, UpdateAgentRequest};
-pub use session::{Session, SessionState, CreateSessionRequest, RemixSessionRequest, UpdateSessionStateRequest, UpdateSessionRequest};
-pub use message::{SessionMessage, MessageRole, CreateMessageRequest, MessageResponse, ListMessagesQuery};
+pub use agent::{Agent, AgentRevision, CreateAgentRequest, TestAgentRequest, UpdateAgentRequest};
+pub use session::{Session, SessionState, SessionStateChange, SessionStatus, TerminationCause, CreateSessionRequest, RemixSessionRequest, UpdateSessionStateRequest, UpdateSessionRequest, TransferSessionRequest, AttachSessionAgentRequest};
+pub use message::{SessionMessage, MessageRole, CreateMessageRequest, CreateSystemMessageRequest, MessageResponse, ListMessagesQuery, MessageFilter, MessageOrder};
+pub use secret::{Secret, CreateSecretRequest, UpdateSecretRequest};
+pub use created_range::CreatedRange;
+pub use usage::{SessionUsage, RecordUsageRequest};
+pub use task::{SessionTaskRecord, TaskPayload, TASK_STATUSES};
+pub use workspace::{WorkspaceSettings, WorkspaceTier, UpdateWorkspaceSettingsRequest, DEFAULT_WAITING_TIMEOUT_SECONDS, normalize_workspace_name, find_denied_env_var};
 
 // Database errors
 #[derive(Error, Debug)]
@@ -26,5 +39,14 @@ pub enum DatabaseError {
 #[derive(Clone)]
 pub struct AppState {
     pub db: std::sync::Arc<Pool<Postgres>>,
-    pub jwt_secret: String,
+    pub jwt_keys: crate::server::auth::JwtKeySet,
+    /// None when RAWORC_SECRETS_KEY is unset
+    pub secrets: Option<std::sync::Arc<crate::shared::secrets::SecretsCipher>>,
+    pub config: std::sync::Arc<crate::shared::config::Config>,
+    /// Maintenance switch checked on every write; starts from RAWORC_READ_ONLY
+    pub read_only: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// None when RAWORC_RATE_LIMIT_PER_MINUTE is unset
+    pub rate_limiter: Option<std::sync::Arc<crate::shared::rate_limit::RateLimiter>>,
+    /// One permit per agent test allowed to run at once
+    pub agent_tests: std::sync::Arc<tokio::sync::Semaphore>,
 }
\ No newline at end of file
@@ -0,0 +1,34 @@
+//! Nodes operators run on. An operator started with RAWORC_NODE_NAME registers its node, so
+//! sessions can't be pinned to a node no operator serves, along with the URL of its node API.
+
+/// Record that an operator is running on `name`, serving its node API at `api_url`
+pub async fn register_node(pool: &sqlx::PgPool, name: &str, api_url: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO operator_nodes (name, api_url) VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE SET last_seen_at = NOW(), api_url = EXCLUDED.api_url
+        "#,
+    )
+    .bind(name)
+    .bind(api_url)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Node API URL the operator on `name` registered; None when it has none or isn't registered
+pub async fn node_api_url(pool: &sqlx::PgPool, name: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<String>>("SELECT api_url FROM operator_nodes WHERE name = $1")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+}
+
+/// Whether an operator has registered `name`
+pub async fn node_exists(pool: &sqlx::PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM operator_nodes WHERE name = $1)")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize a nullable field of a partial update so that an absent field and an
+/// explicit `null` can be told apart. Use with
+/// `#[serde(default, deserialize_with = "nullable")]` on an `Option<Option<T>>`:
+/// absent → `None` (keep), `null` → `Some(None)` (clear), value → `Some(Some(value))` (set).
+pub fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Patch {
+        #[serde(default, deserialize_with = "nullable")]
+        description: Option<Option<String>>,
+    }
+
+    fn patch(json: &str) -> Option<Option<String>> {
+        serde_json::from_str::<Patch>(json).unwrap().description
+    }
+
+    #[test]
+    fn absent_null_and_set_fields_are_told_apart() {
+        assert_eq!(patch("{}"), None);
+        assert_eq!(patch(r#"{"description": null}"#), Some(None));
+        assert_eq!(patch(r#"{"description": "new"}"#), Some(Some("new".to_string())));
+        assert!(serde_json::from_str::<Patch>(r#"{"description": 1}"#).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use super::validation::is_valid_env_var_name;
+
+/// Stored secret. Deliberately not Serialize: the encrypted value never leaves the server.
+#[derive(Debug, Clone, FromRow)]
+pub struct Secret {
+    pub id: Uuid,
+    pub workspace: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSecretRequest {
+    /// Environment variable name the value is injected as, e.g. ANTHROPIC_API_KEY
+    pub name: String,
+    #[serde(default = "default_workspace")]
+    pub workspace: String,
+    pub value: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateSecretRequest {
+    pub value: Option<String>,
+    pub description: Option<String>,
+}
+
+fn default_workspace() -> String {
+    "default".to_string()
+}
+
+/// Secret names double as environment variable names
+pub fn is_valid_secret_name(name: &str) -> bool {
+    is_valid_env_var_name(name)
+}
+
+/// Secret names a session's `metadata.secrets` asks to have injected; empty when absent
+pub fn requested_secret_names(metadata: &serde_json::Value) -> Result<Vec<String>, serde_json::Error> {
+    match metadata.get("secrets") {
+        Some(value) => serde_json::from_value(value.clone()),
+        None => Ok(Vec::new()),
+    }
+}
+
+// Database operations
+impl Secret {
+    pub async fn find_all(pool: &sqlx::PgPool, workspace: &str) -> Result<Vec<Secret>, sqlx::Error> {
+        sqlx::query_as::<_, Secret>(
+            r#"
+            SELECT id, workspace, name, description, nonce, ciphertext, created_by, created_at, updated_at
+            FROM secrets
+            WHERE workspace = $1
+            ORDER BY name
+            "#
+        )
+        .bind(workspace)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_name(pool: &sqlx::PgPool, workspace: &str, name: &str) -> Result<Option<Secret>, sqlx::Error> {
+        sqlx::query_as::<_, Secret>(
+            r#"
+            SELECT id, workspace, name, description, nonce, ciphertext, created_by, created_at, updated_at
+            FROM secrets
+            WHERE workspace = $1 AND name = $2
+            "#
+        )
+        .bind(workspace)
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        workspace: &str,
+        name: &str,
+        description: Option<&str>,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        created_by: &str,
+    ) -> Result<Secret, sqlx::Error> {
+        sqlx::query_as::<_, Secret>(
+            r#"
+            INSERT INTO secrets (workspace, name, description, nonce, ciphertext, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, workspace, name, description, nonce, ciphertext, created_by, created_at, updated_at
+            "#
+        )
+        .bind(workspace)
+        .bind(name)
+        .bind(description)
+        .bind(nonce)
+        .bind(ciphertext)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Replace the value and/or description; `sealed` is the new (nonce, ciphertext) if the value changed
+    pub async fn update(
+        pool: &sqlx::PgPool,
+        workspace: &str,
+        name: &str,
+        description: Option<&str>,
+        sealed: Option<(&[u8], &[u8])>,
+    ) -> Result<Option<Secret>, sqlx::Error> {
+        sqlx::query_as::<_, Secret>(
+            r#"
+            UPDATE secrets
+            SET description = COALESCE($3, description),
+                nonce = COALESCE($4, nonce),
+                ciphertext = COALESCE($5, ciphertext)
+            WHERE workspace = $1 AND name = $2
+            RETURNING id, workspace, name, description, nonce, ciphertext, created_by, created_at, updated_at
+            "#
+        )
+        .bind(workspace)
+        .bind(name)
+        .bind(description)
+        .bind(sealed.map(|(nonce, _)| nonce))
+        .bind(sealed.map(|(_, ciphertext)| ciphertext))
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &sqlx::PgPool, workspace: &str, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM secrets WHERE workspace = $1 AND name = $2")
+            .bind(workspace)
+            .bind(name)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
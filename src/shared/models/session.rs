@@ -3,6 +3,14 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use uuid::Uuid;
 use utoipa::ToSchema;
+use validator::Validate;
+
+use super::created_range::CreatedRange;
+use super::message::{CreateMessageRequest, MessageRole, SessionMessage, CANCEL_REQUEST_TYPE};
+use super::patch::nullable;
+use super::task::TaskPayload;
+use super::validation::{node_name, not_blank};
+use super::workspace::{WorkspaceSettings, DEFAULT_WAITING_TIMEOUT_SECONDS};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
 #[sqlx(type_name = "session_state", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -15,6 +23,36 @@ pub enum SessionState {
     Error,
 }
 
+/// Why a session ended; `termination_reason` carries the detail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "termination_cause", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationCause {
+    /// Set to ERROR through the API, by a user or the session's host agent
+    Manual,
+    /// Soft-deleted; the operator then destroys its container
+    Deleted,
+    /// The operator found its container gone
+    ContainerLost,
+    /// The operator found its container present but no longer running, e.g. crashed or
+    /// failing its health checks
+    HealthCheckFailed,
+    /// Stopped after waiting longer than its waiting timeout
+    IdleTimeout,
+    /// Purged by the reaper once its retention period after deletion expired
+    TtlExpired,
+}
+
+/// A state change that queues a container task, for `Session::transition_with_task`
+struct Transition {
+    /// States the session must be in, as their database names
+    from: &'static [&'static str],
+    to: SessionState,
+    /// Recorded when the transition stops the container
+    cause: Option<TerminationCause>,
+    task: TaskPayload,
+}
+
 impl SessionState {
     pub fn can_transition_to(&self, target: &SessionState) -> bool {
         match (self, target) {
@@ -58,36 +96,74 @@ pub struct Session {
     pub created_by: String,
     pub parent_session_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
+    /// Last change to the row, of any kind; maintained by a trigger
+    pub updated_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub last_activity_at: Option<DateTime<Utc>>,
     pub terminated_at: Option<DateTime<Utc>>,
     pub termination_reason: Option<String>,
+    pub termination_cause: Option<TerminationCause>,
+    pub terminated_by: Option<String>, // Principal that deleted the session or set it to ERROR
     pub metadata: serde_json::Value,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Node whose operator runs this session; None lets any operator take it
+    pub node_selector: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+/// What a client polls for state changes, read without the agent join of a full session
+#[derive(Debug, Clone, FromRow)]
+pub struct SessionStatus {
+    pub created_by: String,
+    pub state: SessionState,
+    pub last_activity_at: Option<DateTime<Utc>>,
+    pub container_id: Option<String>,
+    /// When the state last changed, or the creation time if it never has
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One row of `session_state_history`; `from_state` is None for the session's creation
+#[derive(Debug, Clone, FromRow)]
+pub struct SessionStateChange {
+    pub from_state: Option<SessionState>,
+    pub to_state: SessionState,
+    pub reason: Option<String>,
+    pub changed_by: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateSessionRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
     pub name: String,
     #[serde(default = "default_workspace")]
     pub workspace: String, // Organization for this session
+    #[validate(custom(function = "not_blank"))]
     pub starting_prompt: String,
     #[serde(default)]
     pub agent_ids: Vec<Uuid>,
-    #[serde(default = "default_timeout")]
-    pub waiting_timeout_seconds: i32,
+    /// Falls back to the workspace's default, then the global default, when omitted
+    #[serde(default)]
+    #[validate(range(min = 1, message = "must be positive"))]
+    pub waiting_timeout_seconds: Option<i32>,
     #[serde(default = "default_metadata")]
     pub metadata: serde_json::Value,
+    /// Run the session only on the operator started with this `RAWORC_NODE_NAME`, e.g. a GPU host
+    #[serde(default)]
+    #[validate(custom(function = "node_name"))]
+    pub node_selector: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct RemixSessionRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
     pub name: String,
     #[serde(default)]
+    #[validate(custom(function = "not_blank"))]
     pub starting_prompt: Option<String>,
     #[serde(default)]
     pub agent_ids: Option<Vec<Uuid>>,
     #[serde(default)]
+    #[validate(range(min = 1, message = "must be positive"))]
     pub waiting_timeout_seconds: Option<i32>,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
@@ -104,16 +180,34 @@ pub struct UpdateSessionStateRequest {
     pub termination_reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateSessionRequest {
     #[serde(default)]
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
     pub name: Option<String>,
-    #[serde(default)]
-    pub waiting_timeout_seconds: Option<i32>,
+    /// Omit to keep the current timeout; null disables the idle timeout
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i32>, nullable)]
+    #[validate(range(min = 1, message = "must be positive"))]
+    pub waiting_timeout_seconds: Option<Option<i32>>,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransferSessionRequest {
+    pub new_owner: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttachSessionAgentRequest {
+    pub agent_id: Uuid,
+    /// Settings for this agent in this session, e.g. `context_window_messages` or
+    /// `context_window_chars` for the host; replaces any earlier configuration
+    #[serde(default)]
+    pub configuration: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SessionAgent {
     pub session_id: Uuid,
@@ -122,10 +216,6 @@ pub struct SessionAgent {
     pub configuration: serde_json::Value,
 }
 
-fn default_timeout() -> i32 {
-    300 // 5 minutes
-}
-
 fn default_metadata() -> serde_json::Value {
     serde_json::json!({})
 }
@@ -136,67 +226,128 @@ fn default_workspace() -> String {
 
 // Database queries
 impl Session {
-    pub async fn find_all(pool: &sqlx::PgPool, workspace: Option<&str>, created_by: Option<&str>) -> Result<Vec<Session>, sqlx::Error> {
-        let query = match (workspace, created_by) {
-            (Some(ns), Some(user)) => {
-                sqlx::query_as::<_, Session>(
-                    r#"
-                    SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
-                           container_id, persistent_volume_id, created_by, parent_session_id,
-                           created_at, started_at, last_activity_at, terminated_at,
-                           termination_reason, metadata, deleted_at
-                    FROM sessions
-                    WHERE workspace = $1 AND created_by = $2 AND deleted_at IS NULL
-                    ORDER BY created_at DESC
-                    "#
-                )
-                .bind(ns)
-                .bind(user)
-            },
-            (Some(ns), None) => {
-                sqlx::query_as::<_, Session>(
-                    r#"
-                    SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
-                           container_id, persistent_volume_id, created_by, parent_session_id,
-                           created_at, started_at, last_activity_at, terminated_at,
-                           termination_reason, metadata, deleted_at
-                    FROM sessions
-                    WHERE workspace = $1 AND deleted_at IS NULL
-                    ORDER BY created_at DESC
-                    "#
-                )
-                .bind(ns)
-            },
-            (None, Some(user)) => {
-                sqlx::query_as::<_, Session>(
-                    r#"
-                    SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
-                           container_id, persistent_volume_id, created_by, parent_session_id,
-                           created_at, started_at, last_activity_at, terminated_at,
-                           termination_reason, metadata, deleted_at
-                    FROM sessions
-                    WHERE created_by = $1 AND deleted_at IS NULL
-                    ORDER BY created_at DESC
-                    "#
-                )
-                .bind(user)
-            },
-            (None, None) => {
-                sqlx::query_as::<_, Session>(
-                    r#"
-                    SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
-                           container_id, persistent_volume_id, created_by, parent_session_id,
-                           created_at, started_at, last_activity_at, terminated_at,
-                           termination_reason, metadata, deleted_at
-                    FROM sessions
-                    WHERE deleted_at IS NULL
-                    ORDER BY created_at DESC
-                    "#
-                )
-            }
-        };
-        
-        query.fetch_all(pool).await
+    pub async fn find_all(
+        pool: &sqlx::PgPool,
+        workspace: Option<&str>,
+        created_by: Option<&str>,
+        parent_id: Option<Uuid>,
+        name: Option<&str>,
+        created: CreatedRange,
+        include_deleted: bool,
+    ) -> Result<Vec<Session>, sqlx::Error> {
+        let mut sql = String::from(
+            r#"
+            SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
+                   container_id, persistent_volume_id, created_by, parent_session_id,
+                   created_at, started_at, last_activity_at, terminated_at,
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            FROM sessions
+            WHERE TRUE
+            "#
+        );
+
+        if !include_deleted {
+            sql.push_str(" AND deleted_at IS NULL");
+        }
+
+        let mut param_count = 0;
+
+        if workspace.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND workspace = ${}", param_count));
+        }
+
+        if created_by.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND created_by = ${}", param_count));
+        }
+
+        if parent_id.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND parent_session_id = ${}", param_count));
+        }
+
+        if name.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" AND name = ${}", param_count));
+        }
+
+        created.push_sql(&mut sql, "created_at", &mut param_count);
+
+        sql.push_str(" ORDER BY created_at DESC");
+
+        let mut query = sqlx::query_as::<_, Session>(&sql);
+
+        if let Some(ns) = workspace {
+            query = query.bind(ns);
+        }
+
+        if let Some(user) = created_by {
+            query = query.bind(user);
+        }
+
+        if let Some(parent) = parent_id {
+            query = query.bind(parent);
+        }
+
+        if let Some(name) = name {
+            query = query.bind(name);
+        }
+
+        created.bind(query).fetch_all(pool).await
+    }
+
+    /// All live descendants of a session (children, grandchildren, ...), excluding the session itself
+    pub async fn find_descendants(pool: &sqlx::PgPool, id: Uuid) -> Result<Vec<Session>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(
+            r#"
+            WITH RECURSIVE descendants AS (
+                SELECT s.*
+                FROM sessions s
+                WHERE s.parent_session_id = $1 AND s.deleted_at IS NULL
+                UNION
+                SELECT s.*
+                FROM sessions s
+                JOIN descendants d ON s.parent_session_id = d.id
+                WHERE s.deleted_at IS NULL
+            )
+            SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
+                   container_id, persistent_volume_id, created_by, parent_session_id,
+                   created_at, started_at, last_activity_at, terminated_at,
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            FROM descendants
+            ORDER BY created_at ASC
+            "#
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remixes between a session and the root of its chain: 0 for a session that isn't a remix.
+    /// The walk stops after `limit` steps, so a corrupted chain that loops can't run forever.
+    pub async fn remix_depth(pool: &sqlx::PgPool, id: Uuid, limit: u32) -> Result<u32, sqlx::Error> {
+        let depth = sqlx::query_scalar::<_, i32>(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT parent_session_id, 0 AS depth
+                FROM sessions
+                WHERE id = $1
+                UNION ALL
+                SELECT s.parent_session_id, a.depth + 1
+                FROM sessions s
+                JOIN ancestors a ON s.id = a.parent_session_id
+                WHERE a.depth < $2
+            )
+            SELECT COALESCE(MAX(depth), 0) FROM ancestors
+            "#
+        )
+        .bind(id)
+        .bind(limit as i32)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(depth as u32)
     }
 
     pub async fn find_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<Option<Session>, sqlx::Error> {
@@ -205,7 +356,7 @@ impl Session {
             SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
                    container_id, persistent_volume_id, created_by, parent_session_id,
                    created_at, started_at, last_activity_at, terminated_at,
-                   termination_reason, metadata, deleted_at
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
             FROM sessions
             WHERE id = $1 AND deleted_at IS NULL
             "#
@@ -215,33 +366,63 @@ impl Session {
         .await
     }
 
-    pub async fn create(
+    /// Find a live session by name; names are unique per (workspace, created_by)
+    pub async fn find_by_name(
         pool: &sqlx::PgPool,
+        workspace: &str,
+        created_by: &str,
+        name: &str,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
+                   container_id, persistent_volume_id, created_by, parent_session_id,
+                   created_at, started_at, last_activity_at, terminated_at,
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            FROM sessions
+            WHERE workspace = $1 AND created_by = $2 AND name = $3 AND deleted_at IS NULL
+            "#
+        )
+        .bind(workspace)
+        .bind(created_by)
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        conn: &mut sqlx::PgConnection,
         req: CreateSessionRequest,
         created_by: String,
     ) -> Result<Session, sqlx::Error> {
+        let waiting_timeout_seconds = match req.waiting_timeout_seconds {
+            Some(timeout) => timeout,
+            None => WorkspaceSettings::waiting_timeout_for(&mut *conn, &req.workspace).await?,
+        };
+
         let session = sqlx::query_as::<_, Session>(
             r#"
-            INSERT INTO sessions (name, workspace, starting_prompt, waiting_timeout_seconds, created_by, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO sessions (name, workspace, starting_prompt, waiting_timeout_seconds, created_by, metadata, node_selector)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
                       container_id, persistent_volume_id, created_by, parent_session_id,
                       created_at, started_at, last_activity_at, terminated_at,
-                      termination_reason, metadata, deleted_at
+                      termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
             "#
         )
         .bind(&req.name)
         .bind(&req.workspace)
         .bind(&req.starting_prompt)
-        .bind(req.waiting_timeout_seconds)
+        .bind(waiting_timeout_seconds)
         .bind(&created_by)
         .bind(&req.metadata)
-        .fetch_one(pool)
+        .bind(&req.node_selector)
+        .fetch_one(&mut *conn)
         .await?;
 
         // Assign agents if provided
         if !req.agent_ids.is_empty() {
-            Self::assign_agents(pool, session.id, &req.agent_ids).await?;
+            Self::assign_agents(&mut *conn, session.id, &req.agent_ids).await?;
         }
 
         Ok(session)
@@ -263,22 +444,23 @@ impl Session {
             r#"
             INSERT INTO sessions (
                 name, workspace, starting_prompt, waiting_timeout_seconds, 
-                created_by, parent_session_id, metadata
+                created_by, parent_session_id, metadata, node_selector
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
                       container_id, persistent_volume_id, created_by, parent_session_id,
                       created_at, started_at, last_activity_at, terminated_at,
-                      termination_reason, metadata, deleted_at
+                      termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
             "#
         )
         .bind(&req.name)
         .bind(&parent.workspace) // Inherit workspace from parent
         .bind(req.starting_prompt.as_ref().unwrap_or(&parent.starting_prompt))
-        .bind(req.waiting_timeout_seconds.unwrap_or(parent.waiting_timeout_seconds.unwrap_or(300)))
+        .bind(req.waiting_timeout_seconds.unwrap_or(parent.waiting_timeout_seconds.unwrap_or(DEFAULT_WAITING_TIMEOUT_SECONDS)))
         .bind(&created_by)
         .bind(parent_id)
         .bind(req.metadata.as_ref().unwrap_or(&parent.metadata))
+        .bind(&parent.node_selector) // Stay on the parent's node
         .fetch_one(pool)
         .await?;
 
@@ -299,6 +481,7 @@ impl Session {
         pool: &sqlx::PgPool,
         id: Uuid,
         req: UpdateSessionStateRequest,
+        updated_by: &str,
     ) -> Result<Option<Session>, sqlx::Error> {
         // Check current state and validate transition
         let current = Self::find_by_id(pool, id).await?;
@@ -326,6 +509,9 @@ impl Session {
         if req.state == SessionState::Error {
             param_count += 1;
             query_builder.push_str(&format!(", terminated_at = ${}", param_count));
+            param_count += 1;
+            query_builder.push_str(&format!(", terminated_by = ${}", param_count));
+            query_builder.push_str(", termination_cause = 'manual'");
             if req.termination_reason.is_some() {
                 param_count += 1;
                 query_builder.push_str(&format!(", termination_reason = ${}", param_count));
@@ -345,7 +531,7 @@ impl Session {
         query_builder.push_str(" WHERE id = $");
         param_count += 1;
         query_builder.push_str(&param_count.to_string());
-        query_builder.push_str(" RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, metadata, deleted_at");
+        query_builder.push_str(" RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at");
 
         // Build and execute query
         let mut query = sqlx::query_as::<_, Session>(&query_builder)
@@ -357,7 +543,7 @@ impl Session {
         }
 
         if req.state == SessionState::Error {
-            query = query.bind(now);
+            query = query.bind(now).bind(updated_by);
             if let Some(reason) = req.termination_reason {
                 query = query.bind(reason);
             }
@@ -373,7 +559,52 @@ impl Session {
 
         query = query.bind(id);
 
-        query.fetch_optional(pool).await
+        // The state history trigger attributes the transition to the transaction's actor
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT set_config('raworc.actor', $1, true)")
+            .bind(updated_by)
+            .execute(&mut *tx)
+            .await?;
+        let session = query.fetch_optional(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(session)
+    }
+
+    /// State and activity for status polling, with when the state last changed
+    pub async fn find_status(pool: &sqlx::PgPool, id: Uuid) -> Result<Option<SessionStatus>, sqlx::Error> {
+        sqlx::query_as::<_, SessionStatus>(
+            r#"
+            SELECT s.created_by, s.state, s.last_activity_at, s.container_id,
+                   COALESCE(
+                       (SELECT h.changed_at FROM session_state_history h
+                        WHERE h.session_id = s.id
+                        ORDER BY h.id DESC
+                        LIMIT 1),
+                       s.created_at
+                   ) AS updated_at
+            FROM sessions s
+            WHERE s.id = $1 AND s.deleted_at IS NULL
+            "#
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The session's state transitions, oldest first
+    pub async fn state_history(pool: &sqlx::PgPool, id: Uuid) -> Result<Vec<SessionStateChange>, sqlx::Error> {
+        sqlx::query_as::<_, SessionStateChange>(
+            r#"
+            SELECT from_state, to_state, reason, changed_by, changed_at
+            FROM session_state_history
+            WHERE session_id = $1
+            ORDER BY id
+            "#
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
     }
 
     pub async fn update(
@@ -409,7 +640,7 @@ impl Session {
         param_count += 1;
         query_builder.push_str(&param_count.to_string());
         query_builder.push_str(" AND deleted_at IS NULL");
-        query_builder.push_str(" RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, metadata, deleted_at");
+        query_builder.push_str(" RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at");
 
         let mut query = sqlx::query_as::<_, Session>(&query_builder);
 
@@ -430,9 +661,31 @@ impl Session {
         query.fetch_optional(pool).await
     }
 
-    pub async fn delete(pool: &sqlx::PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    pub async fn transfer_owner(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        new_owner: &str,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions SET created_by = $2
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
+                      container_id, persistent_volume_id, created_by, parent_session_id,
+                      created_at, started_at, last_activity_at, terminated_at,
+                      termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            "#
+        )
+        .bind(id)
+        .bind(new_owner)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record activity without changing state, deferring the idle timeout
+    pub async fn touch_activity(pool: &sqlx::PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query(
-            "UPDATE sessions SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1 AND deleted_at IS NULL"
+            "UPDATE sessions SET last_activity_at = CURRENT_TIMESTAMP WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
         .execute(pool)
@@ -441,6 +694,224 @@ impl Session {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Replace the session's host token, revoking the one its previous container used
+    pub async fn set_host_token_hash(pool: &sqlx::PgPool, id: Uuid, token_hash: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET host_token_hash = $2 WHERE id = $1")
+            .bind(id)
+            .bind(token_hash)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Hash of the token the session's host authenticates with; None for deleted sessions
+    /// and sessions that never had a container
+    pub async fn host_token_hash(pool: &sqlx::PgPool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, Option<String>>(
+            "SELECT host_token_hash FROM sessions WHERE id = $1 AND deleted_at IS NULL"
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+    }
+
+    /// Interrupt a BUSY session: move it back to READY and post the SYSTEM message that
+    /// tells its host to abort the current operation. None when the session isn't BUSY.
+    pub async fn cancel(pool: &sqlx::PgPool, id: Uuid, cancelled_by: &str) -> Result<Option<Session>, sqlx::Error> {
+        let reason = format!("Operation cancelled by {}", cancelled_by);
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT set_config('raworc.actor', $1, true), set_config('raworc.reason', $2, true)")
+            .bind(cancelled_by)
+            .bind(&reason)
+            .execute(&mut *tx)
+            .await?;
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions
+            SET state = 'READY', last_activity_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND state = 'BUSY' AND deleted_at IS NULL
+            RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if session.is_some() {
+            let request = CreateMessageRequest {
+                role: MessageRole::System,
+                content: reason,
+                agent_id: None,
+                metadata: serde_json::json!({
+                    "type": CANCEL_REQUEST_TYPE,
+                    "cancelled_by": cancelled_by
+                }),
+            };
+            SessionMessage::insert(&mut tx, id, request, None).await?;
+        }
+
+        tx.commit().await?;
+        Ok(session)
+    }
+
+    /// Pause a READY or BUSY session: move it to IDLE and queue the stop of its container.
+    /// None when the session is in another state.
+    pub async fn pause(pool: &sqlx::PgPool, id: Uuid, paused_by: &str) -> Result<Option<Session>, sqlx::Error> {
+        let reason = format!("Paused by {}", paused_by);
+        let stop = Transition {
+            from: &["READY", "BUSY"],
+            to: SessionState::Idle,
+            cause: Some(TerminationCause::Manual),
+            task: TaskPayload::StopSession {},
+        };
+        Self::transition_with_task(pool, id, stop, paused_by, &reason).await
+    }
+
+    /// Idle a READY session whose waiting timeout passed and queue the stop of its container.
+    /// None when the session is no longer READY, e.g. a message arrived since it was selected.
+    pub async fn expire_waiting(pool: &sqlx::PgPool, id: Uuid, expired_by: &str) -> Result<Option<Session>, sqlx::Error> {
+        let reason = format!("Waiting timeout expired; reaped by {}", expired_by);
+        let stop = Transition {
+            from: &["READY"],
+            to: SessionState::Idle,
+            cause: Some(TerminationCause::IdleTimeout),
+            task: TaskPayload::StopSession {},
+        };
+        Self::transition_with_task(pool, id, stop, expired_by, &reason).await
+    }
+
+    /// Wake an IDLE session: move it to INIT and queue the restart of its container. The operator
+    /// moves it to READY once the container runs. None when the session isn't IDLE.
+    /// Clears `terminated_at` and the termination cause, which stopping the container had set.
+    pub async fn reactivate(pool: &sqlx::PgPool, id: Uuid, reactivated_by: &str, reason: &str) -> Result<Option<Session>, sqlx::Error> {
+        let wake = Transition {
+            from: &["IDLE"],
+            to: SessionState::Init,
+            cause: None,
+            task: TaskPayload::ReactivateSession {},
+        };
+        Self::transition_with_task(pool, id, wake, reactivated_by, reason).await
+    }
+
+    /// Apply `transition` and queue its task in the same transaction. A transition with a cause
+    /// records it with `reason` as the termination detail. Moving to INIT starts a new container,
+    /// so the previous one's `terminated_at` and termination cause no longer apply.
+    async fn transition_with_task(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        transition: Transition,
+        actor: &str,
+        reason: &str,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        let Transition { from, to, cause, task } = transition;
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT set_config('raworc.actor', $1, true), set_config('raworc.reason', $2, true)")
+            .bind(actor)
+            .bind(reason)
+            .execute(&mut *tx)
+            .await?;
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions
+            SET state = $2, last_activity_at = CURRENT_TIMESTAMP,
+                terminated_at = CASE WHEN $2 = 'INIT' THEN NULL ELSE terminated_at END,
+                termination_cause = CASE WHEN $2 = 'INIT' THEN NULL ELSE COALESCE($4, termination_cause) END,
+                termination_reason = CASE
+                    WHEN $2 = 'INIT' THEN NULL
+                    WHEN $4 IS NOT NULL THEN $5
+                    ELSE termination_reason
+                END
+            WHERE id = $1 AND state::text = ANY($3) AND deleted_at IS NULL
+            RETURNING id, name, workspace, starting_prompt, state, waiting_timeout_seconds, container_id, persistent_volume_id, created_by, parent_session_id, created_at, started_at, last_activity_at, terminated_at, termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            "#
+        )
+        .bind(id)
+        .bind(to)
+        .bind(from)
+        .bind(cause)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if session.is_some() {
+            Self::enqueue_task(&mut *tx, id, task).await?;
+        }
+
+        tx.commit().await?;
+        Ok(session)
+    }
+
+    pub async fn delete<'e, E: sqlx::PgExecutor<'e>>(executor: E, id: Uuid, deleted_by: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET deleted_at = CURRENT_TIMESTAMP,
+                terminated_by = $2,
+                termination_cause = COALESCE(termination_cause, 'deleted')
+            WHERE id = $1 AND deleted_at IS NULL
+            "#
+        )
+        .bind(id)
+        .bind(deleted_by)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Containers running or being started: READY/BUSY sessions plus create and reactivate
+    /// tasks in flight, whose sessions stay INIT until their container runs
+    pub async fn count_running_containers<'e, E: sqlx::PgExecutor<'e>>(executor: E) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM sessions
+                 WHERE state IN ('READY', 'BUSY') AND deleted_at IS NULL)
+              + (SELECT COUNT(*) FROM session_tasks
+                 WHERE task_type IN ('create_session', 'reactivate_session') AND status = 'processing')
+            "#,
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Create and reactivate tasks waiting in the operator queue for container capacity
+    pub async fn count_queued_starts<'e, E: sqlx::PgExecutor<'e>>(executor: E) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM session_tasks WHERE task_type IN ('create_session', 'reactivate_session') AND status = 'pending'"
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// 1-based position of the session's pending create or reactivate task in the operator
+    /// queue, or None when the session isn't waiting for a container
+    pub async fn start_queue_position(pool: &sqlx::PgPool, session_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar::<_, Option<i64>>(
+            r#"
+            WITH own AS (
+                SELECT MIN(created_at) AS created_at
+                FROM session_tasks
+                WHERE session_id = $1 AND task_type IN ('create_session', 'reactivate_session') AND status = 'pending'
+            )
+            SELECT CASE WHEN own.created_at IS NULL THEN NULL ELSE (
+                SELECT COUNT(*)
+                FROM session_tasks t
+                WHERE t.task_type IN ('create_session', 'reactivate_session') AND t.status = 'pending'
+                  AND t.created_at <= own.created_at
+            ) END
+            FROM own
+            "#
+        )
+        .bind(session_id)
+        .fetch_one(pool)
+        .await
+    }
+
     pub async fn get_agents(pool: &sqlx::PgPool, session_id: Uuid) -> Result<Vec<crate::shared::models::Agent>, sqlx::Error> {
         sqlx::query_as::<_, crate::shared::models::Agent>(
             r#"
@@ -458,19 +929,84 @@ impl Session {
         .await
     }
 
-    async fn assign_agents(pool: &sqlx::PgPool, session_id: Uuid, agent_ids: &[Uuid]) -> Result<(), sqlx::Error> {
-        for agent_id in agent_ids {
-            sqlx::query(
-                "INSERT INTO session_agents (session_id, agent_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
-            )
+    /// The session's agent attachments with their per-session configuration, oldest first
+    pub async fn get_agent_links(pool: &sqlx::PgPool, session_id: Uuid) -> Result<Vec<SessionAgent>, sqlx::Error> {
+        sqlx::query_as::<_, SessionAgent>(
+            r#"
+            SELECT session_id, agent_id, assigned_at, configuration
+            FROM session_agents
+            WHERE session_id = $1
+            ORDER BY assigned_at
+            "#
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn set_agent_configuration(
+        pool: &sqlx::PgPool,
+        session_id: Uuid,
+        agent_id: Uuid,
+        configuration: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE session_agents SET configuration = $3 WHERE session_id = $1 AND agent_id = $2")
             .bind(session_id)
             .bind(agent_id)
+            .bind(configuration)
             .execute(pool)
             .await?;
-        }
         Ok(())
     }
 
+    pub async fn assign_agents<'e, E: sqlx::PgExecutor<'e>>(executor: E, session_id: Uuid, agent_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_agents (session_id, agent_id)
+            SELECT $1, agent_id FROM UNNEST($2::uuid[]) AS agent_id
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(session_id)
+        .bind(agent_ids)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue a task for the operator
+    pub async fn enqueue_task<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        session_id: Uuid,
+        task: crate::shared::models::TaskPayload,
+    ) -> Result<(), sqlx::Error> {
+        let (task_type, payload) = task.into_columns();
+        sqlx::query(
+            r#"
+            INSERT INTO session_tasks (session_id, task_type, payload, status)
+            VALUES ($1, $2, $3, 'pending')
+            "#
+        )
+        .bind(session_id)
+        .bind(task_type)
+        .bind(payload)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn unassign_agent(pool: &sqlx::PgPool, session_id: Uuid, agent_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM session_agents WHERE session_id = $1 AND agent_id = $2"
+        )
+        .bind(session_id)
+        .bind(agent_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     async fn copy_agents_from_parent(pool: &sqlx::PgPool, session_id: Uuid, parent_id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -487,23 +1023,182 @@ impl Session {
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Live sessions that own a container or are about to: INIT, READY and BUSY,
+    /// plus IDLE sessions, whose stopped container is kept for reactivation. Sessions whose
+    /// container another node created are left out, since it isn't in this node's Docker.
+    pub async fn find_expecting_container(pool: &sqlx::PgPool, node_name: Option<&str>) -> Result<Vec<Session>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
+                   container_id, persistent_volume_id, created_by, parent_session_id,
+                   created_at, started_at, last_activity_at, terminated_at,
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
+            FROM sessions
+            WHERE state IN ('INIT', 'READY', 'BUSY', 'IDLE')
+              AND deleted_at IS NULL
+              AND (container_id IS NULL OR node_name IS NOT DISTINCT FROM $1)
+            "#
+        )
+        .bind(node_name)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Node whose operator created the session's container, where its logs and shell are;
+    /// None for an operator without a node name or a session without a container
+    pub async fn container_node(pool: &sqlx::PgPool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, Option<String>>("SELECT node_name FROM sessions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map(Option::flatten)
+    }
+
+    /// READY sessions inactive for longer than their waiting timeout.
+    /// A missing, zero or negative timeout means the session never times out.
     pub async fn find_waiting_sessions_to_timeout(pool: &sqlx::PgPool) -> Result<Vec<Session>, sqlx::Error> {
         sqlx::query_as::<_, Session>(
             r#"
-            SELECT id, name, starting_prompt, state, waiting_timeout_seconds,
+            SELECT id, name, workspace, starting_prompt, state, waiting_timeout_seconds,
                    container_id, persistent_volume_id, created_by, parent_session_id,
                    created_at, started_at, last_activity_at, terminated_at,
-                   termination_reason, metadata, deleted_at
+                   termination_reason, termination_cause, terminated_by, metadata, deleted_at, node_selector, updated_at
             FROM sessions
             WHERE state = 'READY'
-              AND waiting_timeout_seconds IS NOT NULL
+              AND waiting_timeout_seconds > 0
               AND last_activity_at IS NOT NULL
-              AND last_activity_at + (waiting_timeout_seconds || ' seconds')::interval < NOW()
+              AND last_activity_at + make_interval(secs => waiting_timeout_seconds) < NOW()
               AND deleted_at IS NULL
             "#
         )
         .fetch_all(pool)
         .await
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{Session, SessionState, TerminationCause};
+    use crate::server::rest::test_support::TestApp;
+
+    async fn ready_session(app: &TestApp, idle_secs: i32, timeout_secs: i32) -> Uuid {
+        let id = app.create_session("timeout-test").await;
+        sqlx::query(
+            "UPDATE sessions SET state = 'READY', waiting_timeout_seconds = $2, last_activity_at = NOW() - make_interval(secs => $3) WHERE id = $1",
+        )
+        .bind(id)
+        .bind(timeout_secs)
+        .bind(idle_secs as f64)
+        .execute(&*app.state.db)
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn finds_ready_session_past_its_waiting_timeout() {
+        let app = TestApp::new().await;
+        let timed_out = ready_session(&app, 120, 60).await;
+
+        let sessions = Session::find_waiting_sessions_to_timeout(&app.state.db).await.unwrap();
+        let session = sessions.iter().find(|s| s.id == timed_out).expect("timed-out session is returned");
+        assert_eq!(session.workspace, "default");
+        assert_eq!(session.created_by, "timeout-test");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn skips_sessions_within_or_without_a_waiting_timeout() {
+        let app = TestApp::new().await;
+        let waiting = ready_session(&app, 30, 60).await;
+        let no_timeout = ready_session(&app, 3600, 0).await;
+
+        let sessions = Session::find_waiting_sessions_to_timeout(&app.state.db).await.unwrap();
+        assert!(!sessions.iter().any(|s| s.id == waiting || s.id == no_timeout));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn idle_timeout_stop_records_the_idle_cause() {
+        let app = TestApp::new().await;
+        let id = ready_session(&app, 120, 60).await;
+
+        let session = Session::expire_waiting(&app.state.db, id, "reaper").await.unwrap().expect("READY session is idled");
+        assert_eq!(session.state, SessionState::Idle);
+        assert_eq!(session.termination_cause, Some(TerminationCause::IdleTimeout));
+        assert!(session.termination_reason.unwrap().starts_with("Waiting timeout expired"));
+
+        // Waking it starts a new container, which hasn't ended
+        let session = Session::reactivate(&app.state.db, id, "owner", "Woken").await.unwrap().unwrap();
+        assert_eq!(session.termination_cause, None);
+        assert_eq!(session.termination_reason, None);
+    }
+
+    /// Query plan of `query` as text
+    async fn explain(conn: &mut sqlx::PgConnection, query: &str) -> String {
+        let lines: Vec<String> = sqlx::query_scalar(&format!("EXPLAIN {}", query))
+            .fetch_all(&mut *conn)
+            .await
+            .unwrap();
+        lines.join("\n")
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn task_polls_and_idle_scans_use_their_indexes() {
+        let app = TestApp::new().await;
+        // The rows are rolled back, so other tests never see them
+        let mut tx = app.state.db.begin().await.unwrap();
+
+        // Mostly finished sessions and tasks, as in a long-running deployment
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (name, workspace, starting_prompt, created_by, state, deleted_at)
+            SELECT 'plan-' || n, 'default', 'test', 'plan-test',
+                   CASE WHEN n % 100 = 0 THEN 'READY' ELSE 'IDLE' END::session_state,
+                   CASE WHEN n % 3 = 0 THEN NOW() END
+            FROM generate_series(1, 5000) AS n
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO session_tasks (task_type, session_id, status, created_at)
+            SELECT 'destroy_session', id, CASE WHEN random() < 0.01 THEN 'pending' ELSE 'completed' END,
+                   NOW() - random() * INTERVAL '30 days'
+            FROM sessions, generate_series(1, 4)
+            WHERE created_by = 'plan-test'
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query("ANALYZE sessions, session_tasks").execute(&mut *tx).await.unwrap();
+
+        let task_poll = explain(
+            &mut tx,
+            "SELECT id FROM session_tasks WHERE status = 'pending' ORDER BY created_at LIMIT 10",
+        )
+        .await;
+        assert!(!task_poll.contains("Seq Scan"), "{}", task_poll);
+        assert!(task_poll.contains("idx_session_tasks_pending_created_at"), "{}", task_poll);
+
+        let idle_scan = explain(
+            &mut tx,
+            r#"SELECT id FROM sessions
+               WHERE state = 'READY' AND waiting_timeout_seconds > 0 AND last_activity_at IS NOT NULL
+                 AND last_activity_at + make_interval(secs => waiting_timeout_seconds) < NOW()
+                 AND deleted_at IS NULL"#,
+        )
+        .await;
+        assert!(!idle_scan.contains("Seq Scan"), "{}", idle_scan);
+        assert!(idle_scan.contains("idx_sessions_state_last_activity"), "{}", idle_scan);
+
+        tx.rollback().await.unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Values of `session_tasks.status`
+pub const TASK_STATUSES: &[&str] = &["pending", "processing", "completed", "failed"];
+
+/// A queued operator task, stored as `session_tasks.task_type` plus the `payload` JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "task_type", content = "payload", rename_all = "snake_case")]
+pub enum TaskPayload {
+    CreateSession {
+        user_id: String,
+        #[serde(default)]
+        agent_ids: Vec<Uuid>,
+    },
+    DestroySession {},
+    /// Stop the container of a session that went IDLE, keeping it for reactivation
+    StopSession {},
+    /// Restart the container of a session leaving IDLE, recreating it if it is gone
+    ReactivateSession {},
+    ExecuteCommand { command: String },
+}
+
+impl TaskPayload {
+    /// Decode a task row's type and payload, naming the task type when the payload doesn't match it
+    pub fn parse(task_type: &str, payload: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(serde_json::json!({
+            "task_type": task_type,
+            "payload": payload,
+        }))
+    }
+
+    /// Split into the `task_type` and `payload` columns
+    pub fn into_columns(self) -> (String, serde_json::Value) {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        let task_type = value["task_type"].as_str().unwrap_or_default().to_string();
+        let payload = value["payload"].take();
+        (task_type, payload)
+    }
+}
+
+/// A queued or finished operator task, without its payload
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SessionTaskRecord {
+    pub id: Uuid,
+    pub task_type: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl SessionTaskRecord {
+    /// A session's tasks, oldest first, optionally narrowed to one status and task type
+    pub async fn find_by_session(
+        pool: &sqlx::PgPool,
+        session_id: Uuid,
+        status: Option<&str>,
+        task_type: Option<&str>,
+    ) -> Result<Vec<SessionTaskRecord>, sqlx::Error> {
+        sqlx::query_as::<_, SessionTaskRecord>(
+            r#"
+            SELECT id, task_type, status, error, created_at, updated_at, started_at, completed_at
+            FROM session_tasks
+            WHERE session_id = $1
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::text IS NULL OR task_type = $3)
+            ORDER BY created_at, id
+            "#
+        )
+        .bind(session_id)
+        .bind(status)
+        .bind(task_type)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn every_task_type_decodes_from_its_columns() {
+        let agent_id = Uuid::new_v4();
+        let cases = [
+            (
+                "create_session",
+                json!({"user_id": "alice", "agent_ids": [agent_id]}),
+                TaskPayload::CreateSession { user_id: "alice".to_string(), agent_ids: vec![agent_id] },
+            ),
+            ("destroy_session", json!({}), TaskPayload::DestroySession {}),
+            ("stop_session", json!({}), TaskPayload::StopSession {}),
+            ("reactivate_session", json!({}), TaskPayload::ReactivateSession {}),
+            (
+                "execute_command",
+                json!({"command": "ls -la"}),
+                TaskPayload::ExecuteCommand { command: "ls -la".to_string() },
+            ),
+        ];
+
+        for (task_type, payload, expected) in cases {
+            assert_eq!(TaskPayload::parse(task_type, &payload).unwrap(), expected);
+            assert_eq!(expected.into_columns(), (task_type.to_string(), payload));
+        }
+
+        // Rows queued before agent_ids was added
+        let payload = TaskPayload::parse("create_session", &json!({"user_id": "alice"})).unwrap();
+        assert_eq!(payload, TaskPayload::CreateSession { user_id: "alice".to_string(), agent_ids: Vec::new() });
+    }
+
+    #[test]
+    fn malformed_payloads_name_what_is_wrong() {
+        let error = TaskPayload::parse("execute_command", &json!({"cmd": "ls"})).unwrap_err();
+        assert!(error.to_string().contains("missing field `command`"), "{}", error);
+
+        let error = TaskPayload::parse("create_session", &json!({"user_id": "alice", "agent_ids": ["not-a-uuid"]})).unwrap_err();
+        assert!(error.to_string().contains("UUID"), "{}", error);
+
+        let error = TaskPayload::parse("reboot_session", &json!({})).unwrap_err();
+        assert!(error.to_string().contains("unknown variant `reboot_session`"), "{}", error);
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::model_name;
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Validate)]
+pub struct RecordUsageRequest {
+    #[validate(range(min = 0, message = "must not be negative"))]
+    pub prompt_tokens: i64,
+    #[validate(range(min = 0, message = "must not be negative"))]
+    pub completion_tokens: i64,
+    #[validate(custom(function = "model_name"), length(max = 100, message = "must be at most 100 characters"))]
+    pub model: Option<String>,
+    pub agent_id: Option<Uuid>,
+    /// Estimated cost of the turn in US dollars
+    #[validate(range(min = 0.0, message = "must not be negative"))]
+    pub cost_usd: Option<f64>,
+}
+
+/// Token usage of a session, summed over every turn the host agent reported
+#[derive(Debug, Clone, FromRow)]
+pub struct SessionUsage {
+    pub turns: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    /// Sum of the turns that reported a cost; None when none did
+    pub cost_usd: Option<f64>,
+    pub last_recorded_at: Option<DateTime<Utc>>,
+}
+
+// Database operations
+impl SessionUsage {
+    /// Store the usage of one LLM turn
+    pub async fn record(pool: &sqlx::PgPool, session_id: Uuid, req: RecordUsageRequest) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_usage (session_id, agent_id, model, prompt_tokens, completion_tokens, cost_usd)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(session_id)
+        .bind(req.agent_id)
+        .bind(req.model)
+        .bind(req.prompt_tokens)
+        .bind(req.completion_tokens)
+        .bind(req.cost_usd)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn for_session(pool: &sqlx::PgPool, session_id: Uuid) -> Result<SessionUsage, sqlx::Error> {
+        sqlx::query_as::<_, SessionUsage>(
+            r#"
+            SELECT COUNT(*) AS turns,
+                   COALESCE(SUM(prompt_tokens), 0)::BIGINT AS prompt_tokens,
+                   COALESCE(SUM(completion_tokens), 0)::BIGINT AS completion_tokens,
+                   SUM(cost_usd) AS cost_usd,
+                   MAX(created_at) AS last_recorded_at
+            FROM session_usage
+            WHERE session_id = $1
+            "#
+        )
+        .bind(session_id)
+        .fetch_one(pool)
+        .await
+    }
+}
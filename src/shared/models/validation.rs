@@ -0,0 +1,81 @@
+use validator::{ValidationError, ValidationErrors};
+
+/// Reject empty or whitespace-only strings
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("blank").with_message("must not be blank".into()));
+    }
+    Ok(())
+}
+
+/// Reject `field` when it is longer than `max_length` characters. The limits are configurable,
+/// so handlers call this next to the derived checks instead of naming it in an attribute.
+pub fn check_length(field: &'static str, value: &str, max_length: usize) -> Result<(), ValidationErrors> {
+    if value.chars().count() > max_length {
+        let mut errors = ValidationErrors::new();
+        errors.add(
+            field,
+            ValidationError::new("length").with_message(format!("must be at most {} characters", max_length).into()),
+        );
+        return Err(errors);
+    }
+    Ok(())
+}
+
+/// Model identifiers such as `claude-3-5-sonnet-latest` or `anthropic/claude-3-haiku`
+pub fn model_name(value: &str) -> Result<(), ValidationError> {
+    let valid = !value.is_empty()
+        && value.len() <= 255
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/'));
+
+    if !valid {
+        return Err(ValidationError::new("model")
+            .with_message("must be a model identifier (letters, digits, '-', '_', '.', ':', '/')".into()));
+    }
+    Ok(())
+}
+
+/// Image references such as `alpine`, `ghcr.io/org/image:1.2` or `image@sha256:...`
+pub fn image_reference(value: &str) -> Result<(), ValidationError> {
+    let valid = !value.is_empty()
+        && value.len() <= 255
+        && !value.starts_with('-')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/' | '@'));
+
+    if !valid {
+        return Err(ValidationError::new("image")
+            .with_message("must be an image reference (letters, digits, '-', '_', '.', ':', '/', '@')".into()));
+    }
+    Ok(())
+}
+
+/// Node names as set by `RAWORC_NODE_NAME`: 1-63 characters of a-z, 0-9, '-' or '_'
+pub fn node_name(value: &str) -> Result<(), ValidationError> {
+    if !is_valid_node_name(value) {
+        return Err(ValidationError::new("node_name")
+            .with_message("must be 1-63 characters of a-z, 0-9, '-' or '_'".into()));
+    }
+    Ok(())
+}
+
+pub fn is_valid_node_name(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 63
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Upper-case environment variable names such as `GITHUB_TOKEN`
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_uppercase() || first == '_' => {}
+        _ => return false,
+    }
+    name.len() <= 255 && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// Idle timeout applied to sessions when neither the request nor the workspace sets one
+pub const DEFAULT_WAITING_TIMEOUT_SECONDS: i32 = 300; // 5 minutes
+
+/// Maximum length of a workspace name, matching a DNS label
+pub const MAX_WORKSPACE_NAME_LEN: usize = 63;
+
+/// Lower-case a workspace name and check it against `[a-z0-9][a-z0-9-]{0,62}`.
+/// Returns `None` when the name is not valid.
+pub fn normalize_workspace_name(name: &str) -> Option<String> {
+    let normalized = name.trim().to_ascii_lowercase();
+    let mut chars = normalized.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_lowercase() || first.is_ascii_digit() => {}
+        _ => return None,
+    }
+    let valid = normalized.len() <= MAX_WORKSPACE_NAME_LEN
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    valid.then_some(normalized)
+}
+
+/// Variables no session container may receive, whatever the workspace allows;
+/// they change how the agent's binaries are found and loaded
+pub const BUILTIN_DENIED_ENV_VARS: &[&str] = &["LD_PRELOAD", "LD_LIBRARY_PATH", "LD_AUDIT", "PATH"];
+
+/// Prefix of the variables raworc sets on every session container
+pub const RESERVED_ENV_PREFIX: &str = "RAWORC_";
+
+/// First of `names` a session container may not receive: a built-in or reserved
+/// variable, or one listed in `denied`
+pub fn find_denied_env_var<'a>(names: &'a [String], denied: &[String]) -> Option<&'a str> {
+    names
+        .iter()
+        .map(String::as_str)
+        .find(|name| {
+            name.starts_with(RESERVED_ENV_PREFIX)
+                || BUILTIN_DENIED_ENV_VARS.contains(name)
+                || denied.iter().any(|d| d == name)
+        })
+}
+
+/// Customer tier of a workspace, which picks its session containers' default resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "workspace_tier", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceTier {
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl WorkspaceTier {
+    pub const ALL: [WorkspaceTier; 3] = [WorkspaceTier::Free, WorkspaceTier::Pro, WorkspaceTier::Enterprise];
+}
+
+impl fmt::Display for WorkspaceTier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkspaceTier::Free => write!(f, "free"),
+            WorkspaceTier::Pro => write!(f, "pro"),
+            WorkspaceTier::Enterprise => write!(f, "enterprise"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkspaceSettings {
+    pub workspace: String,
+    pub default_waiting_timeout_seconds: Option<i32>,
+    /// Variables denied to session containers in this workspace, on top of the built-in and global lists
+    pub denied_env_vars: Vec<String>,
+    /// None gives session containers the globally configured resources
+    pub tier: Option<WorkspaceTier>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateWorkspaceSettingsRequest {
+    /// Idle timeout for new sessions in this workspace; null falls back to the global default
+    #[serde(default)]
+    pub default_waiting_timeout_seconds: Option<i32>,
+    /// Variables session containers in this workspace may not receive, in addition to the built-in list;
+    /// omitted keeps the current list and `[]` clears it
+    #[serde(default)]
+    pub denied_env_vars: Option<Vec<String>>,
+    /// Tier whose default container resources sessions in this workspace receive; omitted keeps the
+    /// current tier, and a workspace that never had one uses the global limits
+    #[serde(default)]
+    pub tier: Option<WorkspaceTier>,
+}
+
+// Database operations
+impl WorkspaceSettings {
+    pub async fn find<'e, E: sqlx::PgExecutor<'e>>(executor: E, workspace: &str) -> Result<Option<WorkspaceSettings>, sqlx::Error> {
+        sqlx::query_as::<_, WorkspaceSettings>(
+            r#"
+            SELECT workspace, default_waiting_timeout_seconds, denied_env_vars, tier, created_at, updated_at
+            FROM workspace_settings
+            WHERE workspace = $1
+            "#
+        )
+        .bind(workspace)
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &sqlx::PgPool,
+        workspace: &str,
+        req: UpdateWorkspaceSettingsRequest,
+    ) -> Result<WorkspaceSettings, sqlx::Error> {
+        sqlx::query_as::<_, WorkspaceSettings>(
+            r#"
+            INSERT INTO workspace_settings (workspace, default_waiting_timeout_seconds, denied_env_vars, tier)
+            VALUES ($1, $2, COALESCE($3, '{}'), $4)
+            ON CONFLICT (workspace) DO UPDATE
+            SET default_waiting_timeout_seconds = EXCLUDED.default_waiting_timeout_seconds,
+                denied_env_vars = COALESCE($3, workspace_settings.denied_env_vars),
+                tier = COALESCE(EXCLUDED.tier, workspace_settings.tier)
+            RETURNING workspace, default_waiting_timeout_seconds, denied_env_vars, tier, created_at, updated_at
+            "#
+        )
+        .bind(workspace)
+        .bind(req.default_waiting_timeout_seconds)
+        .bind(&req.denied_env_vars)
+        .bind(req.tier)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Idle timeout for a new session in the workspace, falling back to the global default
+    pub async fn waiting_timeout_for<'e, E: sqlx::PgExecutor<'e>>(executor: E, workspace: &str) -> Result<i32, sqlx::Error> {
+        Ok(Self::find(executor, workspace)
+            .await?
+            .and_then(|settings| settings.default_waiting_timeout_seconds)
+            .unwrap_or(DEFAULT_WAITING_TIMEOUT_SECONDS))
+    }
+
+    /// Variables denied to session containers in the workspace: the globally configured
+    /// list plus the workspace's own. The built-in list is applied by `find_denied_env_var`.
+    pub async fn denied_env_vars_for<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        workspace: &str,
+        global: &[String],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut denied = global.to_vec();
+        if let Some(settings) = Self::find(executor, workspace).await? {
+            denied.extend(settings.denied_env_vars);
+        }
+        Ok(denied)
+    }
+
+    /// The workspace's tier, or None when it has no settings or no tier
+    pub async fn tier_for<'e, E: sqlx::PgExecutor<'e>>(executor: E, workspace: &str) -> Result<Option<WorkspaceTier>, sqlx::Error> {
+        Ok(Self::find(executor, workspace).await?.and_then(|settings| settings.tier))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::rest::test_support::{unique, TestApp};
+
+    fn request(denied_env_vars: Option<&[&str]>) -> UpdateWorkspaceSettingsRequest {
+        UpdateWorkspaceSettingsRequest {
+            default_waiting_timeout_seconds: None,
+            denied_env_vars: denied_env_vars.map(|names| names.iter().map(|name| name.to_string()).collect()),
+            tier: None,
+        }
+    }
+
+    #[test]
+    fn workspace_names_are_lowercased_and_checked() {
+        assert_eq!(normalize_workspace_name("default").as_deref(), Some("default"));
+        assert_eq!(normalize_workspace_name(" Team-A ").as_deref(), Some("team-a"));
+        assert_eq!(normalize_workspace_name("0-staging").as_deref(), Some("0-staging"));
+        let longest = "a".repeat(MAX_WORKSPACE_NAME_LEN);
+        assert_eq!(normalize_workspace_name(&longest), Some(longest.clone()));
+
+        for name in ["", "-team", "../evil", "team a", "team_a", "équipe", &format!("{}a", longest)] {
+            assert_eq!(normalize_workspace_name(name), None, "{:?}", name);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database the server may migrate"]
+    async fn omitted_denied_env_vars_are_kept() {
+        let app = TestApp::new().await;
+        let workspace = unique("ws");
+
+        let settings = WorkspaceSettings::upsert(&app.state.db, &workspace, request(None)).await.unwrap();
+        assert!(settings.denied_env_vars.is_empty());
+
+        WorkspaceSettings::upsert(&app.state.db, &workspace, request(Some(&["AWS_SECRET_ACCESS_KEY"]))).await.unwrap();
+        let settings = WorkspaceSettings::upsert(&app.state.db, &workspace, request(None)).await.unwrap();
+        assert_eq!(settings.denied_env_vars, vec!["AWS_SECRET_ACCESS_KEY"]);
+
+        let settings = WorkspaceSettings::upsert(&app.state.db, &workspace, request(Some(&[]))).await.unwrap();
+        assert!(settings.denied_env_vars.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+//! Token-bucket rate limiting of API requests, kept per server process
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::shared::config::RateLimitConfig;
+
+/// Once this many principals are tracked, buckets that have refilled completely are dropped
+const MAX_TRACKED_PRINCIPALS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Outcome of one request against its principal's bucket, reported in `X-RateLimit-*` headers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Bucket size
+    pub limit: u32,
+    /// Requests that can still be made right now
+    pub remaining: u32,
+    /// Seconds until the bucket is full again
+    pub reset_secs: u64,
+    /// Seconds until the next request is allowed; 0 when this one was
+    pub retry_after_secs: u64,
+}
+
+/// In-memory token buckets keyed by principal. Limits are per server process.
+pub struct RateLimiter {
+    refill_per_sec: f64,
+    burst: u32,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            refill_per_sec: f64::from(config.requests_per_minute) / 60.0,
+            burst: config.burst,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from `key`'s bucket if one is available
+    pub fn check(&self, key: &str, now: Instant) -> RateLimitDecision {
+        let burst = f64::from(self.burst);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_TRACKED_PRINCIPALS && !buckets.contains_key(key) {
+            let refill_per_sec = self.refill_per_sec;
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.saturating_duration_since(bucket.updated).as_secs_f64() * refill_per_sec < burst
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket { tokens: burst, updated: now });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(burst);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        RateLimitDecision {
+            allowed,
+            limit: self.burst,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs: ((burst - bucket.tokens) / self.refill_per_sec).ceil() as u64,
+            retry_after_secs: if allowed {
+                0
+            } else {
+                ((1.0 - bucket.tokens) / self.refill_per_sec).ceil().max(1.0) as u64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimitDecision, RateLimiter};
+    use crate::shared::config::RateLimitConfig;
+
+    fn limiter() -> RateLimiter {
+        // One token every second, three at once
+        RateLimiter::new(&RateLimitConfig { requests_per_minute: 60, burst: 3 })
+    }
+
+    #[test]
+    fn burst_is_allowed_then_denied_until_a_token_refills() {
+        let limiter = limiter();
+        let start = Instant::now();
+
+        for remaining in [2, 1, 0] {
+            let decision = limiter.check("a", start);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+            assert_eq!(decision.retry_after_secs, 0);
+        }
+        assert_eq!(
+            limiter.check("a", start),
+            RateLimitDecision { allowed: false, limit: 3, remaining: 0, reset_secs: 3, retry_after_secs: 1 }
+        );
+
+        assert!(limiter.check("a", start + Duration::from_secs(1)).allowed);
+        assert!(!limiter.check("a", start + Duration::from_secs(1)).allowed);
+    }
+
+    #[test]
+    fn keys_have_separate_buckets_that_refill_up_to_the_burst() {
+        let limiter = limiter();
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.check("a", start);
+        }
+
+        assert_eq!(limiter.check("b", start).remaining, 2);
+        // Long idle periods don't bank more than the burst
+        let later = limiter.check("a", start + Duration::from_secs(600));
+        assert_eq!(later.remaining, 2);
+        assert_eq!(later.reset_secs, 1);
+    }
+}
@@ -0,0 +1,57 @@
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+/// AES-256-GCM cipher for workspace secrets.
+/// Each value is sealed with a fresh random nonce and bound to its workspace and name,
+/// so a ciphertext copied onto another secret's row fails to decrypt.
+pub struct SecretsCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretsCipher {
+    pub fn new(key_bytes: &[u8]) -> anyhow::Result<Self> {
+        let key = UnboundKey::new(&AES_256_GCM, key_bytes)
+            .map_err(|_| anyhow::anyhow!("Secrets key must be exactly 32 bytes"))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Encrypt a value, returning (nonce, ciphertext with tag)
+    pub fn encrypt(&self, workspace: &str, name: &str, plaintext: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce_bytes)
+            .map_err(|_| anyhow::anyhow!("Failed to generate nonce"))?;
+
+        let mut in_out = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(associated_data(workspace, name)),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt secret"))?;
+
+        Ok((nonce_bytes.to_vec(), in_out))
+    }
+
+    pub fn decrypt(&self, workspace: &str, name: &str, nonce: &[u8], ciphertext: &[u8]) -> anyhow::Result<String> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| anyhow::anyhow!("Invalid nonce for secret '{}'", name))?;
+
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(associated_data(workspace, name)), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt secret '{}' (wrong key?)", name))?;
+
+        Ok(String::from_utf8(plaintext.to_vec())?)
+    }
+}
+
+fn associated_data(workspace: &str, name: &str) -> Vec<u8> {
+    format!("{}/{}", workspace, name).into_bytes()
+}